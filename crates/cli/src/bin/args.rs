@@ -7,18 +7,148 @@
 //! command-line parsing for the `ora` cli component of `oraide`
 
 use {
-    std::path::PathBuf,
+    std::{
+        env,
+        path::PathBuf,
+        str::FromStr,
+    },
     anyhow::{bail, Result},
     pico_args::Arguments,
+    oraide_miniyaml::{
+        Config,
+        IndentStyle,
+        LintConfig,
+        Severity,
+    },
 };
 
 pub(crate) struct Args {
     pub(crate) command: Command,
+
+    /// the project's configuration (if any), with command-line flags merged
+    /// over it
+    pub(crate) config: Config,
 }
 
 pub(crate) enum Command {
     Help,
     CheckSingleFile(PathBuf),
+    CheckMod {
+        manifest_path: PathBuf,
+        format: CheckFormat,
+        is_fixing: bool,
+    },
+    Dump {
+        file_path: PathBuf,
+        format: Format,
+        is_tree: bool,
+    },
+    Convert {
+        file_path: PathBuf,
+        target: ConvertTarget,
+    },
+    Resolve {
+        manifest_path: PathBuf,
+        name: String,
+    },
+    GraphInherits {
+        manifest_path: PathBuf,
+        format: GraphFormat,
+        filter: GraphFilter,
+    },
+    Lsp,
+}
+
+/// how `ora check` reports the findings of lints
+#[derive(Copy, Clone)]
+pub(crate) enum CheckFormat {
+    /// `path:line:col: severity[code]: message` lines
+    Human,
+    Json,
+    Sarif,
+    /// GitHub Actions workflow commands, which annotate files
+    Github,
+}
+
+impl FromStr for CheckFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "human" => Ok(CheckFormat::Human),
+            "json" => Ok(CheckFormat::Json),
+            "sarif" => Ok(CheckFormat::Sarif),
+            "github" => Ok(CheckFormat::Github),
+            other => Err(format!("format {:?} not supported (expected `human`, `json`, `sarif`, or `github`)", other)),
+        }
+    }
+}
+
+/// how `ora graph` renders a graph
+#[derive(Copy, Clone)]
+pub(crate) enum GraphFormat {
+    Dot,
+    Mermaid,
+}
+
+impl FromStr for GraphFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "dot" => Ok(GraphFormat::Dot),
+            "mermaid" => Ok(GraphFormat::Mermaid),
+            other => Err(format!("format {:?} not supported (expected `dot` or `mermaid`)", other)),
+        }
+    }
+}
+
+/// which nodes of a graph `ora graph` renders
+pub(crate) enum GraphFilter {
+    All,
+    AncestorsOf(String),
+    DescendantsOf(String),
+}
+
+/// what `ora convert` converts to (MiniYaml is converted to JSON or YAML, JSON
+/// is converted to MiniYaml)
+#[derive(Copy, Clone)]
+pub(crate) enum ConvertTarget {
+    Json,
+    Yaml,
+    MiniYaml,
+}
+
+impl FromStr for ConvertTarget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(ConvertTarget::Json),
+            "yaml" => Ok(ConvertTarget::Yaml),
+            "miniyaml" => Ok(ConvertTarget::MiniYaml),
+            other => Err(format!("target {:?} not supported (expected `json`, `yaml`, or `miniyaml`)", other)),
+        }
+    }
+}
+
+/// how output meant for other tools is formatted
+#[derive(Copy, Clone)]
+pub(crate) enum Format {
+    Text,
+    Json,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            other => Err(format!("format {:?} not supported (expected `text` or `json`)", other)),
+        }
+    }
 }
 
 impl Args {
@@ -29,6 +159,7 @@ impl Args {
 
         let help = Ok(Self {
             command: Command::Help,
+            config: Config::default(),
         });
 
         let cmd = match matches.subcommand()? {
@@ -40,22 +171,113 @@ impl Args {
             },
         };
 
+        let mut config = match is_user_requesting_help {
+            true => Config::default(),
+            false => Config::discover(&env::current_dir()?)?.unwrap_or_default(),
+        };
+
         let command = match cmd.as_str() {
             "check" => {
                 if is_user_requesting_help {
                     eprintln!("\
 ora check
 
+runs every lint against the files of a mod, or prints the components of every
+line of a single file (as `ora dump` does)
+
 USAGE:
+    ora check [--mod <mod.yaml-path>] [FLAGS] [OPTIONS]
     ora check <file-path-to-check> [FLAGS]
 
 FLAGS:
-    -h, --help        prints help information"
+    -h, --help                  prints help information
+        --fix                   applies the fix of every finding which has
+                                one before reporting the remaining findings
+
+OPTIONS:
+        --mod <mod.yaml-path>   the manifest of the mod to lint (defaults to
+                                `manifest` of oraide.toml)
+        --allow <CODE>          don't run the lint CODE
+        --warn <CODE>           report findings of the lint CODE as warnings
+        --deny <CODE>           report findings of the lint CODE as errors
+        --format <FORMAT>       how findings are reported, one of `human`
+                                (default), `json`, `sarif`, or `github`
+                                (GitHub Actions annotations)"
                     );
 
                     return help;
                 }
 
+                let opt_manifest_path = matches.opt_value_from_str("--mod")?;
+                let opt_format = matches.opt_value_from_str("--format")?;
+                let is_fixing = matches.contains("--fix");
+
+                let lint_flags = [
+                    ("--allow", LintConfig { is_enabled: false, severity: None }),
+                    ("--warn", LintConfig { is_enabled: true, severity: Some(Severity::Warning) }),
+                    ("--deny", LintConfig { is_enabled: true, severity: Some(Severity::Error) }),
+                ];
+
+                for &(flag, lint_config) in &lint_flags {
+                    for code in matches.values_from_str::<_, String>(flag)? {
+                        config.lints.insert(code, lint_config);
+                    }
+                }
+
+                let mut trailing = matches.free()?;
+
+                let manifest_path = match (opt_manifest_path, trailing.len()) {
+                    (Some(manifest_path), 0) => manifest_path,
+                    (None, 1) => {
+                        if opt_format.is_some() {
+                            bail!("--format is only supported when checking a mod (see `ora dump --format` for a single file)");
+                        }
+
+                        if is_fixing {
+                            bail!("--fix is only supported when checking a mod");
+                        }
+
+                        return Ok(Args {
+                            command: Command::CheckSingleFile(trailing.pop().unwrap().into()),
+                            config,
+                        });
+                    },
+                    (None, 0) => configured_manifest_path(&config)?,
+                    _ => bail!("must provide either --mod or a single file-path"),
+                };
+
+                Command::CheckMod {
+                    manifest_path,
+                    format: opt_format.unwrap_or(CheckFormat::Human),
+                    is_fixing,
+                }
+            },
+            "dump" => {
+                if is_user_requesting_help {
+                    eprintln!("\
+ora dump
+
+prints the components of every line of a file (or, with --tree, its nodes),
+with byte offsets and zero-based line/column positions when formatted as json
+
+USAGE:
+    ora dump <file-path-to-dump> [FLAGS] [OPTIONS]
+
+FLAGS:
+    -h, --help           prints help information
+        --tree           prints nested nodes instead of lines
+
+OPTIONS:
+        --format <FORMAT>    one of `text` (default) or `json`"
+                    );
+
+                    return help;
+                }
+
+                let format = matches.opt_value_from_str("--format")?
+                    .unwrap_or(Format::Text);
+                let is_tree = matches.contains("--tree");
+
                 let file_path = {
                     let mut trailing = matches.free()?;
                     if trailing.len() != 1 {
@@ -65,12 +287,184 @@ FLAGS:
                     trailing.pop().unwrap().into()
                 };
 
-                Command::CheckSingleFile(file_path)
+                Command::Dump {
+                    file_path,
+                    format,
+                    is_tree,
+                }
+            },
+            "convert" => {
+                if is_user_requesting_help {
+                    eprintln!("\
+ora convert
+
+converts a MiniYaml file to JSON or YAML, or a JSON file to MiniYaml, writing
+the result to stdout (comments are not preserved)
+
+USAGE:
+    ora convert <file-path-to-convert> --to <TARGET> [FLAGS] [OPTIONS]
+
+FLAGS:
+    -h, --help           prints help information
+
+OPTIONS:
+        --to <TARGET>    one of `json`, `yaml`, or `miniyaml`
+        --indent <INDENT>
+                         `tabs` or a number of spaces, to indent MiniYaml
+                         with (defaults to `format.indent` of oraide.toml)"
+                    );
+
+                    return help;
+                }
+
+                let target = matches.value_from_str("--to")?;
+
+                if let Some(indent) = matches.opt_value_from_str::<_, IndentStyle>("--indent")? {
+                    config.indent = Some(indent);
+                }
+
+                let file_path = {
+                    let mut trailing = matches.free()?;
+                    if trailing.len() != 1 {
+                        bail!("must provide a single file-path");
+                    }
+
+                    trailing.pop().unwrap().into()
+                };
+
+                Command::Convert {
+                    file_path,
+                    target,
+                }
+            },
+            "resolve" => {
+                if is_user_requesting_help {
+                    eprintln!("\
+ora resolve
+
+prints the final definition of a top-level node (e.g. an actor) after merging
+every rules file of a mod, expanding `Inherits`, and applying removals, with
+the file and line each line came from
+
+USAGE:
+    ora resolve [--mod <mod.yaml-path>] <name> [FLAGS]
+
+FLAGS:
+    -h, --help                  prints help information
+
+OPTIONS:
+        --mod <mod.yaml-path>   the manifest of the mod (defaults to
+                                `manifest` of oraide.toml)"
+                    );
+
+                    return help;
+                }
+
+                let manifest_path = match matches.opt_value_from_str("--mod")? {
+                    Some(manifest_path) => manifest_path,
+                    None => configured_manifest_path(&config)?,
+                };
+
+                let name = {
+                    let mut trailing = matches.free()?;
+                    if trailing.len() != 1 {
+                        bail!("must provide a single name to resolve");
+                    }
+
+                    trailing.pop().unwrap()
+                };
+
+                Command::Resolve {
+                    manifest_path,
+                    name,
+                }
+            },
+            "graph" => {
+                let graph_cmd = matches.subcommand()?;
+
+                if is_user_requesting_help || graph_cmd.as_deref() != Some("inherits") {
+                    eprintln!("\
+ora graph inherits
+
+prints the graph of `Inherits` relationships between the top-level nodes of a
+mod, in which abstract nodes are dashed and edges of cycles are red
+
+USAGE:
+    ora graph inherits [--mod <mod.yaml-path>] [FLAGS] [OPTIONS]
+
+FLAGS:
+    -h, --help                  prints help information
+
+OPTIONS:
+        --mod <mod.yaml-path>   the manifest of the mod (defaults to
+                                `manifest` of oraide.toml)
+        --format <FORMAT>       one of `dot` (default) or `mermaid`
+        --ancestors-of <NAME>   only the node NAME and what it inherits from
+        --descendants-of <NAME> only the node NAME and what inherits from it"
+                    );
+
+                    if is_user_requesting_help {
+                        return help;
+                    }
+
+                    bail!("must provide a graph to print (`inherits`)");
+                }
+
+                let manifest_path = match matches.opt_value_from_str("--mod")? {
+                    Some(manifest_path) => manifest_path,
+                    None => configured_manifest_path(&config)?,
+                };
+                let format = matches.opt_value_from_str("--format")?
+                    .unwrap_or(GraphFormat::Dot);
+                let opt_ancestors_of = matches.opt_value_from_str("--ancestors-of")?;
+                let opt_descendants_of = matches.opt_value_from_str("--descendants-of")?;
+                matches.finish()?;
+
+                let filter = match (opt_ancestors_of, opt_descendants_of) {
+                    (None, None) => GraphFilter::All,
+                    (Some(name), None) => GraphFilter::AncestorsOf(name),
+                    (None, Some(name)) => GraphFilter::DescendantsOf(name),
+                    (Some(_), Some(_)) => bail!("--ancestors-of and --descendants-of are mutually exclusive"),
+                };
+
+                Command::GraphInherits {
+                    manifest_path,
+                    format,
+                    filter,
+                }
+            },
+            "lsp" => {
+                if is_user_requesting_help {
+                    eprintln!("\
+ora lsp
+
+serves the Language Server Protocol over stdin/stdout, for use by editors
+
+USAGE:
+    ora lsp [FLAGS]
+
+FLAGS:
+    -h, --help        prints help information"
+                    );
+
+                    return help;
+                }
+
+                matches.finish()?;
+                Command::Lsp
             },
             other => bail!("command {:?} not supported", other),
         };
 
-        Ok(Args { command })
+        Ok(Args { command, config })
+    }
+}
+
+/// the manifest path of `config`, for when `--mod` isn't provided
+fn configured_manifest_path(config: &Config) -> Result<PathBuf> {
+    match &config.manifest_path {
+        Some(manifest_path) => Ok(manifest_path.clone()),
+        None => bail!("must provide --mod <mod.yaml-path> (or `manifest` in oraide.toml)"),
     }
 }
 
@@ -85,6 +479,14 @@ FLAGS:
     -h, --help        prints help information
 
 COMMANDS:
-    check"
+    check
+    convert
+    dump
+    graph
+    lsp
+    resolve
+
+settings are read from the nearest oraide.toml in the current directory or any
+of its ancestors, and flags take precedence over them"
     );
 }
\ No newline at end of file
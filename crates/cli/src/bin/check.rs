@@ -0,0 +1,474 @@
+// oraide - tools for OpenRA-based mod/game development
+// get the source code at https://github.com/Phrohdoh/oraide
+//
+// copyright (c)
+// - 2020 Taryn "Phrohdoh" Hill
+
+//! `ora check --mod`, which runs every lint against the files of a mod
+
+use {
+    std::{
+        fs,
+        path::Path,
+    },
+    serde_json::json,
+    oraide_cli::Result,
+    oraide_miniyaml::{
+        Config,
+        Diagnostic,
+        FileLocation,
+        LineCol,
+        LineIndex,
+        Linter,
+        ModFiles,
+        Severity,
+    },
+    crate::{
+        args::CheckFormat,
+        read_mod_files,
+    },
+};
+
+pub(crate) fn check_mod(
+    manifest_path: &Path,
+    format: CheckFormat,
+    is_fixing: bool,
+    config: &Config,
+) -> Result<()> {
+    let mut linter = Linter::new();
+    for code in config.configure(&mut linter) {
+        eprintln!("warning: `{}` is not the code of any lint", code);
+    }
+
+    let mut files = read_mod_files(manifest_path, config)?;
+
+    if is_fixing {
+        fix_mod(&mut files, &linter)?;
+    }
+
+    let diagnostics = linter.run(&files);
+
+    // grouped by file, in load order
+    let mut located_diagnostics = vec![];
+    for (_, file) in files.iter() {
+        let line_index = LineIndex::new(&file.text);
+
+        for diagnostic in diagnostics.iter().filter(|diagnostic| diagnostic.path == file.path) {
+            let (start, end) = diagnostic.span.into();
+
+            located_diagnostics.push(Located {
+                diagnostic,
+                start: line_index.line_col(&file.text, start),
+                end: line_index.line_col(&file.text, end),
+            });
+        }
+    }
+
+    print!("{}", report_of(&located_diagnostics, format)?);
+
+    let error_count = diagnostics.iter()
+        .filter(|diagnostic| diagnostic.severity == Severity::Error)
+        .count();
+
+    if error_count > 0 {
+        return Err(format!("{} error(s) found", error_count).into());
+    }
+
+    Ok(())
+}
+
+/// apply every fix `linter` finds to `files`, and write each fixed file back
+/// if it was read from disk (the fixes of any other file, e.g. one in a zipped
+/// map package, are undone, so `files` stays what was read)
+///
+/// Files which aren't valid UTF-8 are never fixed (see [`Linter::fix`]).
+///
+/// [`Linter::fix`]: ../oraide_miniyaml/struct.Linter.html#method.fix
+fn fix_mod(files: &mut ModFiles, linter: &Linter) -> Result<()> {
+    let original_files = files.clone();
+    linter.fix(files);
+
+    let mut fixed_file_count = 0;
+    for ((_, original), fixed) in original_files.iter().zip(files.iter_mut()) {
+        if fixed.text == original.text {
+            continue;
+        }
+
+        match &fixed.location {
+            FileLocation::Disk => {},
+            FileLocation::Zip { package, .. } => {
+                eprintln!("warning: {}: not fixed, as it is in the zipped map package {}", fixed.path.display(), package.display());
+                fixed.text = original.text.clone();
+                continue;
+            },
+            FileLocation::Memory => {
+                eprintln!("warning: {}: not fixed, as it wasn't read from disk", fixed.path.display());
+                fixed.text = original.text.clone();
+                continue;
+            },
+        }
+
+        fs::write(&fixed.path, &fixed.text)
+            .map_err(|err| format!("{}: {}", fixed.path.display(), err))?;
+
+        fixed_file_count += 1;
+    }
+
+    eprintln!("fixed {} file(s)", fixed_file_count);
+    Ok(())
+}
+
+/// `located_diagnostics` formatted as `format`
+fn report_of(located_diagnostics: &[Located], format: CheckFormat) -> Result<String> {
+    let report = match format {
+        CheckFormat::Human => located_diagnostics.iter()
+            .map(|located| format!(
+                "{}:{}:{}: {}[{}]: {}\n",
+                located.diagnostic.path.display(),
+                located.start.line + 1,
+                located.start.col + 1,
+                severity_txt(located.diagnostic.severity),
+                located.diagnostic.code,
+                located.diagnostic.message,
+            ))
+            .collect(),
+        CheckFormat::Json => {
+            let json = located_diagnostics.iter()
+                .map(|located| json!({
+                    "path": located.diagnostic.path,
+                    "line": located.start.line + 1,
+                    "column": located.start.col + 1,
+                    "end_line": located.end.line + 1,
+                    "end_column": located.end.col + 1,
+                    "severity": severity_txt(located.diagnostic.severity),
+                    "code": located.diagnostic.code,
+                    "message": located.diagnostic.message,
+                }))
+                .collect::<Vec<_>>();
+
+            serde_json::to_string_pretty(&json)? + "\n"
+        },
+        CheckFormat::Sarif => serde_json::to_string_pretty(&sarif_of(located_diagnostics))? + "\n",
+        CheckFormat::Github => located_diagnostics.iter()
+            .map(|located| {
+                let command = match located.diagnostic.severity {
+                    Severity::Error => "error",
+                    Severity::Warning => "warning",
+                    Severity::Info => "notice",
+                };
+
+                format!(
+                    "::{} file={},line={},col={},endLine={},endColumn={},title={}::{}\n",
+                    command,
+                    escape_github_property(&located.diagnostic.path.to_string_lossy()),
+                    located.start.line + 1,
+                    located.start.col + 1,
+                    located.end.line + 1,
+                    located.end.col + 1,
+                    escape_github_property(located.diagnostic.code),
+                    escape_github_data(&located.diagnostic.message),
+                )
+            })
+            .collect(),
+    };
+
+    Ok(report)
+}
+
+/// a diagnostic and the zero-based positions (with columns in `char`s) of the
+/// start and end of its span
+struct Located<'diagnostic> {
+    diagnostic: &'diagnostic Diagnostic,
+    start: LineCol,
+    end: LineCol,
+}
+
+fn severity_txt(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Info => "info",
+    }
+}
+
+/// a [SARIF] 2.1.0 log of a single run
+///
+/// [SARIF]: https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html
+fn sarif_of(located_diagnostics: &[Located]) -> serde_json::Value {
+    let mut rule_ids = located_diagnostics.iter()
+        .map(|located| located.diagnostic.code)
+        .collect::<Vec<_>>();
+
+    rule_ids.sort_unstable();
+    rule_ids.dedup();
+
+    let results = located_diagnostics.iter()
+        .map(|located| {
+            let level = match located.diagnostic.severity {
+                Severity::Error => "error",
+                Severity::Warning => "warning",
+                Severity::Info => "note",
+            };
+
+            json!({
+                "ruleId": located.diagnostic.code,
+                "level": level,
+                "message": { "text": located.diagnostic.message },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": {
+                            "uri": located.diagnostic.path.to_string_lossy().replace('\\', "/"),
+                        },
+                        "region": {
+                            "startLine": located.start.line + 1,
+                            "startColumn": located.start.col + 1,
+                            "endLine": located.end.line + 1,
+                            "endColumn": located.end.col + 1,
+                        },
+                    },
+                }],
+            })
+        })
+        .collect::<Vec<_>>();
+
+    json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "oraide",
+                    "informationUri": "https://github.com/Phrohdoh/oraide",
+                    "rules": rule_ids.iter()
+                        .map(|rule_id| json!({ "id": rule_id }))
+                        .collect::<Vec<_>>(),
+                },
+            },
+            "columnKind": "unicodeCodePoints",
+            "results": results,
+        }],
+    })
+}
+
+/// escape the message of a GitHub Actions workflow command
+fn escape_github_data(txt: &str) -> String {
+    txt.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// escape a property (e.g. `file`) of a GitHub Actions workflow command
+fn escape_github_property(txt: &str) -> String {
+    escape_github_data(txt)
+        .replace(':', "%3A")
+        .replace(',', "%2C")
+}
+
+// ----- tests -----------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        std::io::Write,
+        oraide_miniyaml::{
+            Fix,
+            MapPackage,
+            MissingSeparator,
+            TextEdit,
+        },
+    };
+
+    fn diagnostics() -> Vec<Diagnostic> {
+        vec![
+            Diagnostic {
+                path: "mods/ra/rules/infantry.yaml".into(),
+                span: (14, 22).into(),
+                severity: Severity::Error,
+                code: "undefined-reference",
+                message: "`^Soldeir` is not a defined actor (did you mean `^Soldier`?)".to_owned(),
+                fix: None,
+            },
+            Diagnostic {
+                path: "mods/ra/weapons, old.yaml".into(),
+                span: (0, 3).into(),
+                severity: Severity::Info,
+                code: "unused-suppression",
+                message: "100% unused\nreally".to_owned(),
+                fix: Some(Fix::new("remove it", TextEdit::new((0, 3), ""))),
+            },
+        ]
+    }
+
+    fn located_of(diagnostics: &[Diagnostic]) -> Vec<Located<'_>> {
+        diagnostics.iter()
+            .zip(&[(1, 10, 1, 18), (0, 0, 0, 3)])
+            .map(|(diagnostic, &(start_line, start_col, end_line, end_col))| Located {
+                diagnostic,
+                start: LineCol { line: start_line, col: start_col },
+                end: LineCol { line: end_line, col: end_col },
+            })
+            .collect()
+    }
+
+    #[test]
+    fn human_report() {
+        // arrange
+        let diagnostics = diagnostics();
+
+        // act
+        let actual = report_of(&located_of(&diagnostics), CheckFormat::Human).unwrap();
+
+        // assert
+        assert_eq!(
+            concat!(
+                "mods/ra/rules/infantry.yaml:2:11: error[undefined-reference]: `^Soldeir` is not a defined actor (did you mean `^Soldier`?)\n",
+                "mods/ra/weapons, old.yaml:1:1: info[unused-suppression]: 100% unused\nreally\n",
+            ),
+            actual,
+        );
+    }
+
+    #[test]
+    fn json_report() {
+        // arrange
+        let diagnostics = diagnostics();
+
+        // act
+        let actual = report_of(&located_of(&diagnostics), CheckFormat::Json).unwrap();
+
+        // assert
+        assert_eq!(
+            json!([
+                {
+                    "path": "mods/ra/rules/infantry.yaml",
+                    "line": 2,
+                    "column": 11,
+                    "end_line": 2,
+                    "end_column": 19,
+                    "severity": "error",
+                    "code": "undefined-reference",
+                    "message": "`^Soldeir` is not a defined actor (did you mean `^Soldier`?)",
+                },
+                {
+                    "path": "mods/ra/weapons, old.yaml",
+                    "line": 1,
+                    "column": 1,
+                    "end_line": 1,
+                    "end_column": 4,
+                    "severity": "info",
+                    "code": "unused-suppression",
+                    "message": "100% unused\nreally",
+                },
+            ]),
+            serde_json::from_str::<serde_json::Value>(&actual).unwrap(),
+        );
+    }
+
+    #[test]
+    fn sarif_report() {
+        // arrange
+        let diagnostics = diagnostics();
+
+        // act
+        let actual = sarif_of(&located_of(&diagnostics));
+
+        // assert
+        let run = &actual["runs"][0];
+        assert_eq!("2.1.0", actual["version"]);
+        assert_eq!(
+            json!([{ "id": "undefined-reference" }, { "id": "unused-suppression" }]),
+            run["tool"]["driver"]["rules"],
+        );
+        assert_eq!(
+            json!({
+                "ruleId": "undefined-reference",
+                "level": "error",
+                "message": { "text": "`^Soldeir` is not a defined actor (did you mean `^Soldier`?)" },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": "mods/ra/rules/infantry.yaml" },
+                        "region": { "startLine": 2, "startColumn": 11, "endLine": 2, "endColumn": 19 },
+                    },
+                }],
+            }),
+            run["results"][0],
+        );
+        assert_eq!("note", run["results"][1]["level"]);
+    }
+
+    #[test]
+    fn github_report() {
+        // arrange
+        let diagnostics = diagnostics();
+
+        // act
+        let actual = report_of(&located_of(&diagnostics), CheckFormat::Github).unwrap();
+
+        // assert
+        assert_eq!(
+            concat!(
+                "::error file=mods/ra/rules/infantry.yaml,line=2,col=11,endLine=2,endColumn=19,title=undefined-reference::`^Soldeir` is not a defined actor (did you mean `^Soldier`?)\n",
+                "::notice file=mods/ra/weapons%2C old.yaml,line=1,col=1,endLine=1,endColumn=4,title=unused-suppression::100%25 unused%0Areally\n",
+            ),
+            actual,
+        );
+    }
+
+    #[test]
+    fn github_properties_are_escaped() {
+        assert_eq!("C%3A\\mods%2Cra%25%0D%0A", escape_github_property("C:\\mods,ra%\r\n"));
+        assert_eq!("a:b,c%25%0A", escape_github_data("a:b,c%\n"));
+    }
+
+    #[test]
+    fn never_writes_zipped_files() {
+        // arrange
+        let dir = tempfile::tempdir().unwrap();
+        let victim_path = dir.path().join("victim.yaml");
+        fs::write(&victim_path, "E1:\n\tHealth\n").unwrap();
+
+        let map_path = dir.path().join("maps/evil.oramap");
+        fs::create_dir(dir.path().join("maps")).unwrap();
+
+        let absolute_name = victim_path.to_str().unwrap();
+        let map_yaml = format!("Rules: rules.yaml, ../victim.yaml, {}\n", absolute_name);
+
+        let entries = [
+            ("map.yaml", map_yaml.as_str()),
+            ("rules.yaml", "E2:\n\tHealth\n"),
+            ("../victim.yaml", "E1:\n\tHealth\n"),
+            (absolute_name, "E1:\n\tHealth\n"),
+        ];
+
+        let mut writer = zip::ZipWriter::new(fs::File::create(&map_path).unwrap());
+        for (name, text) in &entries {
+            writer.start_file(*name, zip::write::FileOptions::default()).unwrap();
+            writer.write_all(text.as_bytes()).unwrap();
+        }
+
+        writer.finish().unwrap();
+
+        let map_package = MapPackage::open(&map_path).unwrap();
+        let mut files = ModFiles {
+            map_overrides: map_package.override_files_of("Rules"),
+            ..ModFiles::default()
+        };
+
+        let mut linter = Linter::empty();
+        linter.add_lint(MissingSeparator);
+
+        // act
+        fix_mod(&mut files, &linter).unwrap();
+
+        // assert
+        // only the package's own file is read, and its fix is undone
+        assert_eq!(
+            vec![(map_path.join("rules.yaml"), "E2:\n\tHealth\n")],
+            files.map_overrides.iter()
+                .map(|file| (file.path.clone(), file.text.as_str()))
+                .collect::<Vec<_>>(),
+        );
+        assert_eq!("E1:\n\tHealth\n", fs::read_to_string(&victim_path).unwrap());
+    }
+}
@@ -0,0 +1,89 @@
+// oraide - tools for OpenRA-based mod/game development
+// get the source code at https://github.com/Phrohdoh/oraide
+//
+// copyright (c)
+// - 2020 Taryn "Phrohdoh" Hill
+
+//! `ora convert`, which converts MiniYaml to JSON / YAML and JSON to MiniYaml
+
+use {
+    std::path::Path,
+    oraide_cli::Result,
+    oraide_miniyaml::{
+        json_of_tree,
+        miniyaml_of_json_indented,
+        span_lines_of,
+        tree_of,
+        yaml_of_tree,
+        IndentStyle,
+    },
+    crate::{
+        args::ConvertTarget,
+        read_doc,
+    },
+};
+
+pub(crate) fn convert(path: &Path, target: ConvertTarget, indent: IndentStyle) -> Result<()> {
+    let doc = read_doc(path)?;
+    print!("{}", converted_of(&doc, target, indent)?);
+
+    Ok(())
+}
+
+// ----- private implementation details ----------------------------------------
+
+/// `doc` converted to `target`
+fn converted_of(doc: &str, target: ConvertTarget, indent: IndentStyle) -> Result<String> {
+    let converted = match target {
+        ConvertTarget::Json | ConvertTarget::Yaml => {
+            let lines = span_lines_of(doc);
+            let tree = tree_of(doc, &lines);
+
+            match target {
+                ConvertTarget::Json => {
+                    let json = json_of_tree(doc, &tree)?;
+                    serde_json::to_string_pretty(&json)? + "\n"
+                },
+                _ => yaml_of_tree(doc, &tree)?,
+            }
+        },
+        ConvertTarget::MiniYaml => {
+            let json = serde_json::from_str(doc)?;
+            miniyaml_of_json_indented(&json, &indent.txt())?
+        },
+    };
+
+    Ok(converted)
+}
+
+// ----- tests -----------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_through_json() {
+        // arrange
+        let doc = "E1:\n\tInherits: ^Soldier\n\tArmament@PRIMARY:\n\t\tWeapon: M1Carbine\n";
+
+        // act
+        let json = converted_of(doc, ConvertTarget::Json, IndentStyle::Tabs).unwrap();
+        let miniyaml = converted_of(&json, ConvertTarget::MiniYaml, IndentStyle::Tabs).unwrap();
+
+        // assert
+        assert_eq!(doc, miniyaml);
+    }
+
+    #[test]
+    fn converts_to_spaces() {
+        // arrange
+        let json = r#"{ "E1": { "Buildable": { "Queue": "Infantry" } } }"#;
+
+        // act
+        let actual = converted_of(json, ConvertTarget::MiniYaml, IndentStyle::Spaces(2)).unwrap();
+
+        // assert
+        assert_eq!("E1:\n  Buildable:\n    Queue: Infantry\n", actual);
+    }
+}
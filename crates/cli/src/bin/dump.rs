@@ -0,0 +1,224 @@
+// oraide - tools for OpenRA-based mod/game development
+// get the source code at https://github.com/Phrohdoh/oraide
+//
+// copyright (c)
+// - 2020 Taryn "Phrohdoh" Hill
+
+//! `ora dump`, which prints the parse of a single file for humans (`text`) or
+//! other tools (`json`)
+
+use {
+    std::path::Path,
+    serde_json::{
+        json,
+        Value,
+    },
+    oraide_cli::Result,
+    oraide_miniyaml::{
+        span_lines_of,
+        tree_of,
+        AbsByteIdxSpan,
+        LineIndex,
+        Node,
+        SpannedLine,
+    },
+    crate::{
+        args::Format,
+        read_doc,
+    },
+};
+
+pub(crate) fn dump(path: &Path, format: Format, is_tree: bool) -> Result<()> {
+    let doc = read_doc(path)?;
+    let lines = span_lines_of(&doc);
+
+    if is_tree {
+        let tree = tree_of(&doc, &lines);
+
+        match format {
+            Format::Text => print_nodes(&doc, &tree.nodes),
+            Format::Json => println!("{}", serde_json::to_string_pretty(&json_of_nodes(&doc, &tree.nodes)?)?),
+        }
+
+        return Ok(());
+    }
+
+    match format {
+        Format::Text => {
+            let map_opt_span_to_txt = |opt_span: Option<AbsByteIdxSpan>| -> Option<&str> {
+                opt_span.map(|span| &doc[span])
+            };
+
+            for line in lines {
+                let raw_txt = &doc[line.raw];
+                let opt_indent_txt = map_opt_span_to_txt(line.indent);
+                let opt_key_txt = map_opt_span_to_txt(line.key);
+                let opt_key_sep_txt = map_opt_span_to_txt(line.key_sep);
+                let opt_value_txt = map_opt_span_to_txt(line.value);
+                let opt_comment_txt = map_opt_span_to_txt(line.comment);
+                let opt_term_txt = map_opt_span_to_txt(line.term);
+
+                println!("raw     = {:?}", raw_txt);
+                println!("indent  = {:?}", opt_indent_txt);
+                println!("key     = {:?}", opt_key_txt);
+                println!("key_sep = {:?}", opt_key_sep_txt);
+                println!("value   = {:?}", opt_value_txt);
+                println!("comment = {:?}", opt_comment_txt);
+                println!("term    = {:?}", opt_term_txt);
+                println!();
+            }
+        },
+        Format::Json => println!("{}", serde_json::to_string_pretty(&json_of_lines(&doc, &lines)?)?),
+    }
+
+    Ok(())
+}
+
+// ----- private implementation details ----------------------------------------
+
+/// the serialized `lines` of `doc`, each with its index, and each of their
+/// spans annotated (see [`annotate_spans`])
+///
+/// [`annotate_spans`]: fn.annotate_spans.html
+fn json_of_lines(doc: &str, lines: &[SpannedLine]) -> Result<Value> {
+    let line_index = LineIndex::new(doc);
+    let mut json = serde_json::to_value(lines)?;
+
+    if let Value::Array(lines) = &mut json {
+        for (line_idx, line) in lines.iter_mut().enumerate() {
+            annotate_spans(doc, &line_index, line);
+
+            if let Value::Object(line) = line {
+                line.insert("line_idx".to_owned(), line_idx.into());
+            }
+        }
+    }
+
+    Ok(json)
+}
+
+/// the serialized `nodes` of `doc`, with each of their spans annotated (see
+/// [`annotate_spans`])
+///
+/// [`annotate_spans`]: fn.annotate_spans.html
+fn json_of_nodes(doc: &str, nodes: &[Node]) -> Result<Value> {
+    let mut json = serde_json::to_value(nodes)?;
+    annotate_spans(doc, &LineIndex::new(doc), &mut json);
+
+    Ok(json)
+}
+
+/// add, to every serialized span within `json`, the (zero-based,
+/// `char`-counted) positions of its start and end and the text of `doc` it
+/// spans
+fn annotate_spans(doc: &str, line_index: &LineIndex, json: &mut Value) {
+    match json {
+        Value::Object(object) => {
+            let opt_span = match (object.get("start"), object.get("end")) {
+                (Some(Value::Number(start)), Some(Value::Number(end))) if object.len() == 2 => {
+                    start.as_u64().zip(end.as_u64())
+                },
+                _ => None,
+            };
+
+            match opt_span {
+                Some((start, end)) => {
+                    let (start, end) = (start as usize, end as usize);
+                    object.insert("start_pos".to_owned(), json!(line_index.line_col(doc, start)));
+                    object.insert("end_pos".to_owned(), json!(line_index.line_col(doc, end)));
+                    object.insert("text".to_owned(), doc[start..end].into());
+                },
+                None => {
+                    for value in object.values_mut() {
+                        annotate_spans(doc, line_index, value);
+                    }
+                },
+            }
+        },
+        Value::Array(values) => {
+            for value in values {
+                annotate_spans(doc, line_index, value);
+            }
+        },
+        _ => {},
+    }
+}
+
+fn print_nodes(doc: &str, nodes: &[Node]) {
+    for node in nodes {
+        let indent_txt = "    ".repeat(node.level);
+        let opt_value_txt = node.value.map(|span| &doc[span]);
+
+        println!("{}{} = {:?}", indent_txt, doc[node.key].trim_end(), opt_value_txt);
+        print_nodes(doc, &node.children);
+    }
+}
+
+// ----- tests -----------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lines_as_json() {
+        // arrange
+        let doc = "E1:\n\tCost: 请 # c\n";
+
+        // act
+        let actual = json_of_lines(doc, &span_lines_of(doc)).unwrap();
+
+        // assert
+        assert_eq!(
+            json!({
+                "line_idx": 1,
+                "raw": { "start": 4, "end": 19, "start_pos": { "line": 1, "col": 0 }, "end_pos": { "line": 2, "col": 0 }, "text": "\tCost: 请 # c\n" },
+                "indent": { "start": 4, "end": 5, "start_pos": { "line": 1, "col": 0 }, "end_pos": { "line": 1, "col": 1 }, "text": "\t" },
+                "key": { "start": 5, "end": 9, "start_pos": { "line": 1, "col": 1 }, "end_pos": { "line": 1, "col": 5 }, "text": "Cost" },
+                "key_sep": { "start": 9, "end": 10, "start_pos": { "line": 1, "col": 5 }, "end_pos": { "line": 1, "col": 6 }, "text": ":" },
+                "value": { "start": 11, "end": 15, "start_pos": { "line": 1, "col": 7 }, "end_pos": { "line": 1, "col": 9 }, "text": "请 " },
+                "comment": { "start": 15, "end": 18, "start_pos": { "line": 1, "col": 9 }, "end_pos": { "line": 1, "col": 12 }, "text": "# c" },
+                "term": { "start": 18, "end": 19, "start_pos": { "line": 1, "col": 12 }, "end_pos": { "line": 2, "col": 0 }, "text": "\n" },
+            }),
+            actual[1],
+        );
+    }
+
+    #[test]
+    fn nodes_as_json() {
+        // arrange
+        let doc = "E1:\n\tCost: 100\n";
+        let lines = span_lines_of(doc);
+
+        // act
+        let actual = json_of_nodes(doc, &tree_of(doc, &lines).nodes).unwrap();
+
+        // assert
+        let span = |start: usize, end: usize, line: usize, col: usize, text: &str| json!({
+            "start": start,
+            "end": end,
+            "start_pos": { "line": line, "col": col },
+            "end_pos": { "line": line, "col": col + text.chars().count() },
+            "text": text,
+        });
+
+        assert_eq!(
+            json!([{
+                "line_idx": 0,
+                "last_line_idx": 1,
+                "level": 0,
+                "key": span(0, 2, 0, 0, "E1"),
+                "value": null,
+                "children": [{
+                    "line_idx": 1,
+                    "last_line_idx": 1,
+                    "level": 1,
+                    "key": span(5, 9, 1, 1, "Cost"),
+                    "value": span(11, 14, 1, 7, "100"),
+                    "children": [],
+                }],
+            }]),
+            actual,
+        );
+    }
+}
@@ -0,0 +1,46 @@
+// oraide - tools for OpenRA-based mod/game development
+// get the source code at https://github.com/Phrohdoh/oraide
+//
+// copyright (c)
+// - 2020 Taryn "Phrohdoh" Hill
+
+//! `ora graph inherits`, which prints the inheritance graph of a mod
+
+use {
+    std::path::Path,
+    oraide_cli::Result,
+    oraide_miniyaml::{
+        Config,
+        InheritsGraph,
+    },
+    crate::{
+        args::{
+            GraphFilter,
+            GraphFormat,
+        },
+        read_rules_of,
+    },
+};
+
+pub(crate) fn graph_inherits(
+    manifest_path: &Path,
+    format: GraphFormat,
+    filter: GraphFilter,
+    config: &Config,
+) -> Result<()> {
+    let rules = read_rules_of(manifest_path, config)?;
+    let graph = InheritsGraph::of_rules(&rules);
+
+    let graph = match filter {
+        GraphFilter::All => graph,
+        GraphFilter::AncestorsOf(name) => graph.retain(&graph.ancestors_of(&name)),
+        GraphFilter::DescendantsOf(name) => graph.retain(&graph.descendants_of(&name)),
+    };
+
+    match format {
+        GraphFormat::Dot => print!("{}", graph.to_dot()),
+        GraphFormat::Mermaid => print!("{}", graph.to_mermaid()),
+    }
+
+    Ok(())
+}
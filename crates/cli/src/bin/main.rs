@@ -5,17 +5,36 @@
 // - 2020 Taryn "Phrohdoh" Hill
 
 mod args;
+mod check;
+mod convert;
+mod dump;
+mod graph;
+mod resolve;
 
 use {
     std::{
         fs,
         process,
-        path::Path,
+        path::{
+            Path,
+            PathBuf,
+        },
     },
     oraide_cli::Result,
     oraide_miniyaml::{
-        span_lines_of,
-        AbsByteIdxSpan,
+        decode_lossy,
+        is_map_package,
+        Assets,
+        BinaryFile,
+        Config,
+        FileLocation,
+        Manifest,
+        MapPackage,
+        ModFiles,
+        Rules,
+        SourceFile,
+        MAP_BIN,
+        OVERRIDE_SECTIONS,
     },
 };
 
@@ -31,38 +50,134 @@ fn try_main() -> Result<()> {
 
     match args.command {
         args::Command::Help => /* handled in args.rs */ Ok(()),
-        args::Command::CheckSingleFile(path) => _check_single_file(&path),
+        args::Command::CheckSingleFile(path) => dump::dump(&path, args::Format::Text, false),
+        args::Command::CheckMod { manifest_path, format, is_fixing } => {
+            check::check_mod(&manifest_path, format, is_fixing, &args.config)
+        },
+        args::Command::Dump { file_path, format, is_tree } => dump::dump(&file_path, format, is_tree),
+        args::Command::Convert { file_path, target } => {
+            convert::convert(&file_path, target, args.config.indent.unwrap_or_default())
+        },
+        args::Command::Resolve { manifest_path, name } => {
+            resolve::resolve(&manifest_path, &name, &args.config)
+        },
+        args::Command::GraphInherits { manifest_path, format, filter } => {
+            graph::graph_inherits(&manifest_path, format, filter, &args.config)
+        },
+        args::Command::Lsp => oraide_lsp::run(),
     }
 }
 
-fn _check_single_file(
-    path: &Path,
-) -> Result<()> {
-    let file_contents = fs::read_to_string(path)?;
-    let lines = span_lines_of(&file_contents);
+/// read the file at `path` lossily, warning about (but otherwise ignoring) any
+/// bytes which are not valid UTF-8
+pub(crate) fn read_doc(path: &Path) -> Result<String> {
+    let bytes = fs::read(path)
+        .map_err(|err| format!("{}: {}", path.display(), err))?;
+    let decoded = decode_lossy(&bytes);
 
-    let map_opt_span_to_txt = |opt_span: Option<AbsByteIdxSpan>| -> Option<&str> {
-        opt_span.map(|span| &file_contents[span])
+    for invalid_sequence in &decoded.invalid_sequences {
+        eprintln!(
+            "warning: {}: invalid UTF-8 at byte offset {} (replaced with U+FFFD)",
+            path.display(),
+            invalid_sequence.byte_idx,
+        );
+    }
+
+    Ok(decoded.text)
+}
+
+/// read the manifest at `manifest_path` and merge every rules file it lists
+/// (which `config` doesn't ignore)
+pub(crate) fn read_rules_of(manifest_path: &Path, config: &Config) -> Result<Rules> {
+    let manifest = Manifest::new(manifest_path, &read_doc(manifest_path)?);
+
+    let mut rules = Rules::new();
+    for path in manifest.files_of("Rules") {
+        if config.is_ignored(&path) {
+            continue;
+        }
+
+        let doc = read_doc(&path)?;
+        rules.add_file(path, &doc);
+    }
+
+    Ok(rules)
+}
+
+/// read the manifest at `manifest_path` and every file it lists which lints
+/// inspect (and `config` doesn't ignore), including the `map.yaml` and override
+/// files of every map (whether a directory or an `.oramap`) in its
+/// `MapFolders`
+pub(crate) fn read_mod_files(manifest_path: &Path, config: &Config) -> Result<ModFiles> {
+    let manifest = Manifest::new(manifest_path, &read_doc(manifest_path)?);
+
+    let read_files = |paths: Vec<PathBuf>| -> Result<Vec<SourceFile>> {
+        paths.into_iter()
+            .filter(|path| !config.is_ignored(path))
+            .map(|path| {
+                let bytes = fs::read(&path)
+                    .map_err(|err| format!("{}: {}", path.display(), err))?;
+
+                // bytes which aren't valid UTF-8 are reported by the
+                // `invalid-utf8` lint
+                Ok(SourceFile::decode(path, &bytes).with_location(FileLocation::Disk))
+            })
+            .collect()
     };
 
-    for line in lines {
-        let raw_txt = &file_contents[line.raw];
-        let opt_indent_txt = map_opt_span_to_txt(line.indent);
-        let opt_key_txt = map_opt_span_to_txt(line.key);
-        let opt_key_sep_txt = map_opt_span_to_txt(line.key_sep);
-        let opt_value_txt = map_opt_span_to_txt(line.value);
-        let opt_comment_txt = map_opt_span_to_txt(line.comment);
-        let opt_term_txt = map_opt_span_to_txt(line.term);
-
-        println!("raw     = {:?}", raw_txt);
-        println!("indent  = {:?}", opt_indent_txt);
-        println!("key     = {:?}", opt_key_txt);
-        println!("key_sep = {:?}", opt_key_sep_txt);
-        println!("value   = {:?}", opt_value_txt);
-        println!("comment = {:?}", opt_comment_txt);
-        println!("term    = {:?}", opt_term_txt);
-        println!();
+    let mut map_package_paths = vec![];
+    for map_folder in manifest.files_of("MapFolders") {
+        let entries = match fs::read_dir(&map_folder) {
+            Ok(entries) => entries,
+            // folders which don't exist (e.g. user map folders) are skipped
+            Err(_) => continue,
+        };
+
+        map_package_paths.extend(entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| is_map_package(path) && !config.is_ignored(path)));
+    }
+
+    map_package_paths.sort();
+
+    let mut maps = vec![];
+    let mut map_overrides = vec![];
+    let mut map_bins = vec![];
+
+    for map_package_path in map_package_paths {
+        // one broken map shouldn't prevent linting the rest of the mod
+        let map_package = match MapPackage::open(map_package_path) {
+            Ok(map_package) => map_package,
+            Err(err) => {
+                eprintln!("warning: {}", err);
+                continue;
+            },
+        };
+
+        maps.extend(map_package.map_yaml()
+            .filter(|file| !config.is_ignored(&file.path)));
+
+        for section in &OVERRIDE_SECTIONS {
+            map_overrides.extend(map_package.override_files_of(section).into_iter()
+                .filter(|file| !config.is_ignored(&file.path)));
+        }
+
+        map_bins.extend(map_package.map_bin().map(|bytes| BinaryFile {
+            path: map_package.path_of(MAP_BIN),
+            bytes: bytes.to_vec(),
+        }));
     }
 
-    Ok(())
+    Ok(ModFiles {
+        rules: read_files(manifest.files_of("Rules"))?,
+        weapons: read_files(manifest.files_of("Weapons"))?,
+        sequences: read_files(manifest.files_of("Sequences"))?,
+        tilesets: read_files(manifest.files_of("TileSets"))?,
+        maps,
+        map_overrides,
+        map_bins,
+        assets: Assets::read(&manifest),
+        schema: config.schema()?,
+    })
 }
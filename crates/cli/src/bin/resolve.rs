@@ -0,0 +1,53 @@
+// oraide - tools for OpenRA-based mod/game development
+// get the source code at https://github.com/Phrohdoh/oraide
+//
+// copyright (c)
+// - 2020 Taryn "Phrohdoh" Hill
+
+//! `ora resolve`, which prints the final definition of a top-level node
+
+use {
+    std::path::Path,
+    oraide_cli::Result,
+    oraide_miniyaml::{
+        Config,
+        RuleNode,
+    },
+    crate::read_rules_of,
+};
+
+pub(crate) fn resolve(manifest_path: &Path, name: &str, config: &Config) -> Result<()> {
+    let rules = read_rules_of(manifest_path, config)?;
+    let resolved = rules.resolve(name)?;
+
+    let mut lines = vec![];
+    collect_lines(&resolved, 0, &mut lines);
+
+    let width = lines.iter()
+        .map(|(txt, _)| txt.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    for (txt, origin) in lines {
+        println!("{:width$}  # {}", txt, origin, width = width);
+    }
+
+    Ok(())
+}
+
+// ----- private implementation details ----------------------------------------
+
+/// the text of each line of `node` (indented with spaces, so that annotations
+/// line up) and where it came from
+fn collect_lines(node: &RuleNode, depth: usize, lines: &mut Vec<(String, String)>) {
+    let txt = match &node.value {
+        Some(value) => format!("{}{}: {}", "    ".repeat(depth), node.key, value),
+        None => format!("{}{}:", "    ".repeat(depth), node.key),
+    };
+
+    lines.push((txt, node.origin.to_string()));
+
+    for child in &node.children {
+        collect_lines(child, depth + 1, lines);
+    }
+}
@@ -0,0 +1,81 @@
+// oraide - tools for OpenRA-based mod/game development
+// get the source code at https://github.com/Phrohdoh/oraide
+//
+// copyright (c)
+// - 2020 Taryn "Phrohdoh" Hill
+
+//! `textDocument/codeAction`, offering the fix of each published diagnostic
+//! (which has one) in the requested range as a quick fix
+
+use {
+    std::collections::HashMap,
+    lsp_types::{
+        CodeAction,
+        CodeActionKind,
+        CodeActionOrCommand,
+        CodeActionParams,
+        CodeActionResponse,
+        Range,
+        TextEdit,
+        WorkspaceEdit,
+    },
+    crate::{
+        diagnostics::{
+            self,
+            Published,
+        },
+        workspace::{
+            self,
+            Workspace,
+        },
+    },
+};
+
+pub(crate) fn code_actions(
+    workspace: &Workspace,
+    published: &Published,
+    params: CodeActionParams,
+) -> Option<CodeActionResponse> {
+    let (uri, range) = (params.text_document.uri, params.range);
+    let path = workspace::path_of(&uri);
+    let file = workspace.file(&path)?;
+
+    let actions = published.get(&path)?.iter()
+        .filter(|diagnostic| intersects(file.range_of(diagnostic.span), range))
+        .filter_map(|diagnostic| {
+            let fix = diagnostic.fix.as_ref()?;
+
+            let edits = fix.edits.iter()
+                .map(|edit| TextEdit {
+                    range: file.range_of(edit.replaced),
+                    new_text: edit.new_text.clone(),
+                })
+                .collect();
+
+            let mut changes = HashMap::new();
+            changes.insert(uri.clone(), edits);
+
+            Some(CodeActionOrCommand::CodeAction(CodeAction {
+                title: fix.description.clone(),
+                kind: Some(CodeActionKind::QUICKFIX),
+                diagnostics: Some(vec![diagnostics::lsp_diagnostic_of(file, diagnostic)]),
+                edit: Some(WorkspaceEdit {
+                    changes: Some(changes),
+                    ..WorkspaceEdit::default()
+                }),
+                is_preferred: Some(!fix.is_suggestion),
+                ..CodeAction::default()
+            }))
+        })
+        .collect();
+
+    Some(actions)
+}
+
+// ----- private implementation details ----------------------------------------
+
+/// `true` if `a` and `b` overlap or touch (so a cursor at either end of a
+/// diagnostic's range is offered its fix)
+fn intersects(a: Range, b: Range) -> bool {
+    a.start <= b.end && b.start <= a.end
+}
@@ -0,0 +1,272 @@
+// oraide - tools for OpenRA-based mod/game development
+// get the source code at https://github.com/Phrohdoh/oraide
+//
+// copyright (c)
+// - 2020 Taryn "Phrohdoh" Hill
+
+//! lint diagnostics of the mod whose manifest (`mod.yaml`) is in the
+//! workspace, run by the same [`Linter`] as `ora check --mod`
+//!
+//! [`Linter`]: ../../oraide_miniyaml/struct.Linter.html
+
+use {
+    std::{
+        collections::HashMap,
+        fs,
+        path::{
+            Path,
+            PathBuf,
+        },
+    },
+    lsp_server::{
+        Connection,
+        Notification,
+    },
+    lsp_types::{
+        notification::{
+            Notification as _,
+            PublishDiagnostics,
+        },
+        DiagnosticSeverity,
+        NumberOrString,
+        PublishDiagnosticsParams,
+    },
+    oraide_miniyaml::{
+        is_map_package,
+        is_mod_entry,
+        overrides_of,
+        span_lines_of,
+        tree_of,
+        BinaryFile,
+        Diagnostic,
+        FileLocation,
+        Linter,
+        Manifest,
+        MapPackage,
+        ModFiles,
+        Severity,
+        SourceFile,
+        MAP_BIN,
+        OVERRIDE_SECTIONS,
+    },
+    crate::{
+        workspace::{
+            self,
+            File,
+            Workspace,
+        },
+        Result,
+    },
+};
+
+/// the diagnostics last published for each file, whose fixes are offered as
+/// code actions
+pub(crate) type Published = HashMap<PathBuf, Vec<Diagnostic>>;
+
+/// lint the workspace's mod and publish the diagnostics of each of its files
+/// (except those within zip packages, which have no URI the client could
+/// open), clearing those of `published` which are no longer part of it
+pub(crate) fn publish_diagnostics(
+    connection: &Connection,
+    workspace: &mut Workspace,
+    published: &mut Published,
+) -> Result<()> {
+    let (files, diagnostics) = lint(workspace);
+    let mut now_published = Published::new();
+
+    let unzipped_files = files.iter()
+        .map(|(_, source_file)| source_file)
+        .filter(|source_file| !matches!(source_file.location, FileLocation::Zip { .. }));
+
+    for source_file in unzipped_files {
+        let owned_file;
+        let file = match workspace.file(&source_file.path) {
+            Some(file) => file,
+            None => {
+                owned_file = File::new(source_file.text.clone());
+                &owned_file
+            },
+        };
+
+        let file_diagnostics = diagnostics.iter()
+            .filter(|diagnostic| diagnostic.path == source_file.path)
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let lsp_diagnostics = file_diagnostics.iter()
+            .map(|diagnostic| lsp_diagnostic_of(file, diagnostic))
+            .collect();
+
+        publish(connection, &source_file.path, lsp_diagnostics)?;
+        now_published.insert(source_file.path.clone(), file_diagnostics);
+    }
+
+    for path in published.keys().filter(|path| !now_published.contains_key(*path)) {
+        publish(connection, path, vec![])?;
+    }
+
+    *published = now_published;
+    Ok(())
+}
+
+// ----- private implementation details ----------------------------------------
+
+/// the files of the workspace's mod, and the diagnostics of running the
+/// configured lints against them
+fn lint(workspace: &mut Workspace) -> (ModFiles, Vec<Diagnostic>) {
+    let files = mod_files_of(workspace).unwrap_or_default();
+
+    let mut linter = Linter::new();
+    workspace.config.configure(&mut linter);
+    let diagnostics = linter.run(&files);
+
+    (files, diagnostics)
+}
+
+/// the files of the configured mod or, if none is configured, of the mod
+/// whose manifest is the first `mod.yaml` (by path) in the workspace,
+/// preferring the workspace's (possibly unsaved) contents to what is on disk
+/// (except for those in `.oramap`s, which are always read from disk, and the
+/// files of packages, which are read once and then cached by the workspace)
+fn mod_files_of(workspace: &mut Workspace) -> Option<ModFiles> {
+    let manifest_path = match &workspace.config.manifest_path {
+        Some(manifest_path) => manifest_path.clone(),
+        None => workspace.paths()
+            .filter(|path| path.file_name().and_then(|name| name.to_str()) == Some("mod.yaml"))
+            .min()?
+            .to_path_buf(),
+    };
+
+    let source_file_of = |path: PathBuf| -> Option<SourceFile> {
+        if workspace.config.is_ignored(&path) {
+            return None;
+        }
+
+        Some(match workspace.file(&path) {
+            Some(file) => SourceFile {
+                text: file.text.clone(),
+                invalid_sequences: file.invalid_sequences.clone(),
+                location: FileLocation::Disk,
+                path,
+            },
+            None => SourceFile::decode(&path, &fs::read(&path).ok()?).with_location(FileLocation::Disk),
+        })
+    };
+
+    let manifest = Manifest::new(&manifest_path, &source_file_of(manifest_path.clone())?.text);
+
+    let source_files_of = |section: &str| -> Vec<SourceFile> {
+        manifest.files_of(section).into_iter()
+            .filter_map(source_file_of)
+            .collect()
+    };
+
+    let map_folders = manifest.files_of("MapFolders");
+    let mut map_yaml_paths = workspace.paths()
+        .filter(|path| path.file_name().and_then(|name| name.to_str()) == Some("map.yaml"))
+        .filter(|path| match path.parent().and_then(Path::parent) {
+            Some(map_folder) => map_folders.iter().any(|folder| folder == map_folder),
+            None => false,
+        })
+        .map(Path::to_path_buf)
+        .collect::<Vec<_>>();
+
+    map_yaml_paths.sort();
+
+    let mut maps = map_yaml_paths.into_iter()
+        .filter_map(source_file_of)
+        .collect::<Vec<_>>();
+
+    let mut map_overrides = vec![];
+    let mut map_bins = vec![];
+
+    for map in &maps {
+        let lines = span_lines_of(&map.text);
+        let tree = tree_of(&map.text, &lines);
+        let package_dir = map.path.parent().unwrap_or(&map.path);
+
+        map_overrides.extend(overrides_of(&map.text, &tree).iter()
+            .flat_map(|map_override| map_override.files.iter())
+            .map(|&entry_span| &map.text[entry_span])
+            .filter(|entry| !is_mod_entry(entry))
+            .filter_map(|entry| source_file_of(package_dir.join(entry))));
+
+        // binary, so never open in the workspace
+        let map_bin_path = package_dir.join(MAP_BIN);
+        if let Ok(bytes) = fs::read(&map_bin_path) {
+            map_bins.push(BinaryFile { path: map_bin_path, bytes });
+        }
+    }
+
+    // zipped maps can't be edited in the workspace, so are read from disk
+    for map_folder in &map_folders {
+        let mut oramap_paths = fs::read_dir(map_folder).into_iter()
+            .flatten()
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file() && is_map_package(path))
+            .filter(|path| !workspace.config.is_ignored(path))
+            .collect::<Vec<_>>();
+
+        oramap_paths.sort();
+
+        for map_package in oramap_paths.into_iter().filter_map(|path| MapPackage::open(path).ok()) {
+            maps.extend(map_package.map_yaml());
+
+            for section in &OVERRIDE_SECTIONS {
+                map_overrides.extend(map_package.override_files_of(section));
+            }
+
+            map_bins.extend(map_package.map_bin().map(|bytes| BinaryFile {
+                path: map_package.path_of(MAP_BIN),
+                bytes: bytes.to_vec(),
+            }));
+        }
+    }
+
+    Some(ModFiles {
+        rules: source_files_of("Rules"),
+        weapons: source_files_of("Weapons"),
+        sequences: source_files_of("Sequences"),
+        tilesets: source_files_of("TileSets"),
+        maps,
+        map_overrides,
+        map_bins,
+        assets: workspace.assets_of(&manifest).clone(),
+        schema: workspace.schema.clone(),
+    })
+}
+
+pub(crate) fn lsp_diagnostic_of(file: &File, diagnostic: &Diagnostic) -> lsp_types::Diagnostic {
+    let severity = match diagnostic.severity {
+        Severity::Error => DiagnosticSeverity::ERROR,
+        Severity::Warning => DiagnosticSeverity::WARNING,
+        Severity::Info => DiagnosticSeverity::INFORMATION,
+    };
+
+    lsp_types::Diagnostic {
+        range: file.range_of(diagnostic.span),
+        severity: Some(severity),
+        code: Some(NumberOrString::String(diagnostic.code.to_owned())),
+        source: Some("oraide".to_owned()),
+        message: diagnostic.message.clone(),
+        ..lsp_types::Diagnostic::default()
+    }
+}
+
+fn publish(
+    connection: &Connection,
+    path: &Path,
+    diagnostics: Vec<lsp_types::Diagnostic>,
+) -> Result<()> {
+    let uri = match workspace::uri_of(path) {
+        Some(uri) => uri,
+        None => return Ok(()),
+    };
+
+    let params = PublishDiagnosticsParams::new(uri, diagnostics, None);
+    let not = Notification::new(PublishDiagnostics::METHOD.to_owned(), params);
+    connection.sender.send(not.into())?;
+
+    Ok(())
+}
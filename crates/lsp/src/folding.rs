@@ -0,0 +1,126 @@
+// oraide - tools for OpenRA-based mod/game development
+// get the source code at https://github.com/Phrohdoh/oraide
+//
+// copyright (c)
+// - 2020 Taryn "Phrohdoh" Hill
+
+//! `textDocument/foldingRange`, derived from the extents of each node's block
+//! and from runs of comment-only lines
+
+use {
+    lsp_types::{
+        FoldingRange,
+        FoldingRangeKind,
+        FoldingRangeParams,
+    },
+    oraide_miniyaml::{
+        tree_of,
+        SpannedLine,
+    },
+    crate::workspace::{
+        self,
+        Workspace,
+    },
+};
+
+pub(crate) fn folding_ranges(
+    workspace: &Workspace,
+    params: FoldingRangeParams,
+) -> Option<Vec<FoldingRange>> {
+    let path = workspace::path_of(&params.text_document.uri);
+    let file = workspace.file(&path)?;
+    let tree = tree_of(&file.text, &file.lines);
+
+    let block_ranges = tree.iter()
+        .filter(|node| node.last_line_idx > node.line_idx)
+        .map(|node| folding_range_of(node.line_idx, node.last_line_idx, None));
+
+    let comment_ranges = comment_runs_of(&file.lines).into_iter()
+        .map(|(first_line_idx, last_line_idx)| folding_range_of(
+            first_line_idx,
+            last_line_idx,
+            Some(FoldingRangeKind::Comment),
+        ));
+
+    Some(block_ranges.chain(comment_ranges).collect())
+}
+
+// ----- private implementation details ----------------------------------------
+
+fn folding_range_of(
+    first_line_idx: usize,
+    last_line_idx: usize,
+    kind: Option<FoldingRangeKind>,
+) -> FoldingRange {
+    FoldingRange {
+        start_line: first_line_idx as u32,
+        start_character: None,
+        end_line: last_line_idx as u32,
+        end_character: None,
+        kind,
+        collapsed_text: None,
+    }
+}
+
+/// `(first_line_idx, last_line_idx)` of every run of at least 2 consecutive
+/// comment-only lines
+fn comment_runs_of(lines: &[SpannedLine]) -> Vec<(usize, usize)> {
+    let mut runs = vec![];
+    let mut opt_run_start_line_idx = None;
+
+    for (line_idx, line) in lines.iter().enumerate() {
+        match (line.is_comment_only(), opt_run_start_line_idx) {
+            (true, None) => opt_run_start_line_idx = Some(line_idx),
+            (false, Some(run_start_line_idx)) => {
+                if line_idx - 1 > run_start_line_idx {
+                    runs.push((run_start_line_idx, line_idx - 1));
+                }
+
+                opt_run_start_line_idx = None;
+            },
+            _ => {},
+        }
+    }
+
+    if let Some(run_start_line_idx) = opt_run_start_line_idx {
+        let last_line_idx = lines.len() - 1;
+        if last_line_idx > run_start_line_idx {
+            runs.push((run_start_line_idx, last_line_idx));
+        }
+    }
+
+    runs
+}
+
+// ----- tests -----------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        oraide_miniyaml::span_lines_of,
+    };
+
+    #[test]
+    fn comment_runs_need_at_least_two_lines() {
+        // arrange
+        let doc = [
+            /* 0 */ "# a lone comment\n",
+            /* 1 */ "E2:\n",
+            /* 2 */ "\t# Cost: 300\n",
+            /* 3 */ "\t# Cost: 400\n",
+            /* 4 */ "\tValued:\n",
+            /* 5 */ "# trailing\n",
+            /* 6 */ "  # comments",
+        ].join("");
+
+        // act
+        let actual_runs = comment_runs_of(&span_lines_of(&doc));
+
+        // assert
+        assert_eq!(
+            vec![(2, 3), (5, 6)],
+            actual_runs,
+        );
+    }
+}
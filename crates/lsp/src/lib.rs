@@ -0,0 +1,325 @@
+// oraide - tools for OpenRA-based mod/game development
+// get the source code at https://github.com/Phrohdoh/oraide
+//
+// copyright (c)
+// - 2020 Taryn "Phrohdoh" Hill
+
+//! A [Language Server Protocol] server for MiniYaml, communicating over
+//! stdin/stdout, which editors use to provide features such as an outline of
+//! the current file, symbol search across the whole mod, and lint diagnostics
+//! (with quick fixes).
+//!
+//! [Language Server Protocol]: https://microsoft.github.io/language-server-protocol/
+
+mod workspace;
+mod symbols;
+mod folding;
+mod semantic_tokens;
+mod diagnostics;
+mod code_actions;
+
+use {
+    std::time::{
+        Duration,
+        Instant,
+    },
+    lsp_server::{
+        Connection,
+        Message,
+        Notification,
+        Request,
+        RequestId,
+        Response,
+    },
+    lsp_types::{
+        notification::{
+            DidChangeTextDocument,
+            DidChangeWatchedFiles,
+            DidCloseTextDocument,
+            DidOpenTextDocument,
+            Notification as _,
+        },
+        request::{
+            CodeActionRequest,
+            DocumentSymbolRequest,
+            FoldingRangeRequest,
+            RegisterCapability,
+            Request as _,
+            SemanticTokensFullRequest,
+            WorkspaceSymbolRequest,
+        },
+        CodeActionProviderCapability,
+        DidChangeWatchedFilesRegistrationOptions,
+        FileSystemWatcher,
+        FoldingRangeProviderCapability,
+        GlobPattern,
+        InitializeParams,
+        OneOf,
+        Registration,
+        RegistrationParams,
+        SemanticTokensFullOptions,
+        SemanticTokensOptions,
+        SemanticTokensServerCapabilities,
+        ServerCapabilities,
+        TextDocumentSyncCapability,
+        TextDocumentSyncKind,
+    },
+    oraide_miniyaml::{
+        Config,
+        Linter,
+    },
+    crate::{
+        diagnostics::Published,
+        workspace::Workspace,
+    },
+};
+
+pub type Result<T, E = Box<dyn std::error::Error + Send + Sync>> = std::result::Result<T, E>;
+
+/// Serve LSP requests over stdin/stdout until the client asks us to exit.
+pub fn run() -> Result<()> {
+    let (connection, io_threads) = Connection::stdio();
+
+    let capabilities = serde_json::to_value(server_capabilities())?;
+    let init_params = connection.initialize(capabilities)?;
+    let init_params = serde_json::from_value::<InitializeParams>(init_params)?;
+
+    let roots = workspace::roots_of(&init_params);
+
+    let can_watch_files = init_params.capabilities.workspace.as_ref()
+        .and_then(|workspace| workspace.did_change_watched_files.as_ref())
+        .and_then(|capabilities| capabilities.dynamic_registration)
+        .unwrap_or(false);
+
+    // the client tells us about changes to the files of packages (which it
+    // doesn't open), so that we know when to re-read them
+    if can_watch_files {
+        connection.sender.send(watch_files_request()?.into())?;
+    }
+
+    // the config of the first root applies to the whole workspace
+    let config = match roots.first() {
+        Some(root) => Config::discover(root).unwrap_or_else(|err| {
+            eprintln!("{}", err);
+            None
+        }),
+        None => None,
+    };
+
+    let config = config.unwrap_or_default();
+
+    for code in config.configure(&mut Linter::new()) {
+        eprintln!("warning: `{}` is not the code of any lint", code);
+    }
+
+    // a schema which can't be read only disables the checks which need it
+    let schema = config.schema().unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        None
+    });
+
+    let mut workspace = Workspace::new(config);
+    workspace.schema = schema;
+    for root in &roots {
+        workspace.load_dir(root);
+    }
+
+    main_loop(&connection, &mut workspace)?;
+
+    // the io threads only finish once every handle to the connection is gone
+    drop(connection);
+    io_threads.join()?;
+
+    Ok(())
+}
+
+// ----- private implementation details ----------------------------------------
+
+fn server_capabilities() -> ServerCapabilities {
+    ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(
+            TextDocumentSyncKind::INCREMENTAL,
+        )),
+        document_symbol_provider: Some(OneOf::Left(true)),
+        workspace_symbol_provider: Some(OneOf::Left(true)),
+        folding_range_provider: Some(FoldingRangeProviderCapability::Simple(true)),
+        semantic_tokens_provider: Some(
+            SemanticTokensServerCapabilities::SemanticTokensOptions(SemanticTokensOptions {
+                legend: semantic_tokens::legend(),
+                full: Some(SemanticTokensFullOptions::Bool(true)),
+                ..SemanticTokensOptions::default()
+            }),
+        ),
+        code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
+        ..ServerCapabilities::default()
+    }
+}
+
+fn watch_files_request() -> Result<Request> {
+    let options = DidChangeWatchedFilesRegistrationOptions {
+        watchers: vec![FileSystemWatcher {
+            glob_pattern: GlobPattern::String("**/*".to_owned()),
+            kind: None,
+        }],
+    };
+
+    let params = RegistrationParams {
+        registrations: vec![Registration {
+            id: DidChangeWatchedFiles::METHOD.to_owned(),
+            method: DidChangeWatchedFiles::METHOD.to_owned(),
+            register_options: Some(serde_json::to_value(options)?),
+        }],
+    };
+
+    Ok(Request::new(
+        RequestId::from(RegisterCapability::METHOD.to_owned()),
+        RegisterCapability::METHOD.to_owned(),
+        params,
+    ))
+}
+
+/// how long after the last change to the workspace its mod is re-linted, so
+/// that a burst of changes (e.g. typing) is linted once rather than once per
+/// keystroke
+const LINT_DELAY: Duration = Duration::from_millis(300);
+
+fn main_loop(connection: &Connection, workspace: &mut Workspace) -> Result<()> {
+    let mut published = Published::new();
+    diagnostics::publish_diagnostics(connection, workspace, &mut published)?;
+
+    // when the workspace last changed, if it has changed since it was linted
+    let mut opt_changed_at: Option<Instant> = None;
+
+    loop {
+        let msg = match opt_changed_at {
+            Some(changed_at) => {
+                match connection.receiver.recv_timeout(LINT_DELAY.saturating_sub(changed_at.elapsed())) {
+                    Ok(msg) => msg,
+                    Err(err) if err.is_timeout() => {
+                        diagnostics::publish_diagnostics(connection, workspace, &mut published)?;
+                        opt_changed_at = None;
+                        continue;
+                    },
+                    Err(_) => break,
+                }
+            },
+            None => match connection.receiver.recv() {
+                Ok(msg) => msg,
+                Err(_) => break,
+            },
+        };
+
+        match msg {
+            Message::Request(req) => {
+                if connection.handle_shutdown(&req)? {
+                    return Ok(());
+                }
+
+                // code actions are the fixes of the published diagnostics, so
+                // those must be of the workspace as it is now
+                if req.method == CodeActionRequest::METHOD && opt_changed_at.take().is_some() {
+                    diagnostics::publish_diagnostics(connection, workspace, &mut published)?;
+                }
+
+                let resp = handle_request(workspace, &published, req);
+                connection.sender.send(resp.into())?;
+            },
+            Message::Notification(not) => {
+                if handle_notification(workspace, not)? {
+                    opt_changed_at = Some(Instant::now());
+                }
+            },
+            Message::Response(_) => {},
+        }
+    }
+
+    Ok(())
+}
+
+fn handle_request(workspace: &Workspace, published: &Published, req: Request) -> Response {
+    let Request { id, method, params } = req;
+
+    match method.as_str() {
+        DocumentSymbolRequest::METHOD => respond_with(id, params, |params| {
+            symbols::document_symbols(workspace, params)
+        }),
+        WorkspaceSymbolRequest::METHOD => respond_with(id, params, |params| {
+            symbols::workspace_symbols(workspace, params)
+        }),
+        FoldingRangeRequest::METHOD => respond_with(id, params, |params| {
+            folding::folding_ranges(workspace, params)
+        }),
+        SemanticTokensFullRequest::METHOD => respond_with(id, params, |params| {
+            semantic_tokens::semantic_tokens(workspace, params)
+        }),
+        CodeActionRequest::METHOD => respond_with(id, params, |params| {
+            code_actions::code_actions(workspace, published, params)
+        }),
+        _ => Response::new_err(
+            id,
+            lsp_server::ErrorCode::MethodNotFound as i32,
+            format!("method {:?} not supported", method),
+        ),
+    }
+}
+
+fn respond_with<P, R>(
+    id: RequestId,
+    params: serde_json::Value,
+    handler: impl FnOnce(P) -> R,
+) -> Response
+where
+    P: serde::de::DeserializeOwned,
+    R: serde::Serialize,
+{
+    match serde_json::from_value::<P>(params) {
+        Ok(params) => Response::new_ok(id, handler(params)),
+        Err(e) => Response::new_err(
+            id,
+            lsp_server::ErrorCode::InvalidParams as i32,
+            e.to_string(),
+        ),
+    }
+}
+
+/// `true` if the notification changed the workspace
+fn handle_notification(workspace: &mut Workspace, not: Notification) -> Result<bool> {
+    match not.method.as_str() {
+        DidOpenTextDocument::METHOD => {
+            let params = not.extract::<lsp_types::DidOpenTextDocumentParams>(DidOpenTextDocument::METHOD)?;
+            let doc = params.text_document;
+            workspace.set_text(workspace::path_of(&doc.uri), doc.text);
+        },
+        DidChangeTextDocument::METHOD => {
+            let params = not.extract::<lsp_types::DidChangeTextDocumentParams>(DidChangeTextDocument::METHOD)?;
+
+            let path = workspace::path_of(&params.text_document.uri);
+
+            // changes are applied in order, each to the result of the previous
+            for change in params.content_changes {
+                match change.range {
+                    Some(range) => workspace.edit(&path, range, change.text),
+                    None => workspace.set_text(path.clone(), change.text),
+                }
+            }
+        },
+        DidCloseTextDocument::METHOD => {
+            let params = not.extract::<lsp_types::DidCloseTextDocumentParams>(DidCloseTextDocument::METHOD)?;
+
+            // the file is still part of the mod, but any unsaved changes were
+            // discarded, so we go back to what is on disk
+            workspace.reload(&workspace::path_of(&params.text_document.uri));
+        },
+        DidChangeWatchedFiles::METHOD => {
+            let params = not.extract::<lsp_types::DidChangeWatchedFilesParams>(DidChangeWatchedFiles::METHOD)?;
+
+            // the assets are forgotten all at once, so the first change to a
+            // package is the only one which matters
+            return Ok(params.changes.iter()
+                .any(|change| workspace.invalidate_assets(&workspace::path_of(&change.uri))));
+        },
+        _ => return Ok(false),
+    }
+
+    Ok(true)
+}
@@ -0,0 +1,104 @@
+// oraide - tools for OpenRA-based mod/game development
+// get the source code at https://github.com/Phrohdoh/oraide
+//
+// copyright (c)
+// - 2020 Taryn "Phrohdoh" Hill
+
+//! `textDocument/semanticTokens/full`, which maps the highlights derived by
+//! `oraide-miniyaml` onto the protocol's standard token types
+
+use {
+    lsp_types::{
+        SemanticToken,
+        SemanticTokenType,
+        SemanticTokens,
+        SemanticTokensLegend,
+        SemanticTokensParams,
+        SemanticTokensResult,
+    },
+    oraide_miniyaml::{
+        highlights_of,
+        HighlightClass,
+        ValueKind,
+    },
+    crate::workspace::{
+        self,
+        Workspace,
+    },
+};
+
+/// the index of each type is what is sent to the client, so the order of this
+/// array must match `token_type_idx_of`
+const TOKEN_TYPES: [SemanticTokenType; 9] = [
+    SemanticTokenType::PROPERTY,
+    SemanticTokenType::MODIFIER,
+    SemanticTokenType::KEYWORD,
+    SemanticTokenType::DECORATOR,
+    SemanticTokenType::OPERATOR,
+    SemanticTokenType::ENUM_MEMBER,
+    SemanticTokenType::NUMBER,
+    SemanticTokenType::STRING,
+    SemanticTokenType::COMMENT,
+];
+
+pub(crate) fn legend() -> SemanticTokensLegend {
+    SemanticTokensLegend {
+        token_types: TOKEN_TYPES.to_vec(),
+        token_modifiers: vec![],
+    }
+}
+
+pub(crate) fn semantic_tokens(
+    workspace: &Workspace,
+    params: SemanticTokensParams,
+) -> Option<SemanticTokensResult> {
+    let path = workspace::path_of(&params.text_document.uri);
+    let file = workspace.file(&path)?;
+
+    let mut data = vec![];
+    let (mut prev_line, mut prev_col) = (0, 0);
+
+    for highlight in highlights_of(&file.text, &file.lines) {
+        let (start, _) = highlight.span.into();
+        let pos = file.position_of(start);
+        let length = file.text[highlight.span].encode_utf16().count();
+
+        // tokens are encoded relative to the previous token
+        let delta_line = pos.line - prev_line;
+        let delta_start = if delta_line == 0 { pos.character - prev_col } else { pos.character };
+
+        data.push(SemanticToken {
+            delta_line,
+            delta_start,
+            length: length as u32,
+            token_type: token_type_idx_of(highlight.class),
+            token_modifiers_bitset: 0,
+        });
+
+        prev_line = pos.line;
+        prev_col = pos.character;
+    }
+
+    Some(SemanticTokensResult::Tokens(SemanticTokens {
+        result_id: None,
+        data,
+    }))
+}
+
+// ----- private implementation details ----------------------------------------
+
+fn token_type_idx_of(class: HighlightClass) -> u32 {
+    match class {
+        HighlightClass::KeyName => 0,
+        HighlightClass::AbstractMarker => 1,
+        HighlightClass::RemovalMarker => 2,
+        HighlightClass::InstanceSuffix => 3,
+        HighlightClass::KeySeparator => 4,
+        HighlightClass::Value(ValueKind::Boolean) => 5,
+        HighlightClass::Value(ValueKind::Integer)
+        | HighlightClass::Value(ValueKind::Decimal)
+        | HighlightClass::Value(ValueKind::WDist) => 6,
+        HighlightClass::Value(ValueKind::Text) => 7,
+        HighlightClass::Comment => 8,
+    }
+}
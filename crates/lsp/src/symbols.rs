@@ -0,0 +1,129 @@
+// oraide - tools for OpenRA-based mod/game development
+// get the source code at https://github.com/Phrohdoh/oraide
+//
+// copyright (c)
+// - 2020 Taryn "Phrohdoh" Hill
+
+//! `textDocument/documentSymbol` (outline, breadcrumbs) and `workspace/symbol`
+//! (search across the whole mod)
+
+use {
+    std::path::Path,
+    lsp_types::{
+        DocumentSymbol,
+        DocumentSymbolParams,
+        DocumentSymbolResponse,
+        Location,
+        SymbolInformation,
+        SymbolKind,
+        WorkspaceSymbolParams,
+        WorkspaceSymbolResponse,
+    },
+    oraide_miniyaml::{
+        parse_key,
+        tree_of,
+        Node,
+    },
+    crate::workspace::{
+        self,
+        File,
+        Workspace,
+    },
+};
+
+pub(crate) fn document_symbols(
+    workspace: &Workspace,
+    params: DocumentSymbolParams,
+) -> Option<DocumentSymbolResponse> {
+    let path = workspace::path_of(&params.text_document.uri);
+    let file = workspace.file(&path)?;
+    let tree = tree_of(&file.text, &file.lines);
+
+    let symbols = tree.nodes.iter()
+        .map(|node| {
+            let is_abstract = parse_key(&file.text, node.key).is_abstract();
+            let kind = top_level_kind_of(&path, is_abstract);
+            document_symbol_of(file, node, kind)
+        })
+        .collect();
+
+    Some(DocumentSymbolResponse::Nested(symbols))
+}
+
+pub(crate) fn workspace_symbols(
+    workspace: &Workspace,
+    params: WorkspaceSymbolParams,
+) -> Option<WorkspaceSymbolResponse> {
+    let symbols = workspace.index.search(&params.query)
+        .filter_map(|(path, symbol)| {
+            let file = workspace.file(path)?;
+
+            #[allow(deprecated /* required field */)]
+            Some(SymbolInformation {
+                name: symbol.name.clone(),
+                kind: top_level_kind_of(path, symbol.is_abstract),
+                tags: None,
+                deprecated: None,
+                location: Location::new(
+                    workspace::uri_of(path)?,
+                    file.range_of(symbol.key),
+                ),
+                container_name: None,
+            })
+        })
+        .collect();
+
+    Some(WorkspaceSymbolResponse::Flat(symbols))
+}
+
+// ----- private implementation details ----------------------------------------
+
+/// abstract nodes are templates, otherwise we go by the conventional directory
+/// layout of a mod (`weapons/*.yaml` vs. `rules/*.yaml`)
+fn top_level_kind_of(path: &Path, is_abstract: bool) -> SymbolKind {
+    let is_weapon = path.components()
+        .any(|component| component.as_os_str() == "weapons");
+
+    if is_abstract {
+        SymbolKind::INTERFACE
+    } else if is_weapon {
+        SymbolKind::OBJECT
+    } else {
+        SymbolKind::CLASS
+    }
+}
+
+fn document_symbol_of(file: &File, node: &Node, kind: SymbolKind) -> DocumentSymbol {
+    let name_txt = file.text[node.key].trim_end();
+    let detail = node.value.map(|span| file.text[span].trim_end().to_owned());
+
+    // traits (and projectiles, warheads, etc.) are directly beneath top-level
+    // nodes, and everything below them is a field
+    let children = node.children.iter()
+        .map(|child| {
+            let child_kind = if kind == SymbolKind::PROPERTY || kind == SymbolKind::FIELD {
+                SymbolKind::FIELD
+            } else {
+                SymbolKind::PROPERTY
+            };
+
+            document_symbol_of(file, child, child_kind)
+        })
+        .collect::<Vec<_>>();
+
+    let (key_start, _) = node.key.into();
+    let selection_range = file.range_of((key_start, key_start + name_txt.len()).into());
+
+    #[allow(deprecated /* required field */)]
+    DocumentSymbol {
+        // editors reject symbols with empty names
+        name: if name_txt.is_empty() { " ".to_owned() } else { name_txt.to_owned() },
+        detail,
+        kind,
+        tags: None,
+        deprecated: None,
+        range: file.line_range(node.line_idx, node.last_line_idx),
+        selection_range,
+        children: if children.is_empty() { None } else { Some(children) },
+    }
+}
@@ -0,0 +1,340 @@
+// oraide - tools for OpenRA-based mod/game development
+// get the source code at https://github.com/Phrohdoh/oraide
+//
+// copyright (c)
+// - 2020 Taryn "Phrohdoh" Hill
+
+//! the MiniYaml files known to the server, either read from disk or as
+//! provided (possibly unsaved) by the client
+
+use {
+    std::{
+        collections::HashMap,
+        fs,
+        path::{
+            Path,
+            PathBuf,
+        },
+    },
+    lsp_types::{
+        InitializeParams,
+        Position,
+        Range,
+        Url,
+    },
+    oraide_miniyaml::{
+        decode_lossy,
+        package_paths_of,
+        Config,
+        respan_lines_of,
+        span_lines_of,
+        AbsByteIdxSpan,
+        Assets,
+        InvalidSequence,
+        LineCol,
+        LineIndex,
+        Manifest,
+        Schema,
+        SpannedLine,
+        SymbolIndex,
+        TextEdit,
+    },
+};
+
+/// a single MiniYaml file and the data derived from it
+pub(crate) struct File {
+    pub(crate) text: String,
+    pub(crate) lines: Vec<SpannedLine>,
+    pub(crate) line_index: LineIndex,
+
+    /// the bytes of the file on disk which aren't valid UTF-8, whose
+    /// replacements `text` still contains
+    pub(crate) invalid_sequences: Vec<InvalidSequence>,
+}
+
+impl File {
+    pub(crate) fn new(text: String) -> Self {
+        Self {
+            lines: span_lines_of(&text),
+            line_index: LineIndex::new(&text),
+            text,
+            invalid_sequences: vec![],
+        }
+    }
+
+    /// the absolute byte index of `pos`
+    pub(crate) fn abs_idx_of(&self, pos: Position) -> usize {
+        let line_col = LineCol {
+            line: pos.line as usize,
+            col: pos.character as usize,
+        };
+
+        self.line_index.abs_idx_of_utf16(&self.text, line_col)
+    }
+
+    pub(crate) fn position_of(&self, abs_idx: usize) -> Position {
+        let LineCol { line, col } = self.line_index.line_col_utf16(&self.text, abs_idx);
+        Position::new(line as u32, col as u32)
+    }
+
+    pub(crate) fn range_of(&self, span: AbsByteIdxSpan) -> Range {
+        let (start, end) = span.into();
+        Range::new(self.position_of(start), self.position_of(end))
+    }
+
+    /// range from the start of line `first_line_idx` to the end of line
+    /// `last_line_idx` (excluding its line-terminator)
+    pub(crate) fn line_range(&self, first_line_idx: usize, last_line_idx: usize) -> Range {
+        let (start, _) = self.lines[first_line_idx].raw.into();
+        let last_line = &self.lines[last_line_idx];
+        let (_, raw_end) = last_line.raw.into();
+        let end = last_line.term
+            .map_or(raw_end, |term| Into::<(usize, usize)>::into(term).0);
+
+        Range::new(self.position_of(start), self.position_of(end))
+    }
+}
+
+pub(crate) struct Workspace {
+    files: HashMap<PathBuf, File>,
+    pub(crate) index: SymbolIndex,
+    pub(crate) config: Config,
+
+    /// what the engine defines, read once from the configured schema
+    pub(crate) schema: Option<Schema>,
+
+    /// the files of the packages the mod mounts, which are only re-read when
+    /// those packages (or the files within them) change
+    assets: Option<CachedAssets>,
+}
+
+/// the assets of the packages `package_entries` of the mod in `mod_dir`
+struct CachedAssets {
+    mod_dir: PathBuf,
+    package_entries: Vec<String>,
+    package_paths: Vec<PathBuf>,
+    assets: Assets,
+}
+
+impl Workspace {
+    pub(crate) fn new(config: Config) -> Self {
+        Self {
+            files: HashMap::new(),
+            index: SymbolIndex::new(),
+            config,
+            schema: None,
+            assets: None,
+        }
+    }
+
+    /// the files of the packages which `manifest` mounts, read from disk only
+    /// if they weren't already read for the same packages
+    pub(crate) fn assets_of(&mut self, manifest: &Manifest) -> &Assets {
+        let package_entries = manifest.sections.get("Packages").cloned().unwrap_or_default();
+
+        let cached = match self.assets.take() {
+            Some(cached) if cached.mod_dir == manifest.mod_dir && cached.package_entries == package_entries => cached,
+            _ => CachedAssets {
+                mod_dir: manifest.mod_dir.clone(),
+                package_entries,
+                package_paths: package_paths_of(manifest),
+                assets: Assets::read(manifest),
+            },
+        };
+
+        &self.assets.insert(cached).assets
+    }
+
+    /// forget the files of the packages if `path` is one of them (or is
+    /// within one), returning `true` if it was
+    pub(crate) fn invalidate_assets(&mut self, path: &Path) -> bool {
+        let is_package_path = self.assets.as_ref().is_some_and(|cached| {
+            cached.package_paths.iter().any(|package_path| path.starts_with(package_path))
+        });
+
+        if is_package_path {
+            self.assets = None;
+        }
+
+        is_package_path
+    }
+
+    pub(crate) fn file(&self, path: &Path) -> Option<&File> {
+        self.files.get(path)
+    }
+
+    pub(crate) fn paths(&self) -> impl Iterator<Item = &Path> {
+        self.files.keys().map(PathBuf::as_path)
+    }
+
+    pub(crate) fn set_text(&mut self, path: PathBuf, text: String) {
+        // a client opening a file we read lossily sends back the same text,
+        // whose replacements are still those of the invalid bytes on disk
+        let invalid_sequences = match self.files.get(&path) {
+            Some(existing) if existing.text == text => existing.invalid_sequences.clone(),
+            _ => vec![],
+        };
+
+        self.insert(path, File { invalid_sequences, ..File::new(text) });
+    }
+
+    /// replace `range` of the file at `path` with `new_text`, re-spanning only
+    /// the affected lines
+    pub(crate) fn edit(&mut self, path: &Path, range: Range, new_text: String) {
+        let file = match self.files.get_mut(path) {
+            Some(file) => file,
+            None => return,
+        };
+
+        let replaced = (file.abs_idx_of(range.start), file.abs_idx_of(range.end));
+        let edit = TextEdit::new(replaced, new_text);
+
+        // replacements within the edit are gone, and those after it move
+        let (start, end) = replaced;
+        let len_delta = edit.len_delta();
+        file.invalid_sequences.retain(|invalid_sequence| {
+            invalid_sequence.text_idx + char::REPLACEMENT_CHARACTER.len_utf8() <= start
+                || invalid_sequence.text_idx >= end
+        });
+
+        for invalid_sequence in file.invalid_sequences.iter_mut().filter(|invalid_sequence| invalid_sequence.text_idx >= end) {
+            invalid_sequence.text_idx = (invalid_sequence.text_idx as isize + len_delta) as usize;
+        }
+
+        file.text = edit.apply_to(&file.text);
+        respan_lines_of(&file.text, &mut file.lines, &edit);
+        file.line_index = LineIndex::new(&file.text);
+
+        self.index.index_file_lines(path, &file.text, &file.lines);
+    }
+
+    /// replace the known contents of `path` with what is on disk (read
+    /// lossily, so a few invalid bytes don't hide the entire file)
+    pub(crate) fn reload(&mut self, path: &Path) {
+        match fs::read(path) {
+            Ok(bytes) => {
+                let decoded = decode_lossy(&bytes);
+                let file = File {
+                    invalid_sequences: decoded.invalid_sequences,
+                    ..File::new(decoded.text)
+                };
+
+                self.insert(path.to_owned(), file);
+            },
+            Err(_) => {
+                self.index.remove_file(path);
+                self.files.remove(path);
+            },
+        }
+    }
+
+    fn insert(&mut self, path: PathBuf, file: File) {
+        self.index.index_file_lines(path.clone(), &file.text, &file.lines);
+        self.files.insert(path, file);
+    }
+
+    /// recursively read every `.yaml` file beneath `dir` (which the config
+    /// doesn't ignore)
+    pub(crate) fn load_dir(&mut self, dir: &Path) {
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(_) => return,
+        };
+
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            let path = entry.path();
+            let is_hidden = entry.file_name().to_string_lossy().starts_with('.');
+
+            if is_hidden || self.config.is_ignored(&path) {
+                continue;
+            }
+
+            if path.is_dir() {
+                self.load_dir(&path);
+            } else if path.extension().and_then(|ext| ext.to_str()) == Some("yaml") {
+                self.reload(&path);
+            }
+        }
+    }
+}
+
+/// directories the client considers to be part of the workspace
+pub(crate) fn roots_of(params: &InitializeParams) -> Vec<PathBuf> {
+    match &params.workspace_folders {
+        Some(folders) => folders.iter()
+            .filter_map(|folder| folder.uri.to_file_path().ok())
+            .collect(),
+        #[allow(deprecated /* fallback for clients which don't send folders */)]
+        None => params.root_uri.iter()
+            .filter_map(|uri| uri.to_file_path().ok())
+            .collect(),
+    }
+}
+
+pub(crate) fn path_of(uri: &Url) -> PathBuf {
+    uri.to_file_path()
+        .unwrap_or_else(|_| PathBuf::from(uri.path()))
+}
+
+pub(crate) fn uri_of(path: &Path) -> Option<Url> {
+    Url::from_file_path(path).ok()
+}
+
+// ----- tests -----------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        oraide_miniyaml::decode_lossy,
+    };
+
+    #[test]
+    fn edits_move_and_remove_invalid_sequences() {
+        // arrange
+        let decoded = decode_lossy(b"a: \xFF\nb: \xFF\nc: \xFF\n");
+        let path = PathBuf::from("rules.yaml");
+
+        let mut workspace = Workspace::new(Config::default());
+        workspace.insert(path.clone(), File {
+            invalid_sequences: decoded.invalid_sequences,
+            ..File::new(decoded.text)
+        });
+
+        // act
+        workspace.edit(&path, Range::new(Position::new(0, 0), Position::new(0, 0)), "-".to_owned());
+        workspace.edit(&path, Range::new(Position::new(1, 3), Position::new(1, 4)), "x".to_owned());
+
+        // assert
+        let file = workspace.file(&path).unwrap();
+        assert_eq!(
+            vec![(3, 4), (13, 16)],
+            file.invalid_sequences.iter()
+                .map(|invalid_sequence| (invalid_sequence.byte_idx, invalid_sequence.text_idx))
+                .collect::<Vec<_>>(),
+        );
+        assert!(file.text[16..].starts_with(char::REPLACEMENT_CHARACTER));
+    }
+
+    #[test]
+    fn assets_are_forgotten_when_a_package_changes() {
+        // arrange
+        let engine_dir = Path::new("engine");
+        let manifest = Manifest::new(
+            &engine_dir.join("mods").join("ra").join("mod.yaml"),
+            "Packages:\n\t~^EngineDir|content\n",
+        );
+
+        let mut workspace = Workspace::new(Config::default());
+        workspace.assets_of(&manifest);
+
+        // act
+        let is_rules_invalidating = workspace.invalidate_assets(&engine_dir.join("mods").join("ra").join("rules.yaml"));
+        let is_content_invalidating = workspace.invalidate_assets(&engine_dir.join("content").join("mouse.shp"));
+
+        // assert
+        assert!(!is_rules_invalidating);
+        assert!(is_content_invalidating);
+        assert!(workspace.assets.is_none());
+    }
+}
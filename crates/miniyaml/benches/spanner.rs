@@ -0,0 +1,88 @@
+// oraide - tools for OpenRA-based mod/game development
+// get the source code at https://github.com/Phrohdoh/oraide
+//
+// copyright (c)
+// - 2020 Taryn "Phrohdoh" Hill
+
+//! Compares collecting every spanned-line up front with lazily iterating over
+//! them, run via `cargo bench -p oraide-miniyaml`.
+
+use {
+    criterion::{
+        black_box,
+        criterion_group,
+        criterion_main,
+        Criterion,
+    },
+    oraide_miniyaml::{
+        iter_spanned_lines_of,
+        span_lines_of,
+        SpannedLine,
+    },
+};
+
+/// a large document, built by repeating every file in `test-miniyaml-files`,
+/// with a single `Sentinel:` key at the very end
+fn big_doc() -> String {
+    let file_txts = [
+        include_str!("../../../test-miniyaml-files/exploding-barrel.yaml"),
+        include_str!("../../../test-miniyaml-files/simple.yaml"),
+        include_str!("../../../test-miniyaml-files/simple-mixed-line-terms.yaml"),
+    ];
+
+    let mut doc = String::new();
+    for _ in 0..500 {
+        for file_txt in file_txts.iter() {
+            doc.push_str(file_txt);
+            doc.push('\n');
+        }
+    }
+
+    doc.push_str("Sentinel: true\n");
+    doc
+}
+
+fn is_sentinel_key(doc: &str, line: &SpannedLine) -> bool {
+    match line.key {
+        Some(key) => doc[key].starts_with("Sentinel"),
+        None => false,
+    }
+}
+
+fn bench_spanner(c: &mut Criterion) {
+    let doc = big_doc();
+
+    let mut group = c.benchmark_group("spanner");
+
+    group.bench_function("span_lines_of", |b| b.iter(|| {
+        span_lines_of(black_box(&doc))
+    }));
+
+    group.bench_function("iter_spanned_lines_of (collect)", |b| b.iter(|| {
+        iter_spanned_lines_of(black_box(&doc)).collect::<Vec<_>>()
+    }));
+
+    group.bench_function("iter_spanned_lines_of (count)", |b| b.iter(|| {
+        iter_spanned_lines_of(black_box(&doc)).count()
+    }));
+
+    group.bench_function("iter_spanned_lines_of (first comment)", |b| b.iter(|| {
+        iter_spanned_lines_of(black_box(&doc))
+            .find(|line| line.comment.is_some())
+    }));
+
+    group.bench_function("span_lines_of (last key)", |b| b.iter(|| {
+        span_lines_of(black_box(&doc)).into_iter()
+            .find(|line| is_sentinel_key(&doc, line))
+    }));
+
+    group.bench_function("iter_spanned_lines_of (last key)", |b| b.iter(|| {
+        iter_spanned_lines_of(black_box(&doc))
+            .find(|line| is_sentinel_key(&doc, line))
+    }));
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_spanner);
+criterion_main!(benches);
@@ -0,0 +1,476 @@
+// oraide - tools for OpenRA-based mod/game development
+// get the source code at https://github.com/Phrohdoh/oraide
+//
+// copyright (c)
+// - 2020 Taryn "Phrohdoh" Hill
+
+#![deny(missing_docs)]
+
+//! This [module] exposes items used to find the files (e.g. sprites) of the
+//! packages which a mod's manifest mounts, and to count the frames of
+//! sprites.
+//!
+//! Each entry of the manifest's `Packages` is a folder or a zip file (which
+//! requires the `oramap` feature), written as one of:
+//!
+//! | entry                  | path                                      |
+//! |------------------------|-------------------------------------------|
+//! | `<mod-id>\|<path>`     | `<path>` in the directory of mod `<mod-id>` |
+//! | `$<mod-id>`            | the directory of mod `<mod-id>`           |
+//! | `^EngineDir\|<path>`   | `<path>` in the engine's directory        |
+//! | `<path>`               | `<path>` in the engine's directory        |
+//!
+//! where the engine's directory is the parent of the directory containing
+//! the mods.  A leading `~` marks a package as optional.
+//!
+//! [module]: https://doc.rust-lang.org/book/ch07-02-defining-modules-to-control-scope-and-privacy.html
+
+use {
+    std::{
+        collections::BTreeMap,
+        fs,
+        io,
+        path::{
+            Path,
+            PathBuf,
+        },
+    },
+    crate::{
+        map::zip_entries_of,
+        Manifest,
+    },
+};
+
+// ----- public interface ------------------------------------------------------
+
+/// The extensions, in the order they are tried, of the file of a sequence
+/// whose filename has none.
+pub const SPRITE_EXTENSIONS: &[&str] = &["shp", "png"];
+
+/// A file of a mounted package.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Asset {
+    /// path of the package (folder or zip file) containing the file
+    pub package: PathBuf,
+
+    /// how many frames the file has, if it is a sprite whose frames could be
+    /// counted (see [`frame_count_of`])
+    ///
+    /// [`frame_count_of`]: fn.frame_count_of.html
+    pub frame_count: Option<usize>,
+}
+
+/// A package which a mod mounts but whose files couldn't be read, as it is of
+/// a kind which can't be read (such as a `.mix`) or is in a user directory
+/// (such as `^SupportDir`).
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct UnreadPackage {
+    /// the package's entry in the manifest's `Packages`, sans `~`
+    pub entry: String,
+
+    /// `true` if the entry is marked optional (with a leading `~`)
+    pub is_optional: bool,
+}
+
+/// The files of the packages which a mod mounts.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct Assets {
+    /// each file, keyed by its `/`-separated path relative to its package
+    assets: BTreeMap<String, Asset>,
+
+    /// the packages whose files couldn't be read, in the order they are
+    /// mounted
+    pub unread_packages: Vec<UnreadPackage>,
+}
+
+impl Assets {
+    /// Read the names of the files of every package which `manifest` mounts
+    /// (and the frame counts of those which are sprites).
+    ///
+    /// Only the top-level files of folders are read, as the engine does, while
+    /// every file of a zip file is.  Optional packages which don't exist are
+    /// skipped.
+    pub fn read(manifest: &Manifest) -> Self {
+        let mut assets = Self::default();
+
+        let entries = manifest.sections.get("Packages")
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+
+        for entry in entries {
+            let (is_optional, entry) = match entry.strip_prefix('~') {
+                Some(entry) => (true, entry),
+                None => (false, entry.as_str()),
+            };
+
+            let is_read = match package_path_of(manifest, entry) {
+                Some(path) if path.is_dir() => assets.read_folder(&path).is_ok(),
+                Some(path) if is_zip(&path) && path.is_file() => match zip_entries_of(&path) {
+                    Ok(entries) => {
+                        for (name, bytes) in entries {
+                            let frame_count = frame_count_of(&name, &bytes);
+                            assets.insert(name, Asset { package: path.clone(), frame_count });
+                        }
+
+                        true
+                    },
+                    Err(_) => false,
+                },
+                // e.g. a `.mix`
+                Some(path) => !path.exists() && is_optional,
+                None => false,
+            };
+
+            if !is_read {
+                assets.unread_packages.push(UnreadPackage {
+                    entry: entry.to_owned(),
+                    is_optional,
+                });
+            }
+        }
+
+        assets
+    }
+
+    /// `true` if every package which the mod requires (i.e. which isn't
+    /// optional) was read, so a file which isn't among them doesn't exist
+    /// unless it is in one of the optional [`unread_packages`].
+    ///
+    /// [`unread_packages`]: #structfield.unread_packages
+    pub fn is_complete(&self) -> bool {
+        self.unread_packages.iter().all(|package| package.is_optional)
+    }
+
+    /// Add the file `name`, unless a package mounted earlier already has a
+    /// file of that name (which the engine would find first).
+    pub fn insert(&mut self, name: impl Into<String>, asset: Asset) {
+        self.assets.entry(name.into()).or_insert(asset);
+    }
+
+    /// The file `name`, a `/`-separated path relative to its package, if any
+    /// package has it.
+    pub fn get(&self, name: &str) -> Option<&Asset> {
+        self.assets.get(name)
+    }
+
+    /// The name and file of the sprite `filename`, which is tried as is, then
+    /// with each of [`SPRITE_EXTENSIONS`] if it has no extension.
+    ///
+    /// [`SPRITE_EXTENSIONS`]: constant.SPRITE_EXTENSIONS.html
+    pub fn sprite(&self, filename: &str) -> Option<(String, &Asset)> {
+        if let Some(asset) = self.get(filename) {
+            return Some((filename.to_owned(), asset));
+        }
+
+        if Path::new(filename).extension().is_some() {
+            return None;
+        }
+
+        SPRITE_EXTENSIONS.iter()
+            .map(|ext| format!("{}.{}", filename, ext))
+            .find_map(|name| self.get(&name).map(|asset| (name, asset)))
+    }
+}
+
+/// How many frames the sprite `name`, whose contents are `bytes`, has, if it
+/// is a `.shp` (of either the Tiberian Dawn or Tiberian Sun format) or a
+/// `.png` (which has a single frame, unless its `FrameSize` text chunk splits
+/// it into several, which its `FrameAmount` text chunk may limit).
+pub fn frame_count_of(name: &str, bytes: &[u8]) -> Option<usize> {
+    let ext = Path::new(name).extension()?.to_str()?.to_ascii_lowercase();
+
+    match ext.as_str() {
+        "shp" => shp_frame_count_of(bytes),
+        "png" => png_frame_count_of(bytes),
+        _ => None,
+    }
+}
+
+/// The paths of the packages which `manifest` mounts, in the order they are
+/// mounted, skipping those which can't be resolved (e.g. those in
+/// `^SupportDir`).
+pub fn package_paths_of(manifest: &Manifest) -> Vec<PathBuf> {
+    manifest.sections.get("Packages").into_iter()
+        .flatten()
+        .map(|entry| entry.strip_prefix('~').unwrap_or(entry))
+        .filter_map(|entry| package_path_of(manifest, entry))
+        .collect()
+}
+
+// ----- private implementation details ----------------------------------------
+
+impl Assets {
+    fn read_folder(&mut self, path: &Path) -> io::Result<()> {
+        for dir_entry in fs::read_dir(path)?.filter_map(Result::ok) {
+            let name = dir_entry.file_name().to_string_lossy().into_owned();
+            let entry_path = dir_entry.path();
+
+            // only sprites are read, as only their frames are counted
+            let frame_count = if is_sprite(&name) && entry_path.is_file() {
+                fs::read(&entry_path).ok()
+                    .and_then(|bytes| frame_count_of(&name, &bytes))
+            } else {
+                None
+            };
+
+            self.insert(name, Asset { package: path.to_path_buf(), frame_count });
+        }
+
+        Ok(())
+    }
+}
+
+/// the path of the package `entry` (sans `~`), if it can be resolved
+fn package_path_of(manifest: &Manifest, entry: &str) -> Option<PathBuf> {
+    let mods_dir = manifest.mod_dir.parent().unwrap_or_else(|| Path::new(""));
+    let engine_dir = mods_dir.parent().unwrap_or_else(|| Path::new(""));
+
+    if let Some(mod_id) = entry.strip_prefix('$') {
+        return Some(mods_dir.join(mod_id));
+    }
+
+    if let Some(engine_entry) = entry.strip_prefix("^EngineDir") {
+        let path = engine_entry.strip_prefix('|').unwrap_or("");
+        return Some(engine_dir.join(path));
+    }
+
+    // other user directories (e.g. `^SupportDir`) depend on the machine
+    if entry.starts_with('^') {
+        return None;
+    }
+
+    if entry.contains('|') {
+        return Some(manifest.path_of(entry));
+    }
+
+    Some(engine_dir.join(entry))
+}
+
+fn is_zip(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("zip") || ext.eq_ignore_ascii_case("oramap"))
+}
+
+fn is_sprite(name: &str) -> bool {
+    Path::new(name).extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| SPRITE_EXTENSIONS.iter().any(|sprite_ext| ext.eq_ignore_ascii_case(sprite_ext)))
+}
+
+fn u16_at(bytes: &[u8], offset: usize) -> Option<usize> {
+    let le_bytes = bytes.get(offset..offset + 2)?;
+    Some(u16::from_le_bytes([le_bytes[0], le_bytes[1]]) as usize)
+}
+
+fn u32_be_at(bytes: &[u8], offset: usize) -> Option<usize> {
+    let be_bytes = bytes.get(offset..offset + 4)?;
+    Some(u32::from_be_bytes([be_bytes[0], be_bytes[1], be_bytes[2], be_bytes[3]]) as usize)
+}
+
+/// the frame count of a Tiberian Sun `.shp` (whose header is a zero `u16`,
+/// then the width, height, and frame count) or else of a Tiberian Dawn one
+/// (whose header starts with the frame count, followed by 5 more `u16`s and
+/// an 8-byte offset for each frame plus 2)
+fn shp_frame_count_of(bytes: &[u8]) -> Option<usize> {
+    const TD_HEADER_LEN: usize = 14;
+    const TD_OFFSET_LEN: usize = 8;
+
+    let first_u16 = u16_at(bytes, 0)?;
+    if first_u16 == 0 {
+        return u16_at(bytes, 6);
+    }
+
+    let is_td = bytes.len() >= TD_HEADER_LEN + (first_u16 + 2) * TD_OFFSET_LEN;
+    if is_td { Some(first_u16) } else { None }
+}
+
+fn png_frame_count_of(bytes: &[u8]) -> Option<usize> {
+    const SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+    if !bytes.starts_with(SIGNATURE) {
+        return None;
+    }
+
+    let mut size = None;
+    let mut frame_size = None;
+    let mut frame_amount = None;
+
+    let mut offset = SIGNATURE.len();
+    while let Some(len) = u32_be_at(bytes, offset) {
+        let chunk_type = bytes.get(offset + 4..offset + 8)?;
+        let data = bytes.get(offset + 8..offset + 8 + len)?;
+
+        match chunk_type {
+            b"IHDR" => size = Some((u32_be_at(data, 0)?, u32_be_at(data, 4)?)),
+            b"tEXt" => {
+                let sep_idx = data.iter().position(|&byte| byte == 0)?;
+                let (keyword, txt) = (&data[..sep_idx], String::from_utf8_lossy(&data[sep_idx + 1..]));
+                let ints = || txt.split(',')
+                    .map(|item_txt| item_txt.trim().parse::<usize>().ok())
+                    .collect::<Option<Vec<_>>>();
+
+                match keyword {
+                    b"FrameSize" => frame_size = match ints()?.as_slice() {
+                        &[width, height] => Some((width, height)),
+                        _ => None,
+                    },
+                    b"FrameAmount" => frame_amount = ints()?.first().copied(),
+                    _ => {},
+                }
+            },
+            b"IEND" => break,
+            _ => {},
+        }
+
+        // length, type, data, and CRC
+        offset += 4 + 4 + len + 4;
+    }
+
+    let (width, height) = size?;
+    let frame_count = match frame_size {
+        Some((0, _)) | Some((_, 0)) => return None,
+        Some((frame_width, frame_height)) => (width / frame_width) * (height / frame_height),
+        None => 1,
+    };
+
+    Some(frame_amount.map_or(frame_count, |amount| amount.min(frame_count)))
+}
+
+// ----- tests -----------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn png_chunk(chunk_type: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut chunk = (data.len() as u32).to_be_bytes().to_vec();
+        chunk.extend(chunk_type);
+        chunk.extend(data);
+        chunk.extend(&[0; 4]);
+        chunk
+    }
+
+    #[test]
+    fn frame_counts() {
+        // arrange
+        let mut td_shp = vec![3, 0];
+        td_shp.resize(14 + 5 * 8, 0);
+
+        let ts_shp = [0, 0, 24, 0, 24, 0, 12, 0];
+
+        let mut ihdr = vec![];
+        ihdr.extend(&64u32.to_be_bytes());
+        ihdr.extend(&32u32.to_be_bytes());
+        ihdr.extend(&[8, 3, 0, 0, 0]);
+
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        png.extend(png_chunk(b"IHDR", &ihdr));
+        let single_png = [png.clone(), png_chunk(b"IEND", &[])].concat();
+
+        png.extend(png_chunk(b"tEXt", b"FrameSize\x0016,16"));
+        let sheet_png = [png.clone(), png_chunk(b"IEND", &[])].concat();
+
+        png.extend(png_chunk(b"tEXt", b"FrameAmount\x005"));
+        png.extend(png_chunk(b"IEND", &[]));
+
+        // act
+        let actual = vec![
+            frame_count_of("e1.shp", &td_shp),
+            frame_count_of("e1.shp", &td_shp[..20]),
+            frame_count_of("E1.SHP", &ts_shp),
+            frame_count_of("e1.png", &single_png),
+            frame_count_of("e1.png", &sheet_png),
+            frame_count_of("e1.png", &png),
+            frame_count_of("e1.tem", &td_shp),
+        ];
+
+        // assert
+        assert_eq!(vec![Some(3), None, Some(12), Some(1), Some(8), Some(5), None], actual);
+    }
+
+    #[test]
+    fn package_paths() {
+        // arrange
+        let manifest = Manifest {
+            mod_dir: PathBuf::from("engine/mods/ra"),
+            ..Manifest::default()
+        };
+
+        // act
+        let actual = ["ra|bits", "$common", "^EngineDir|mods/common", "^SupportDir|Content/ra", "main.zip"].iter()
+            .map(|entry| package_path_of(&manifest, entry))
+            .collect::<Vec<_>>();
+
+        // assert
+        assert_eq!(
+            vec![
+                Some(PathBuf::from("engine/mods/ra/bits")),
+                Some(PathBuf::from("engine/mods/common")),
+                Some(PathBuf::from("engine/mods/common")),
+                None,
+                Some(PathBuf::from("engine/main.zip")),
+            ],
+            actual,
+        );
+    }
+
+    #[test]
+    fn reads_packages() {
+        // arrange
+        let temp_dir = tempfile::tempdir().unwrap();
+        let engine_dir = temp_dir.path().join("engine");
+        let mod_dir = engine_dir.join("mods/ra");
+
+        fs::create_dir_all(mod_dir.join("bits")).unwrap();
+        fs::create_dir_all(mod_dir.join("uibits")).unwrap();
+        fs::create_dir_all(engine_dir.join("mods/common")).unwrap();
+
+        let mut td_shp = vec![3, 0];
+        td_shp.resize(14 + 5 * 8, 0);
+        fs::write(mod_dir.join("bits/e1.shp"), &td_shp).unwrap();
+        fs::write(engine_dir.join("mods/common/chrome.yaml"), "").unwrap();
+        fs::write(mod_dir.join("conquer.mix"), "").unwrap();
+
+        let manifest = Manifest::new(&mod_dir.join("mod.yaml"), &[
+            "Packages:\n",
+            "\t~^SupportDir|Content/ra/v2/\n",
+            "\t~^SupportDir|Content/ra/v2/expand/\n",
+            "\t^EngineDir\n",
+            "\t$ra: ra\n",
+            "\t^EngineDir|mods/common: common\n",
+            "\t~main.mix\n",
+            "\t~mods/ra/conquer.mix\n",
+            "\tra|bits\n",
+            "\tra|uibits\n",
+        ].concat());
+
+        let mut required_manifest = manifest.clone();
+        required_manifest.sections.insert("Packages".to_owned(), vec!["mods/ra/conquer.mix".to_owned()]);
+
+        // act
+        let assets = Assets::read(&manifest);
+        let required_assets = Assets::read(&required_manifest);
+
+        // assert
+        assert_eq!(
+            vec![
+                ("^SupportDir|Content/ra/v2/", true),
+                ("^SupportDir|Content/ra/v2/expand/", true),
+                ("mods/ra/conquer.mix", true),
+            ],
+            assets.unread_packages.iter()
+                .map(|package| (package.entry.as_str(), package.is_optional))
+                .collect::<Vec<_>>(),
+        );
+        assert!(assets.is_complete());
+
+        assert_eq!(
+            Some((mod_dir.join("bits"), Some(3))),
+            assets.get("e1.shp").map(|asset| (asset.package.clone(), asset.frame_count)),
+        );
+        assert!(assets.get("chrome.yaml").is_some());
+
+        assert!(!required_assets.is_complete());
+    }
+}
@@ -0,0 +1,474 @@
+// oraide - tools for OpenRA-based mod/game development
+// get the source code at https://github.com/Phrohdoh/oraide
+//
+// copyright (c)
+// - 2020 Taryn "Phrohdoh" Hill
+
+#![deny(missing_docs)]
+
+//! This [module] exposes items used to read a project's configuration file,
+//! [`CONFIG_FILE_NAME`], which is found by searching upward from a directory:
+//!
+//! ```toml
+//! # the mod's manifest (paths are relative to this file)
+//! manifest = "mods/example/mod.yaml"
+//!
+//! # the schema describing what the engine defines (see [`Schema`])
+//! schema = "schema.json"
+//!
+//! # files which are never read (matched against paths relative to this file)
+//! ignore = ["mods/example/maps/wip-*/**"]
+//!
+//! [format]
+//! # `"tabs"` or a number of spaces
+//! indent = "tabs"
+//!
+//! [lints]
+//! # `"allow"` (which disables the lint), `"info"`, `"warning"`, or `"error"`
+//! unreferenced-actor = "allow"
+//! unused-abstract = "error"
+//! ```
+//!
+//! [module]: https://doc.rust-lang.org/book/ch07-02-defining-modules-to-control-scope-and-privacy.html
+//! [`CONFIG_FILE_NAME`]: constant.CONFIG_FILE_NAME.html
+//! [`Schema`]: struct.Schema.html
+
+use {
+    std::{
+        collections::BTreeMap,
+        error,
+        fmt,
+        fs,
+        path::{
+            Path,
+            PathBuf,
+        },
+    },
+    glob::{
+        MatchOptions,
+        Pattern,
+    },
+    toml::Value,
+    crate::{
+        IndentStyle,
+        LintConfig,
+        Linter,
+        MixedIndentation,
+        Schema,
+        Severity,
+        UNUSED_SUPPRESSION,
+    },
+};
+
+// ----- public interface ------------------------------------------------------
+
+/// The name of a project's configuration file.
+pub const CONFIG_FILE_NAME: &str = "oraide.toml";
+
+/// A project's configuration, where every path has been resolved relative to
+/// the directory containing the configuration file.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Config {
+    /// path of the configuration file, which is empty if there is none
+    pub path: PathBuf,
+
+    /// the manifest (`mod.yaml`) of the project's mod
+    pub manifest_path: Option<PathBuf>,
+
+    /// the schema describing what the engine defines, read by [`schema`]
+    ///
+    /// [`schema`]: #method.schema
+    pub schema_path: Option<PathBuf>,
+
+    /// the configuration of each lint, keyed by lint code
+    pub lints: BTreeMap<String, LintConfig>,
+
+    /// how written MiniYaml is indented, if configured (with tabs otherwise)
+    pub indent: Option<IndentStyle>,
+
+    /// patterns matching paths (relative to the configuration file) of files
+    /// which are never read
+    pub ignore: Vec<Pattern>,
+}
+
+/// Why a configuration file could not be read.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ConfigError {
+    /// path of the configuration file
+    pub path: PathBuf,
+
+    /// what is wrong with it
+    pub kind: ConfigErrorKind,
+}
+
+/// What is wrong with a configuration file.
+#[derive(Clone, PartialEq, Debug)]
+pub enum ConfigErrorKind {
+    /// the file could not be read
+    Io(String),
+
+    /// the file is not valid TOML
+    Toml(String),
+
+    /// a key which is not recognized (e.g. a misspelling)
+    UnknownKey {
+        /// the key, dotted if nested (e.g. `format.indnet`)
+        key: String,
+    },
+
+    /// a value which is not of the expected form
+    InvalidValue {
+        /// the key, dotted if nested (e.g. `format.indent`)
+        key: String,
+
+        /// description of the expected form
+        expected: &'static str,
+    },
+
+    /// an `ignore` pattern which is not a valid glob
+    InvalidPattern {
+        /// the pattern
+        pattern: String,
+
+        /// why it is invalid
+        message: String,
+    },
+
+    /// the schema could not be read, or is not a schema
+    InvalidSchema {
+        /// path of the schema
+        path: PathBuf,
+
+        /// what is wrong with it
+        message: String,
+    },
+}
+
+impl Config {
+    /// Read the first configuration file found in `start_dir` or any of its
+    /// ancestors, if there is one.
+    pub fn discover(start_dir: &Path) -> Result<Option<Self>, ConfigError> {
+        let config_path = start_dir.ancestors()
+            .map(|dir| dir.join(CONFIG_FILE_NAME))
+            .find(|config_path| config_path.is_file());
+
+        let config_path = match config_path {
+            Some(config_path) => config_path,
+            None => return Ok(None),
+        };
+
+        let text = fs::read_to_string(&config_path)
+            .map_err(|err| ConfigError {
+                path: config_path.clone(),
+                kind: ConfigErrorKind::Io(err.to_string()),
+            })?;
+
+        Self::from_toml(config_path, &text).map(Some)
+    }
+
+    /// Read `text`, the contents of the configuration file at `path`.
+    pub fn from_toml(path: impl Into<PathBuf>, text: &str) -> Result<Self, ConfigError> {
+        let path = path.into();
+
+        config_of_toml(&path, text)
+            .map_err(|kind| ConfigError { path, kind })
+    }
+
+    /// The directory containing the configuration file, which paths in it are
+    /// relative to.
+    pub fn dir(&self) -> &Path {
+        self.path.parent().unwrap_or_else(|| Path::new(""))
+    }
+
+    /// `true` if `path` matches any `ignore` pattern.
+    pub fn is_ignored(&self, path: &Path) -> bool {
+        let relative_path = path.strip_prefix(self.dir()).unwrap_or(path);
+        let options = MatchOptions {
+            require_literal_separator: true,
+            ..MatchOptions::default()
+        };
+
+        self.ignore.iter()
+            .any(|pattern| pattern.matches_path_with(relative_path, options))
+    }
+
+    /// Read the schema, if one is configured.
+    pub fn schema(&self) -> Result<Option<Schema>, ConfigError> {
+        let schema_path = match &self.schema_path {
+            Some(schema_path) => schema_path,
+            None => return Ok(None),
+        };
+
+        let invalid = |message| ConfigError {
+            path: self.path.clone(),
+            kind: ConfigErrorKind::InvalidSchema {
+                path: schema_path.clone(),
+                message,
+            },
+        };
+
+        let text = fs::read_to_string(schema_path)
+            .map_err(|err| invalid(err.to_string()))?;
+
+        Schema::from_json(&text)
+            .map(Some)
+            .map_err(invalid)
+    }
+
+    /// Apply the configuration of every lint to `linter`, including checking
+    /// indentation against the configured style (if any), returning the
+    /// configured codes which name none of its lints (nor `unused-suppression`),
+    /// whose configuration has no effect.
+    pub fn configure(&self, linter: &mut Linter) -> Vec<&str> {
+        for (code, lint_config) in &self.lints {
+            linter.configure(code.clone(), *lint_config);
+        }
+
+        if let Some(indent) = self.indent {
+            linter.add_lint(MixedIndentation::new(indent));
+        }
+
+        self.lints.keys()
+            .map(String::as_str)
+            .filter(|&code| code != UNUSED_SUPPRESSION && !linter.lints().any(|lint| lint.code() == code))
+            .collect()
+    }
+}
+
+// ----- external trait impls --------------------------------------------------
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: ", self.path.display())?;
+
+        match &self.kind {
+            ConfigErrorKind::Io(message) | ConfigErrorKind::Toml(message) => {
+                write!(f, "{}", message)
+            },
+            ConfigErrorKind::UnknownKey { key } => write!(f, "unknown key {:?}", key),
+            ConfigErrorKind::InvalidValue { key, expected } => {
+                write!(f, "value of {:?} must be {}", key, expected)
+            },
+            ConfigErrorKind::InvalidPattern { pattern, message } => {
+                write!(f, "ignore pattern {:?} is invalid: {}", pattern, message)
+            },
+            ConfigErrorKind::InvalidSchema { path, message } => {
+                write!(f, "schema {} is invalid: {}", path.display(), message)
+            },
+        }
+    }
+}
+
+impl error::Error for ConfigError {}
+
+// ----- private implementation details ----------------------------------------
+
+fn config_of_toml(path: &Path, text: &str) -> Result<Config, ConfigErrorKind> {
+    let table = match text.parse::<Value>() {
+        Ok(Value::Table(table)) => table,
+        Ok(_) => return Err(ConfigErrorKind::Toml("expected a table".to_owned())),
+        Err(err) => return Err(ConfigErrorKind::Toml(err.to_string())),
+    };
+
+    let mut config = Config {
+        path: path.to_path_buf(),
+        ..Config::default()
+    };
+
+    let dir = config.dir().to_path_buf();
+
+    for (key, value) in &table {
+        let invalid = |expected| ConfigErrorKind::InvalidValue { key: key.clone(), expected };
+
+        match key.as_str() {
+            "manifest" => {
+                let manifest_path = value.as_str().ok_or_else(|| invalid("a path"))?;
+                config.manifest_path = Some(dir.join(manifest_path));
+            },
+            "schema" => {
+                let schema_path = value.as_str().ok_or_else(|| invalid("a path"))?;
+                config.schema_path = Some(dir.join(schema_path));
+            },
+            "ignore" => {
+                let patterns = value.as_array().ok_or_else(|| invalid("an array of globs"))?;
+
+                for pattern in patterns {
+                    let pattern = pattern.as_str().ok_or_else(|| invalid("an array of globs"))?;
+                    let pattern = Pattern::new(pattern)
+                        .map_err(|err| ConfigErrorKind::InvalidPattern {
+                            pattern: pattern.to_owned(),
+                            message: err.msg.to_owned(),
+                        })?;
+
+                    config.ignore.push(pattern);
+                }
+            },
+            "format" => {
+                let format_table = value.as_table().ok_or_else(|| invalid("a table"))?;
+
+                for (format_key, format_value) in format_table {
+                    let dotted_key = format!("format.{}", format_key);
+
+                    match format_key.as_str() {
+                        "indent" => {
+                            config.indent = indent_style_of(format_value)
+                                .map(Some)
+                                .ok_or(ConfigErrorKind::InvalidValue {
+                                    key: dotted_key,
+                                    expected: "`\"tabs\"` or a number of spaces",
+                                })?;
+                        },
+                        _ => return Err(ConfigErrorKind::UnknownKey { key: dotted_key }),
+                    }
+                }
+            },
+            "lints" => {
+                let lints_table = value.as_table().ok_or_else(|| invalid("a table"))?;
+
+                // lints may be registered from outside of this crate, so
+                // unknown codes are only reported once a linter is configured
+                for (code, level) in lints_table {
+                    let lint_config = level.as_str()
+                        .and_then(lint_config_of)
+                        .ok_or_else(|| ConfigErrorKind::InvalidValue {
+                            key: format!("lints.{}", code),
+                            expected: "one of `\"allow\"`, `\"info\"`, `\"warning\"`, or `\"error\"`",
+                        })?;
+
+                    config.lints.insert(code.clone(), lint_config);
+                }
+            },
+            _ => return Err(ConfigErrorKind::UnknownKey { key: key.clone() }),
+        }
+    }
+
+    Ok(config)
+}
+
+fn indent_style_of(value: &Value) -> Option<IndentStyle> {
+    match value {
+        Value::String(txt) => txt.parse().ok(),
+        Value::Integer(count) if *count > 0 => Some(IndentStyle::Spaces(*count as usize)),
+        _ => None,
+    }
+}
+
+fn lint_config_of(level: &str) -> Option<LintConfig> {
+    let enabled_with = |severity| LintConfig {
+        is_enabled: true,
+        severity: Some(severity),
+    };
+
+    match level {
+        "allow" => Some(LintConfig {
+            is_enabled: false,
+            severity: None,
+        }),
+        "info" => Some(enabled_with(Severity::Info)),
+        "warning" => Some(enabled_with(Severity::Warning)),
+        "error" => Some(enabled_with(Severity::Error)),
+        _ => None,
+    }
+}
+
+// ----- tests -----------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_every_setting() {
+        // arrange
+        let text = r#"
+manifest = "mods/ra/mod.yaml"
+schema = "schema.json"
+ignore = ["mods/ra/maps/wip-*/**"]
+
+[format]
+indent = 4
+
+[lints]
+unreferenced-actor = "allow"
+unused-abstract = "error"
+"#;
+
+        // act
+        let config = Config::from_toml("project/oraide.toml", text).unwrap();
+
+        // assert
+        assert_eq!(Some(PathBuf::from("project/mods/ra/mod.yaml")), config.manifest_path);
+        assert_eq!(Some(PathBuf::from("project/schema.json")), config.schema_path);
+        assert_eq!(Some(IndentStyle::Spaces(4)), config.indent);
+
+        assert_eq!(
+            vec![
+                ("unreferenced-actor", LintConfig { is_enabled: false, severity: None }),
+                ("unused-abstract", LintConfig { is_enabled: true, severity: Some(Severity::Error) }),
+            ],
+            config.lints.iter()
+                .map(|(code, lint_config)| (code.as_str(), *lint_config))
+                .collect::<Vec<_>>(),
+        );
+
+        assert!(config.is_ignored(Path::new("project/mods/ra/maps/wip-desert/map.yaml")));
+        assert!(!config.is_ignored(Path::new("project/mods/ra/maps/desert/map.yaml")));
+        assert!(!config.is_ignored(Path::new("project/mods/ra/wip-rules.yaml")));
+    }
+
+    #[test]
+    fn errors() {
+        let kind_of = |text| Config::from_toml("oraide.toml", text).unwrap_err().kind;
+
+        assert_eq!(
+            ConfigErrorKind::UnknownKey { key: "format.indnet".into() },
+            kind_of("[format]\nindnet = 2\n"),
+        );
+
+        assert_eq!(
+            ConfigErrorKind::InvalidValue {
+                key: "lints.unused-abstract".into(),
+                expected: "one of `\"allow\"`, `\"info\"`, `\"warning\"`, or `\"error\"`",
+            },
+            kind_of("[lints]\nunused-abstract = \"deny\"\n"),
+        );
+
+        assert_eq!(
+            ConfigErrorKind::InvalidValue { key: "manifest".into(), expected: "a path" },
+            kind_of("manifest = 1\n"),
+        );
+    }
+
+    #[test]
+    fn unknown_lint_codes() {
+        // arrange
+        let config = Config::from_toml(
+            "oraide.toml",
+            "[lints]\nduplicat-key = \"allow\"\nduplicate-key = \"error\"\nunused-suppression = \"allow\"\n",
+        ).unwrap();
+
+        let mut linter = Linter::new();
+
+        // act
+        let unknown_codes = config.configure(&mut linter);
+
+        // assert
+        assert_eq!(vec!["duplicat-key"], unknown_codes);
+        assert_eq!(Some(Severity::Error), linter.config_of("duplicate-key").severity);
+    }
+
+    #[test]
+    fn unreadable_schema() {
+        // arrange
+        let config = Config::from_toml("project/oraide.toml", "schema = \"missing.json\"\n").unwrap();
+
+        // act
+        let kind = config.schema().unwrap_err().kind;
+
+        // assert
+        match kind {
+            ConfigErrorKind::InvalidSchema { path, .. } => assert_eq!(PathBuf::from("project/missing.json"), path),
+            kind => panic!("unexpected {:?}", kind),
+        }
+    }
+}
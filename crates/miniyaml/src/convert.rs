@@ -0,0 +1,386 @@
+// oraide - tools for OpenRA-based mod/game development
+// get the source code at https://github.com/Phrohdoh/oraide
+//
+// copyright (c)
+// - 2020 Taryn "Phrohdoh" Hill
+
+#![deny(missing_docs)]
+
+//! This [module] exposes items used to convert a MiniYaml [`Tree`] to JSON
+//! and YAML documents, and JSON documents back into MiniYaml text.
+//!
+//! Each node becomes a member of a JSON object (whose members are in document
+//! order) named by the node's key, including any markers and `@` suffix:
+//!
+//! - a node without children is its value (`null` if it has none)
+//! - a node with children is an object of its children, with the node's value
+//!   (if it has one) in the [`VALUE_KEY`] member
+//!
+//! Comments are not preserved.
+//!
+//! [module]: https://doc.rust-lang.org/book/ch07-02-defining-modules-to-control-scope-and-privacy.html
+//! [`Tree`]: ../struct.Tree.html
+//! [`VALUE_KEY`]: constant.VALUE_KEY.html
+
+use {
+    std::{
+        error,
+        fmt,
+    },
+    serde_json::{
+        Map,
+        Value,
+    },
+    crate::{
+        Node,
+        Tree,
+    },
+};
+
+// ----- public interface ------------------------------------------------------
+
+/// The name of the JSON object member holding the value of a node which also
+/// has children.
+pub const VALUE_KEY: &str = "$value";
+
+/// Why a document could not be converted.
+#[derive(Clone, PartialEq, Debug)]
+pub enum ConvertError {
+    /// sibling nodes with the same key, which can't be members of one object
+    DuplicateKey {
+        /// the repeated key
+        key: String,
+
+        /// index of the line defining the second node with `key`
+        line_idx: usize,
+    },
+
+    /// a JSON document which is not an object
+    NotAnObject,
+
+    /// a JSON value which has no MiniYaml equivalent (e.g. a nested array)
+    UnsupportedValue {
+        /// the key of the member holding the value
+        key: String,
+    },
+
+    /// a JSON key or value which contains a line-terminator
+    MultiLineText {
+        /// the key of the member holding the text
+        key: String,
+    },
+}
+
+/// Convert `tree`, derived from `doc`, into a JSON object.
+pub fn json_of_tree(doc: &str, tree: &Tree) -> Result<Value, ConvertError> {
+    json_of_nodes(doc, &tree.nodes)
+        .map(Value::Object)
+}
+
+/// Convert `tree`, derived from `doc`, into a YAML document (of the same shape
+/// as [`json_of_tree`]'s result).
+///
+/// [`json_of_tree`]: fn.json_of_tree.html
+pub fn yaml_of_tree(doc: &str, tree: &Tree) -> Result<String, ConvertError> {
+    let members = json_of_nodes(doc, &tree.nodes)?;
+
+    let mut yaml = String::new();
+    write_yaml(&mut yaml, &members, 0);
+    Ok(yaml)
+}
+
+/// Convert a JSON object (of the same shape as [`json_of_tree`]'s result)
+/// into MiniYaml text, indented with tabs.
+///
+/// Numbers and booleans are written as-is, arrays (of anything but arrays and
+/// objects) as comma-separated lists, and any `#` which is not already escaped
+/// is escaped so that it isn't mistaken for the start of a comment.
+///
+/// [`json_of_tree`]: fn.json_of_tree.html
+pub fn miniyaml_of_json(json: &Value) -> Result<String, ConvertError> {
+    miniyaml_of_json_indented(json, "\t")
+}
+
+/// Convert a JSON object into MiniYaml text, as [`miniyaml_of_json`] does, with
+/// each level of nesting indented by `indent_txt`.
+///
+/// [`miniyaml_of_json`]: fn.miniyaml_of_json.html
+pub fn miniyaml_of_json_indented(json: &Value, indent_txt: &str) -> Result<String, ConvertError> {
+    let members = match json {
+        Value::Object(members) => members,
+        _ => return Err(ConvertError::NotAnObject),
+    };
+
+    let mut miniyaml = String::new();
+    write_miniyaml(&mut miniyaml, members, indent_txt, 0)?;
+    Ok(miniyaml)
+}
+
+// ----- external trait impls --------------------------------------------------
+
+impl fmt::Display for ConvertError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConvertError::DuplicateKey { key, line_idx } => {
+                write!(f, "duplicate key {:?} on line {}", key, line_idx + 1)
+            },
+            ConvertError::NotAnObject => write!(f, "document is not an object"),
+            ConvertError::UnsupportedValue { key } => {
+                write!(f, "value of {:?} can't be represented in MiniYaml", key)
+            },
+            ConvertError::MultiLineText { key } => {
+                write!(f, "key or value of {:?} spans multiple lines", key)
+            },
+        }
+    }
+}
+
+impl error::Error for ConvertError {}
+
+// ----- private implementation details ----------------------------------------
+
+fn json_of_nodes(doc: &str, nodes: &[Node]) -> Result<Map<String, Value>, ConvertError> {
+    let mut members = Map::new();
+
+    for node in nodes {
+        let key = doc[node.key].trim_end().to_owned();
+        if members.contains_key(&key) {
+            return Err(ConvertError::DuplicateKey {
+                key,
+                line_idx: node.line_idx,
+            });
+        }
+
+        let opt_value = node.value
+            .map(|span| Value::String(doc[span].trim_end().to_owned()));
+
+        let value = if node.children.is_empty() {
+            opt_value.unwrap_or(Value::Null)
+        } else {
+            let mut children = Map::new();
+            if let Some(value) = opt_value {
+                children.insert(VALUE_KEY.to_owned(), value);
+            }
+
+            children.extend(json_of_nodes(doc, &node.children)?);
+            Value::Object(children)
+        };
+
+        members.insert(key, value);
+    }
+
+    Ok(members)
+}
+
+fn write_yaml(yaml: &mut String, members: &Map<String, Value>, depth: usize) {
+    for (key, value) in members {
+        yaml.push_str(&"  ".repeat(depth));
+
+        // JSON strings are valid (double-quoted) YAML scalars, and quoting
+        // keeps markers such as `^` and `-` from being interpreted by YAML
+        yaml.push_str(&Value::String(key.clone()).to_string());
+        yaml.push(':');
+
+        match value {
+            Value::Object(children) => {
+                yaml.push('\n');
+                write_yaml(yaml, children, depth + 1);
+            },
+            scalar => {
+                yaml.push(' ');
+                yaml.push_str(&scalar.to_string());
+                yaml.push('\n');
+            },
+        }
+    }
+}
+
+fn write_miniyaml(
+    miniyaml: &mut String,
+    members: &Map<String, Value>,
+    indent_txt: &str,
+    depth: usize,
+) -> Result<(), ConvertError> {
+    for (key, value) in members {
+        if key == VALUE_KEY && depth > 0 {
+            // written as the value of the parent
+            continue;
+        }
+
+        let (opt_value_txt, opt_children) = match value {
+            Value::Object(children) => {
+                let opt_value_txt = match children.get(VALUE_KEY) {
+                    Some(value) => text_of_scalar(key, value)?,
+                    None => None,
+                };
+
+                (opt_value_txt, Some(children))
+            },
+            value => (text_of_scalar(key, value)?, None),
+        };
+
+        if key.contains(is_line_term) {
+            return Err(ConvertError::MultiLineText { key: key.clone() });
+        }
+
+        miniyaml.push_str(&indent_txt.repeat(depth));
+        miniyaml.push_str(key);
+        miniyaml.push(':');
+
+        if let Some(value_txt) = opt_value_txt.filter(|txt| !txt.is_empty()) {
+            miniyaml.push(' ');
+            miniyaml.push_str(&escape_comments(&value_txt));
+        }
+
+        miniyaml.push('\n');
+
+        if let Some(children) = opt_children {
+            write_miniyaml(miniyaml, children, indent_txt, depth + 1)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// the MiniYaml text of `value`, the value of the member named `key`
+fn text_of_scalar(key: &str, value: &Value) -> Result<Option<String>, ConvertError> {
+    let unsupported = || ConvertError::UnsupportedValue { key: key.to_owned() };
+
+    let txt = match value {
+        Value::Null => return Ok(None),
+        Value::String(txt) => txt.clone(),
+        Value::Bool(_) | Value::Number(_) => value.to_string(),
+        Value::Array(items) => items.iter()
+            .map(|item| match item {
+                Value::Array(_) | Value::Object(_) => Err(unsupported()),
+                item => Ok(text_of_scalar(key, item)?.unwrap_or_default()),
+            })
+            .collect::<Result<Vec<_>, _>>()?
+            .join(", "),
+        Value::Object(_) => return Err(unsupported()),
+    };
+
+    if txt.contains(is_line_term) {
+        return Err(ConvertError::MultiLineText { key: key.to_owned() });
+    }
+
+    Ok(Some(txt.trim().to_owned()))
+}
+
+fn is_line_term(ch: char) -> bool {
+    ch == '\n' || ch == '\r'
+}
+
+/// prefix every `#` in `txt`, which isn't already, with `\`
+fn escape_comments(txt: &str) -> String {
+    let mut escaped = String::with_capacity(txt.len());
+    let mut prev_ch = None;
+
+    for ch in txt.chars() {
+        if ch == '#' && prev_ch != Some('\\') {
+            escaped.push('\\');
+        }
+
+        escaped.push(ch);
+        prev_ch = Some(ch);
+    }
+
+    escaped
+}
+
+// ----- tests -----------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            span_lines_of,
+            tree_of,
+        },
+    };
+
+    fn json_of_doc(doc: &str) -> Result<Value, ConvertError> {
+        let lines = span_lines_of(doc);
+        json_of_tree(doc, &tree_of(doc, &lines))
+    }
+
+    fn assert_round_trips(doc: &str) {
+        // act
+        let json = json_of_doc(doc).unwrap();
+        let miniyaml = miniyaml_of_json(&json).unwrap();
+
+        // assert
+        assert_eq!(json, json_of_doc(&miniyaml).unwrap(), "miniyaml: {:?}", miniyaml);
+    }
+
+    #[test]
+    fn round_trips_test_files() {
+        assert_round_trips(include_str!("../../../test-miniyaml-files/exploding-barrel.yaml"));
+        assert_round_trips(include_str!("../../../test-miniyaml-files/simple.yaml"));
+        assert_round_trips(include_str!("../../../test-miniyaml-files/simple-mixed-line-terms.yaml"));
+    }
+
+    #[test]
+    fn preserves_order_suffixes_and_values_of_parents() {
+        // arrange
+        let doc = "E2:\n\tInherits@b: ^B\n\tInherits@a: ^A\n\tArmament: Gun\n\t\tRecoil: 0\n";
+
+        // act
+        let json = json_of_doc(doc).unwrap();
+        let yaml = yaml_of_tree(doc, &tree_of(doc, &span_lines_of(doc))).unwrap();
+
+        // assert
+        assert_eq!(
+            r#"{"E2":{"Inherits@b":"^B","Inherits@a":"^A","Armament":{"$value":"Gun","Recoil":"0"}}}"#,
+            json.to_string(),
+        );
+
+        assert_eq!(
+            [
+                "\"E2\":\n",
+                "  \"Inherits@b\": \"^B\"\n",
+                "  \"Inherits@a\": \"^A\"\n",
+                "  \"Armament\":\n",
+                "    \"$value\": \"Gun\"\n",
+                "    \"Recoil\": \"0\"\n",
+            ].join(""),
+            yaml,
+        );
+    }
+
+    #[test]
+    fn json_to_miniyaml() {
+        // arrange
+        let json = serde_json::json!({
+            "^Tank": {
+                "Cost": 800,
+                "Categories": ["tank", "vehicle"],
+                "Name": "Tank #1",
+                "Description": null,
+            },
+        });
+
+        // act
+        let actual = miniyaml_of_json(&json);
+
+        // assert
+        assert_eq!(
+            Ok("^Tank:\n\tCost: 800\n\tCategories: tank, vehicle\n\tName: Tank \\#1\n\tDescription:\n".to_owned()),
+            actual,
+        );
+    }
+
+    #[test]
+    fn duplicate_and_unsupported() {
+        assert_eq!(
+            Err(ConvertError::DuplicateKey { key: "A".into(), line_idx: 2 }),
+            json_of_doc("E1:\n\tA: 1\n\tA: 2\n"),
+        );
+
+        assert_eq!(
+            Err(ConvertError::UnsupportedValue { key: "A".into() }),
+            miniyaml_of_json(&serde_json::json!({ "A": [[1]] })),
+        );
+    }
+}
@@ -0,0 +1,139 @@
+// oraide - tools for OpenRA-based mod/game development
+// get the source code at https://github.com/Phrohdoh/oraide
+//
+// copyright (c)
+// - 2020 Taryn "Phrohdoh" Hill
+
+#![deny(missing_docs)]
+
+//! This [module] exposes items used to turn the bytes of a file into a
+//! document, without giving up on files which are not entirely valid UTF-8.
+//!
+//! [module]: https://doc.rust-lang.org/book/ch07-02-defining-modules-to-control-scope-and-privacy.html
+
+use {
+    std::{
+        char,
+        str,
+    },
+};
+
+// ----- public interface ------------------------------------------------------
+
+/// A sequence of bytes which is not valid UTF-8.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct InvalidSequence {
+    /// index, in the original bytes, of the first byte of the sequence
+    pub byte_idx: usize,
+
+    /// number of bytes in the sequence
+    pub len: usize,
+
+    /// index, in the decoded document, of the `U+FFFD REPLACEMENT CHARACTER`
+    /// which replaced the sequence
+    pub text_idx: usize,
+}
+
+/// The result of [`decode_lossy`].
+///
+/// [`decode_lossy`]: fn.decode_lossy.html
+#[derive(Clone, PartialEq, Debug)]
+pub struct Decoded {
+    /// the decoded document, in which each invalid sequence has been replaced
+    /// with `U+FFFD REPLACEMENT CHARACTER`
+    pub text: String,
+
+    /// every invalid sequence, ordered by position
+    pub invalid_sequences: Vec<InvalidSequence>,
+}
+
+/// Decode `bytes` as UTF-8, replacing (and recording the position of) each
+/// invalid sequence instead of failing.
+///
+/// Note that a replaced sequence need not be 3 bytes long (the length of
+/// `U+FFFD` in UTF-8), so spans into [`text`] after a replacement don't
+/// correspond to the same positions in `bytes`.
+///
+/// [`text`]: struct.Decoded.html#structfield.text
+pub fn decode_lossy(bytes: &[u8]) -> Decoded {
+    let mut text = String::with_capacity(bytes.len());
+    let mut invalid_sequences = vec![];
+    let mut remaining_start_idx = 0;
+
+    loop {
+        let remaining_bytes = &bytes[remaining_start_idx..];
+
+        match str::from_utf8(remaining_bytes) {
+            Ok(valid_txt) => {
+                text.push_str(valid_txt);
+                break;
+            },
+            Err(err) => {
+                let valid_len = err.valid_up_to();
+                let valid_txt = str::from_utf8(&remaining_bytes[..valid_len])
+                    .expect("bytes up to `valid_up_to` are valid UTF-8");
+
+                text.push_str(valid_txt);
+                let text_idx = text.len();
+                text.push(char::REPLACEMENT_CHARACTER);
+
+                // `None` means the input ended mid-sequence
+                let invalid_len = err.error_len()
+                    .unwrap_or(remaining_bytes.len() - valid_len);
+
+                invalid_sequences.push(InvalidSequence {
+                    byte_idx: remaining_start_idx + valid_len,
+                    len: invalid_len,
+                    text_idx,
+                });
+
+                remaining_start_idx += valid_len + invalid_len;
+            },
+        }
+    }
+
+    Decoded {
+        text,
+        invalid_sequences,
+    }
+}
+
+// ----- tests -----------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_utf8_is_unchanged() {
+        // arrange
+        let bytes = "\u{FEFF}Name: 请\n".as_bytes();
+
+        // act
+        let actual = decode_lossy(bytes);
+
+        // assert
+        assert_eq!("\u{FEFF}Name: 请\n", actual.text);
+        assert!(actual.invalid_sequences.is_empty());
+    }
+
+    #[test]
+    fn invalid_sequences_are_replaced_and_recorded() {
+        // arrange
+        let bytes = b"a: \xFF\xFE\nb: \xE8\xAF";
+
+        // act
+        let actual = decode_lossy(bytes);
+
+        // assert
+        assert_eq!("a: \u{FFFD}\u{FFFD}\nb: \u{FFFD}", actual.text);
+        assert_eq!(
+            vec![
+                InvalidSequence { byte_idx: 3, len: 1, text_idx: 3 },
+                InvalidSequence { byte_idx: 4, len: 1, text_idx: 6 },
+                InvalidSequence { byte_idx: 9, len: 2, text_idx: 13 },
+            ],
+            actual.invalid_sequences,
+        );
+    }
+}
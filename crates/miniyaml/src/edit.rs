@@ -0,0 +1,116 @@
+// oraide - tools for OpenRA-based mod/game development
+// get the source code at https://github.com/Phrohdoh/oraide
+//
+// copyright (c)
+// - 2020 Taryn "Phrohdoh" Hill
+
+#![deny(missing_docs)]
+
+//! This [module] exposes items used to describe and apply changes to the text
+//! of a document.
+//!
+//! [module]: https://doc.rust-lang.org/book/ch07-02-defining-modules-to-control-scope-and-privacy.html
+
+use {
+    crate::AbsByteIdxSpan,
+};
+
+// ----- public interface ------------------------------------------------------
+
+/// A replacement of the text spanned by [`replaced`] with [`new_text`].
+///
+/// An insertion replaces an empty span, a deletion has empty `new_text`.
+///
+/// [`replaced`]: struct.TextEdit.html#structfield.replaced
+/// [`new_text`]: struct.TextEdit.html#structfield.new_text
+#[derive(Clone, PartialEq, Debug)]
+pub struct TextEdit {
+    /// span, in the document before the edit, of the text being replaced
+    pub replaced: AbsByteIdxSpan,
+
+    /// text replacing the spanned text
+    pub new_text: String,
+}
+
+impl TextEdit {
+    /// Create an edit replacing `replaced` with `new_text`.
+    pub fn new(replaced: impl Into<AbsByteIdxSpan>, new_text: impl Into<String>) -> Self {
+        Self {
+            replaced: replaced.into(),
+            new_text: new_text.into(),
+        }
+    }
+
+    /// Apply this edit to `doc`, producing the document after the edit.
+    pub fn apply_to(&self, doc: &str) -> String {
+        let (start, end) = self.replaced.into();
+
+        let mut ret = String::with_capacity(doc.len() - (end - start) + self.new_text.len());
+        ret.push_str(&doc[..start]);
+        ret.push_str(&self.new_text);
+        ret.push_str(&doc[end..]);
+        ret
+    }
+
+    /// The change in document length, in bytes, caused by this edit.
+    pub fn len_delta(&self) -> isize {
+        let (start, end) = self.replaced.into();
+        self.new_text.len() as isize - (end - start) as isize
+    }
+}
+
+/// Apply `edits`, each spanning text of `doc` (not of the document after any
+/// other edit), producing the document after all of them.
+///
+/// Edits are applied in order of position, and an edit which overlaps an
+/// earlier (by position) edit is skipped.  Text no edit replaces is copied
+/// verbatim.
+pub fn apply_edits(doc: &str, edits: &[TextEdit]) -> String {
+    let mut sorted_edits = edits.iter().collect::<Vec<_>>();
+    sorted_edits.sort_by_key(|edit| {
+        let (start, end) = edit.replaced.into();
+        (start, end)
+    });
+
+    let mut ret = String::with_capacity(doc.len());
+    let mut copied_up_to = 0;
+
+    for edit in sorted_edits {
+        let (start, end) = edit.replaced.into();
+        if start < copied_up_to {
+            continue;
+        }
+
+        ret.push_str(&doc[copied_up_to..start]);
+        ret.push_str(&edit.new_text);
+        copied_up_to = end;
+    }
+
+    ret.push_str(&doc[copied_up_to..]);
+    ret
+}
+
+// ----- tests -----------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply_edits_skips_overlapping_edits() {
+        // arrange
+        let doc = "E1:\n\tHealth:\n\t\tHP: 100\n";
+        let edits = vec![
+            TextEdit::new((19, 22), "250"),
+            TextEdit::new((0, 2), "E2"),
+            TextEdit::new((20, 20), "oops"),
+            TextEdit::new((4, 4), "\t"),
+        ];
+
+        // act
+        let actual = apply_edits(doc, &edits);
+
+        // assert
+        assert_eq!("E2:\n\t\tHealth:\n\t\tHP: 250\n", actual);
+    }
+}
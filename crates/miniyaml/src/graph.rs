@@ -0,0 +1,311 @@
+// oraide - tools for OpenRA-based mod/game development
+// get the source code at https://github.com/Phrohdoh/oraide
+//
+// copyright (c)
+// - 2020 Taryn "Phrohdoh" Hill
+
+#![deny(missing_docs)]
+
+//! This [module] exposes a graph of the `Inherits` relationships between the
+//! top-level nodes of a set of [`Rules`], which can be rendered as [Graphviz]
+//! DOT or [Mermaid] for visualization.
+//!
+//! [module]: https://doc.rust-lang.org/book/ch07-02-defining-modules-to-control-scope-and-privacy.html
+//! [`Rules`]: ../struct.Rules.html
+//! [Graphviz]: https://graphviz.org/doc/info/lang.html
+//! [Mermaid]: https://mermaid-js.github.io/mermaid/#/flowchart
+
+use {
+    std::collections::{
+        BTreeSet,
+        HashSet,
+    },
+    crate::Rules,
+};
+
+// ----- public interface ------------------------------------------------------
+
+/// Top-level nodes and the `Inherits` edges between them.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct InheritsGraph {
+    /// every node, in the order it was first seen (either as a top-level node
+    /// or as the target of an `Inherits`)
+    names: Vec<String>,
+
+    /// `(child, parent)` indices into `names`, in document order
+    edges: Vec<(usize, usize)>,
+}
+
+impl InheritsGraph {
+    /// Build the graph of every top-level node of `rules`, with an edge from
+    /// each node to every node named by one of its `Inherits` children.
+    pub fn of_rules(rules: &Rules) -> Self {
+        let mut graph = Self::default();
+
+        for node in rules.nodes() {
+            if node.key.starts_with('-') {
+                continue;
+            }
+
+            let child_idx = graph.idx_of_or_insert(&node.key);
+
+            let parent_names = node.children.iter()
+                .filter(|child| child.key == "Inherits" || child.key.starts_with("Inherits@"))
+                .filter_map(|child| child.value.as_deref());
+
+            for parent_name in parent_names {
+                let parent_idx = graph.idx_of_or_insert(parent_name);
+                graph.edges.push((child_idx, parent_idx));
+            }
+        }
+
+        graph
+    }
+
+    /// `name` and every node it (transitively) inherits from.
+    pub fn ancestors_of(&self, name: &str) -> BTreeSet<String> {
+        self.reachable_from(name, |(child_idx, parent_idx)| (child_idx, parent_idx))
+    }
+
+    /// `name` and every node which (transitively) inherits from it.
+    pub fn descendants_of(&self, name: &str) -> BTreeSet<String> {
+        self.reachable_from(name, |(child_idx, parent_idx)| (parent_idx, child_idx))
+    }
+
+    /// The graph of only the nodes named in `names` (and the edges between
+    /// them).
+    pub fn retain(&self, names: &BTreeSet<String>) -> Self {
+        let mut graph = Self::default();
+
+        for name in self.names.iter().filter(|name| names.contains(*name)) {
+            graph.idx_of_or_insert(name);
+        }
+
+        for &(child_idx, parent_idx) in &self.edges {
+            let (child, parent) = (&self.names[child_idx], &self.names[parent_idx]);
+            if names.contains(child) && names.contains(parent) {
+                let edge = (graph.idx_of_or_insert(child), graph.idx_of_or_insert(parent));
+                graph.edges.push(edge);
+            }
+        }
+
+        graph
+    }
+
+    /// Every `(child, parent)` edge, in document order.
+    pub fn edges(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.edges.iter()
+            .map(move |&(child_idx, parent_idx)| {
+                (self.names[child_idx].as_str(), self.names[parent_idx].as_str())
+            })
+    }
+
+    /// Every `(child, parent)` edge which is part of an inheritance cycle.
+    pub fn cycle_edges(&self) -> HashSet<(&str, &str)> {
+        self.edges.iter()
+            .filter(|&&(child_idx, parent_idx)| self.reaches(parent_idx, child_idx))
+            .map(|&(child_idx, parent_idx)| {
+                (self.names[child_idx].as_str(), self.names[parent_idx].as_str())
+            })
+            .collect()
+    }
+
+    /// Render as a Graphviz DOT digraph, in which abstract nodes are dashed
+    /// and edges which are part of a cycle are red.
+    pub fn to_dot(&self) -> String {
+        let cycle_edges = self.cycle_edges();
+        let mut dot = String::from("digraph inherits {\n    rankdir=BT;\n");
+
+        for name in &self.names {
+            let attrs = if is_abstract(name) { " [style=dashed]" } else { "" };
+            dot.push_str(&format!("    \"{}\"{};\n", escape_dot(name), attrs));
+        }
+
+        for (child, parent) in self.edges() {
+            let attrs = if cycle_edges.contains(&(child, parent)) { " [color=red]" } else { "" };
+            dot.push_str(&format!("    \"{}\" -> \"{}\"{};\n", escape_dot(child), escape_dot(parent), attrs));
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Render as a Mermaid flowchart, in which abstract nodes are dashed and
+    /// edges which are part of a cycle are red.
+    pub fn to_mermaid(&self) -> String {
+        let cycle_edges = self.cycle_edges();
+        let mut mermaid = String::from("graph BT\n");
+
+        // node ids are indices, since keys may contain characters which are
+        // not allowed in ids (such as `^` and `@`)
+        for (name_idx, name) in self.names.iter().enumerate() {
+            let class = if is_abstract(name) { ":::abstract" } else { "" };
+            mermaid.push_str(&format!("    n{}[\"{}\"]{}\n", name_idx, escape_mermaid(name), class));
+        }
+
+        let mut cycle_edge_idxs = vec![];
+        for (edge_idx, &(child_idx, parent_idx)) in self.edges.iter().enumerate() {
+            mermaid.push_str(&format!("    n{} --> n{}\n", child_idx, parent_idx));
+
+            let edge = (self.names[child_idx].as_str(), self.names[parent_idx].as_str());
+            if cycle_edges.contains(&edge) {
+                cycle_edge_idxs.push(edge_idx);
+            }
+        }
+
+        mermaid.push_str("    classDef abstract stroke-dasharray: 5 5\n");
+
+        for edge_idx in cycle_edge_idxs {
+            mermaid.push_str(&format!("    linkStyle {} stroke:red\n", edge_idx));
+        }
+
+        mermaid
+    }
+}
+
+// ----- private implementation details ----------------------------------------
+
+impl InheritsGraph {
+    fn idx_of_or_insert(&mut self, name: &str) -> usize {
+        match self.names.iter().position(|existing| existing == name) {
+            Some(name_idx) => name_idx,
+            None => {
+                self.names.push(name.to_owned());
+                self.names.len() - 1
+            },
+        }
+    }
+
+    /// `name` and every node reachable from it, where `orient` maps each
+    /// `(child, parent)` edge to `(from, to)`
+    fn reachable_from(
+        &self,
+        name: &str,
+        orient: impl Fn((usize, usize)) -> (usize, usize),
+    ) -> BTreeSet<String> {
+        let start_idx = match self.names.iter().position(|existing| existing == name) {
+            Some(name_idx) => name_idx,
+            None => return BTreeSet::new(),
+        };
+
+        let mut to_idxs_by_from_idx = vec![vec![]; self.names.len()];
+        for &edge in &self.edges {
+            let (from_idx, to_idx) = orient(edge);
+            to_idxs_by_from_idx[from_idx].push(to_idx);
+        }
+
+        let mut seen = vec![false; self.names.len()];
+        let mut stack = vec![start_idx];
+
+        while let Some(from_idx) = stack.pop() {
+            if seen[from_idx] {
+                continue;
+            }
+
+            seen[from_idx] = true;
+            stack.extend(&to_idxs_by_from_idx[from_idx]);
+        }
+
+        self.names.iter()
+            .zip(seen)
+            .filter(|(_, is_seen)| *is_seen)
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    /// `true` if `to_idx` is an ancestor of (or is) `from_idx`
+    fn reaches(&self, from_idx: usize, to_idx: usize) -> bool {
+        self.ancestors_of(&self.names[from_idx])
+            .contains(&self.names[to_idx])
+    }
+}
+
+fn is_abstract(name: &str) -> bool {
+    name.starts_with('^')
+}
+
+fn escape_dot(name: &str) -> String {
+    name.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn escape_mermaid(name: &str) -> String {
+    name.replace('"', "#quot;")
+}
+
+// ----- tests -----------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph_of(doc: &str) -> InheritsGraph {
+        let mut rules = Rules::new();
+        rules.add_file("rules.yaml", doc);
+        InheritsGraph::of_rules(&rules)
+    }
+
+    #[test]
+    fn ancestors_descendants_and_cycles() {
+        // arrange
+        let graph = graph_of("^A:\n\tInherits: ^B\n^B:\n\tInherits: ^A\nE1:\n\tInherits: ^A\n\tInherits@x: ^X\nE2:\n\tInherits: E1\n");
+
+        // act
+        let ancestors = graph.ancestors_of("E2");
+        let descendants = graph.descendants_of("E1");
+        let cycle_edges = graph.cycle_edges();
+
+        // assert
+        assert_eq!(
+            vec!["E1", "E2", "^A", "^B", "^X"],
+            ancestors.iter().map(String::as_str).collect::<Vec<_>>(),
+        );
+
+        assert_eq!(
+            vec!["E1", "E2"],
+            descendants.iter().map(String::as_str).collect::<Vec<_>>(),
+        );
+
+        assert_eq!(
+            vec![("^A", "^B"), ("^B", "^A")].into_iter().collect::<HashSet<_>>(),
+            cycle_edges,
+        );
+    }
+
+    #[test]
+    fn renders_dot_and_mermaid() {
+        // arrange
+        let graph = graph_of("^A:\n\tInherits: ^A\nE1:\n\tInherits: ^A\nE2:\n")
+            .retain(&vec!["^A".to_owned(), "E1".to_owned()].into_iter().collect());
+
+        // act
+        let dot = graph.to_dot();
+        let mermaid = graph.to_mermaid();
+
+        // assert
+        assert_eq!(
+            [
+                "digraph inherits {\n",
+                "    rankdir=BT;\n",
+                "    \"^A\" [style=dashed];\n",
+                "    \"E1\";\n",
+                "    \"^A\" -> \"^A\" [color=red];\n",
+                "    \"E1\" -> \"^A\";\n",
+                "}\n",
+            ].join(""),
+            dot,
+        );
+
+        assert_eq!(
+            [
+                "graph BT\n",
+                "    n0[\"^A\"]:::abstract\n",
+                "    n1[\"E1\"]\n",
+                "    n0 --> n0\n",
+                "    n1 --> n0\n",
+                "    classDef abstract stroke-dasharray: 5 5\n",
+                "    linkStyle 0 stroke:red\n",
+            ].join(""),
+            mermaid,
+        );
+    }
+}
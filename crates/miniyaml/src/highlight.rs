@@ -0,0 +1,154 @@
+// oraide - tools for OpenRA-based mod/game development
+// get the source code at https://github.com/Phrohdoh/oraide
+//
+// copyright (c)
+// - 2020 Taryn "Phrohdoh" Hill
+
+#![deny(missing_docs)]
+
+//! This [module] exposes items used to classify the text of spanned-lines
+//! for syntax-highlighting, which (unlike highlighting MiniYaml as YAML)
+//! understands escaped comments, key markers, and instance suffixes.
+//!
+//! [module]: https://doc.rust-lang.org/book/ch07-02-defining-modules-to-control-scope-and-privacy.html
+
+use {
+    crate::{
+        kind_of_value,
+        list_items_of,
+        parse_key,
+        SpannedLine,
+        AbsByteIdxSpan,
+        ValueKind,
+    },
+};
+
+// ----- public interface ------------------------------------------------------
+
+/// What a highlighted piece of text is.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum HighlightClass {
+    /// a key's name, sans markers and instance suffix
+    KeyName,
+
+    /// the `^` which marks a key as abstract
+    AbstractMarker,
+
+    /// the `-` which marks a key as a removal
+    RemovalMarker,
+
+    /// a key's `@` instance suffix, including the `@`
+    InstanceSuffix,
+
+    /// the `:` between a key and its value
+    KeySeparator,
+
+    /// a value (or a single item of a comma-separated value)
+    Value(ValueKind),
+
+    /// a comment, including the leading `#`
+    Comment,
+}
+
+/// A classified span of text.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Highlight {
+    /// the span of the highlighted text, which never crosses a line boundary
+    pub span: AbsByteIdxSpan,
+
+    /// what the text is
+    pub class: HighlightClass,
+}
+
+/// Classify the components of `lines`, which were spanned from `doc`.
+///
+/// The result is ordered by position and no two highlights overlap.
+pub fn highlights_of(doc: &str, lines: &[SpannedLine]) -> Vec<Highlight> {
+    let mut highlights = vec![];
+
+    let mut push = |span: AbsByteIdxSpan, class: HighlightClass| {
+        let (start, end) = span.into();
+        if start < end {
+            highlights.push(Highlight { span, class });
+        }
+    };
+
+    for line in lines {
+        if let Some(key_span) = line.key {
+            let parts = parse_key(doc, key_span);
+
+            if let Some(span) = parts.removal_marker {
+                push(span, HighlightClass::RemovalMarker);
+            }
+
+            if let Some(span) = parts.abstract_marker {
+                push(span, HighlightClass::AbstractMarker);
+            }
+
+            push(parts.name, HighlightClass::KeyName);
+
+            if let Some(span) = parts.instance {
+                push(span, HighlightClass::InstanceSuffix);
+            }
+        }
+
+        if let Some(span) = line.key_sep {
+            push(span, HighlightClass::KeySeparator);
+        }
+
+        if let Some(value_span) = line.value {
+            for item_span in list_items_of(doc, value_span) {
+                let kind = kind_of_value(&doc[item_span]);
+                push(item_span, HighlightClass::Value(kind));
+            }
+        }
+
+        if let Some(span) = line.comment {
+            push(span, HighlightClass::Comment);
+        }
+    }
+
+    highlights
+}
+
+// ----- tests -----------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::span_lines_of,
+    };
+
+    #[test]
+    fn classifies_every_component() {
+        // arrange
+        let doc = "-^Armament@primary: 5c0, true # gun\nName: \\#1";
+
+        let expected_highlights = vec![
+            ("-", HighlightClass::RemovalMarker),
+            ("^", HighlightClass::AbstractMarker),
+            ("Armament", HighlightClass::KeyName),
+            ("@primary", HighlightClass::InstanceSuffix),
+            (":", HighlightClass::KeySeparator),
+            ("5c0", HighlightClass::Value(ValueKind::WDist)),
+            ("true", HighlightClass::Value(ValueKind::Boolean)),
+            ("# gun", HighlightClass::Comment),
+            ("Name", HighlightClass::KeyName),
+            (":", HighlightClass::KeySeparator),
+            ("\\#1", HighlightClass::Value(ValueKind::Text)),
+        ];
+
+        // act
+        let lines = span_lines_of(doc);
+        let actual_highlights = highlights_of(doc, &lines).into_iter()
+            .map(|highlight| (&doc[highlight.span], highlight.class))
+            .collect::<Vec<_>>();
+
+        // assert
+        assert_eq!(
+            expected_highlights,
+            actual_highlights,
+        );
+    }
+}
@@ -0,0 +1,182 @@
+// oraide - tools for OpenRA-based mod/game development
+// get the source code at https://github.com/Phrohdoh/oraide
+//
+// copyright (c)
+// - 2020 Taryn "Phrohdoh" Hill
+
+#![deny(missing_docs)]
+
+//! This [module] exposes a cross-file index of top-level keys (actors,
+//! weapons, templates, etc.), which is how definitions are looked up across
+//! all of the files of a mod.
+//!
+//! [module]: https://doc.rust-lang.org/book/ch07-02-defining-modules-to-control-scope-and-privacy.html
+
+use {
+    std::{
+        collections::BTreeMap,
+        path::{
+            Path,
+            PathBuf,
+        },
+    },
+    crate::{
+        AbsByteIdxSpan,
+        parse_key,
+        span_lines_of,
+        tree_of,
+        SpannedLine,
+    },
+};
+
+// ----- public interface ------------------------------------------------------
+
+/// A top-level key defined in an indexed file.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Symbol {
+    /// the key's text, sans trailing whitespace (e.g. `^Soldier`)
+    pub name: String,
+
+    /// span of the key in the file it was defined in
+    pub key: AbsByteIdxSpan,
+
+    /// index of the line, in the file it was defined in, which defines it
+    pub line_idx: usize,
+
+    /// index of the last line (inclusive) of the symbol's block
+    pub last_line_idx: usize,
+
+    /// `true` if the key is abstract (starts with `^`)
+    pub is_abstract: bool,
+}
+
+/// Top-level keys of any number of files, keyed by file path.
+///
+/// Keys which remove a definition (`-Foo:`) are not symbols.
+#[derive(Clone, Debug, Default)]
+pub struct SymbolIndex {
+    symbols_by_path: BTreeMap<PathBuf, Vec<Symbol>>,
+}
+
+impl SymbolIndex {
+    /// Create an empty index.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Index (or re-index) the file at `path`, the contents of which are `doc`.
+    pub fn index_file(&mut self, path: impl Into<PathBuf>, doc: &str) {
+        self.index_file_lines(path, doc, &span_lines_of(doc));
+    }
+
+    /// Index (or re-index) the file at `path`, the contents of which are `doc`
+    /// which has already been spanned into `lines`.
+    pub fn index_file_lines(
+        &mut self,
+        path: impl Into<PathBuf>,
+        doc: &str,
+        lines: &[SpannedLine],
+    ) {
+        let tree = tree_of(doc, lines);
+
+        let symbols = tree.nodes.iter()
+            .filter_map(|node| {
+                let parts = parse_key(doc, node.key);
+                if parts.is_removal() {
+                    return None;
+                }
+
+                Some(Symbol {
+                    name: doc[node.key].trim_end().to_owned(),
+                    key: node.key,
+                    line_idx: node.line_idx,
+                    last_line_idx: node.last_line_idx,
+                    is_abstract: parts.is_abstract(),
+                })
+            })
+            .collect();
+
+        self.symbols_by_path.insert(path.into(), symbols);
+    }
+
+    /// Forget every symbol of the file at `path`.
+    pub fn remove_file(&mut self, path: &Path) {
+        self.symbols_by_path.remove(path);
+    }
+
+    /// Iterate over every indexed symbol, ordered by file path then position.
+    pub fn iter(&self) -> impl Iterator<Item = (&Path, &Symbol)> {
+        self.symbols_by_path.iter()
+            .flat_map(|(path, symbols)| symbols.iter()
+                .map(move |symbol| (path.as_path(), symbol)))
+    }
+
+    /// Iterate over every symbol whose name contains `query`, ignoring case.
+    ///
+    /// An empty `query` matches every symbol.
+    pub fn search<'index>(
+        &'index self,
+        query: &str,
+    ) -> impl Iterator<Item = (&'index Path, &'index Symbol)> {
+        let query = query.to_lowercase();
+        self.iter()
+            .filter(move |(_, symbol)| symbol.name.to_lowercase().contains(&query))
+    }
+
+    /// Iterate over every definition of `name`.
+    pub fn definitions_of<'index>(
+        &'index self,
+        name: &'index str,
+    ) -> impl Iterator<Item = (&'index Path, &'index Symbol)> {
+        self.iter()
+            .filter(move |(_, symbol)| symbol.name == name)
+    }
+}
+
+// ----- tests -----------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn indexes_top_level_keys_across_files() {
+        // arrange
+        let mut index = SymbolIndex::new();
+
+        // act
+        index.index_file("rules/infantry.yaml", "^Soldier:\n\tHealth:\nE2:\n\tInherits: ^Soldier\n-E3:\n");
+        index.index_file("weapons/explosions.yaml", "large-barrel-explode:\n\tWarhead@1Dam: SpreadDamage\n");
+
+        // assert
+        let actual_names = index.iter()
+            .map(|(path, symbol)| (path.to_str().unwrap(), symbol.name.as_str(), symbol.is_abstract))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            vec![
+                ("rules/infantry.yaml", "^Soldier", true),
+                ("rules/infantry.yaml", "E2", false),
+                ("weapons/explosions.yaml", "large-barrel-explode", false),
+            ],
+            actual_names,
+        );
+    }
+
+    #[test]
+    fn reindexing_replaces_and_search_ignores_case() {
+        // arrange
+        let mut index = SymbolIndex::new();
+        index.index_file("a.yaml", "E1:\nE2:\n");
+
+        // act
+        index.index_file("a.yaml", "e2:\nbarrel:\n");
+        let actual_matches = index.search("E")
+            .map(|(_, symbol)| symbol.name.as_str())
+            .collect::<Vec<_>>();
+
+        // assert
+        assert_eq!(vec!["e2", "barrel"], actual_matches);
+        assert_eq!(1, index.definitions_of("barrel").count());
+    }
+}
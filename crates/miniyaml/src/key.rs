@@ -0,0 +1,184 @@
+// oraide - tools for OpenRA-based mod/game development
+// get the source code at https://github.com/Phrohdoh/oraide
+//
+// copyright (c)
+// - 2020 Taryn "Phrohdoh" Hill
+
+#![deny(missing_docs)]
+
+//! This [module] exposes items used to break a MiniYaml key, such as
+//! `-Inherits@experience` or `^Soldier`, into its components.
+//!
+//! [module]: https://doc.rust-lang.org/book/ch07-02-defining-modules-to-control-scope-and-privacy.html
+
+use {
+    crate::{
+        AbsByteIdx,
+        AbsByteIdxSpan,
+    },
+};
+
+// ----- public interface ------------------------------------------------------
+
+/// The components of a MiniYaml key, each of which is an absolutely-positioned
+/// span into the document the key was spanned from.
+///
+/// Given the key `-Inherits@experience`:
+///
+/// ```text
+/// -Inherits@experience
+/// ^                    removal_marker
+///  ^^^^^^^^            name
+///          ^^^^^^^^^^^ instance (including the `@`)
+/// ```
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct KeyParts {
+    /// span of the leading `-`, if it exists, which removes a previously
+    /// defined node of the same name
+    pub removal_marker: Option<AbsByteIdxSpan>,
+
+    /// span of the leading `^`, if it exists, which marks the node as abstract
+    /// (a template which is only meant to be inherited from)
+    pub abstract_marker: Option<AbsByteIdxSpan>,
+
+    /// span of the key's name, sans markers and instance suffix
+    pub name: AbsByteIdxSpan,
+
+    /// span of the `@` instance suffix, if it exists, including the `@`
+    pub instance: Option<AbsByteIdxSpan>,
+}
+
+impl KeyParts {
+    /// `true` if this key removes a previously defined node
+    pub fn is_removal(&self) -> bool {
+        self.removal_marker.is_some()
+    }
+
+    /// `true` if this key names an abstract node
+    pub fn is_abstract(&self) -> bool {
+        self.abstract_marker.is_some()
+    }
+}
+
+/// Break the key spanned by `key_span` in `doc` into its [`KeyParts`].
+///
+/// Trailing whitespace (which is part of a `SpannedLine`'s key when it is
+/// followed by whitespace before the key-separator) is not part of any
+/// component.
+///
+/// [`KeyParts`]: struct.KeyParts.html
+pub fn parse_key(doc: &str, key_span: AbsByteIdxSpan) -> KeyParts {
+    let key_start_abs_idx = key_span.start.0;
+    let key_txt = doc[key_span].trim_end();
+    let key_end_abs_idx = key_start_abs_idx + key_txt.len();
+
+    let span_of_rel = |rel_start: usize, rel_end: usize| -> AbsByteIdxSpan {
+        AbsByteIdxSpan::from((
+            AbsByteIdx(key_start_abs_idx + rel_start),
+            AbsByteIdx(key_start_abs_idx + rel_end),
+        ))
+    };
+
+    let mut name_rel_start = 0;
+
+    let removal_marker = if key_txt.starts_with('-') {
+        name_rel_start += '-'.len_utf8();
+        Some(span_of_rel(0, name_rel_start))
+    } else {
+        None
+    };
+
+    let abstract_marker = if key_txt[name_rel_start..].starts_with('^') {
+        let marker_rel_start = name_rel_start;
+        name_rel_start += '^'.len_utf8();
+        Some(span_of_rel(marker_rel_start, name_rel_start))
+    } else {
+        None
+    };
+
+    let opt_instance_rel_start = key_txt[name_rel_start..].find('@')
+        .map(|rel_rel_idx| rel_rel_idx + name_rel_start);
+
+    let name_rel_end = opt_instance_rel_start
+        .unwrap_or(key_txt.len());
+
+    let instance = opt_instance_rel_start
+        .map(|instance_rel_start| span_of_rel(
+            instance_rel_start,
+            key_end_abs_idx - key_start_abs_idx,
+        ));
+
+    KeyParts {
+        removal_marker,
+        abstract_marker,
+        name: span_of_rel(name_rel_start, name_rel_end),
+        instance,
+    }
+}
+
+// ----- tests -----------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts_of(
+        doc: &str,
+        parts: KeyParts,
+    ) -> (Option<&str>, Option<&str>, &str, Option<&str>) {
+        (
+            parts.removal_marker.map(|span| &doc[span]),
+            parts.abstract_marker.map(|span| &doc[span]),
+            &doc[parts.name],
+            parts.instance.map(|span| &doc[span]),
+        )
+    }
+
+    #[test]
+    fn plain_key() {
+        // arrange
+        let doc = "Tooltip";
+
+        // act
+        let parts = parse_key(doc, (0, doc.len()).into());
+
+        // assert
+        assert_eq!(
+            (None, None, "Tooltip", None),
+            texts_of(doc, parts),
+        );
+    }
+
+    #[test]
+    fn all_components_with_trailing_whitespace() {
+        // arrange
+        let doc = "    -^Inherits@experience  : ^GainsExperience";
+        let key_span = (4, 27).into();
+
+        // act
+        let parts = parse_key(doc, key_span);
+
+        // assert
+        assert_eq!(
+            (Some("-"), Some("^"), "Inherits", Some("@experience")),
+            texts_of(doc, parts),
+        );
+        assert!(parts.is_removal());
+        assert!(parts.is_abstract());
+    }
+
+    #[test]
+    fn abstract_key_with_non_ascii_name() {
+        // arrange
+        let doc = "^请务必取代@一";
+
+        // act
+        let parts = parse_key(doc, (0, doc.len()).into());
+
+        // assert
+        assert_eq!(
+            (None, Some("^"), "请务必取代", Some("@一")),
+            texts_of(doc, parts),
+        );
+    }
+}
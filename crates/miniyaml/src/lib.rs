@@ -12,8 +12,41 @@
 //! [crate]: https://doc.rust-lang.org/book/ch07-01-packages-and-crates.html
 //! [MiniYaml]: https://www.openra.net/book/glossary.html#miniyaml
 //! [static analysis]: https://en.wikipedia.org/wiki/Static_program_analysis
+//!
+//! Enabling the `serde` feature implements [`Serialize`] for spans,
+//! spanned-lines, trees, and positions, enabling the `json` feature exposes
+//! conversion between trees and JSON / YAML documents (and reading schemas
+//! from JSON), enabling the `config`
+//! feature exposes reading a project's configuration file, and enabling the
+//! `oramap` feature supports reading zipped map packages (`.oramap`) and asset
+//! packages.
+//!
+//! [`Serialize`]: https://docs.rs/serde/1/serde/trait.Serialize.html
 
 mod spanner;
+mod edit;
+mod key;
+mod tree;
+mod position;
+mod index;
+mod value;
+mod highlight;
+mod decode;
+mod manifest;
+mod rules;
+mod graph;
+mod lint;
+mod map;
+mod map_bin;
+mod tileset;
+mod sequences;
+mod assets;
+mod weapons;
+mod schema;
+#[cfg(feature = "json")]
+mod convert;
+#[cfg(feature = "config")]
+mod config;
 
 use {
     std::{
@@ -25,14 +58,180 @@ use {
 
 pub use {
     spanner::{
+        iter_spanned_lines_of,
+        respan_lines_of,
+        respan_lines_with_options,
         span_lines_of,
+        DefaultSpanner,
+        KeySepStyle,
         Spanner,
+        SpannerOptions,
         SpannedLine,
+        SpannedLineIter,
+    },
+    edit::{
+        apply_edits,
+        TextEdit,
+    },
+    key::{
+        parse_key,
+        KeyParts,
+    },
+    tree::{
+        indent_level_of,
+        tree_of,
+        Node,
+        Tree,
+        SPACES_PER_LEVEL,
+    },
+    position::{
+        LineCol,
+        LineIndex,
+    },
+    index::{
+        Symbol,
+        SymbolIndex,
+    },
+    value::{
+        kind_of_value,
+        list_items_of,
+        parse_ints,
+        parse_wdist,
+        ValueKind,
+    },
+    highlight::{
+        highlights_of,
+        Highlight,
+        HighlightClass,
+    },
+    decode::{
+        decode_lossy,
+        Decoded,
+        InvalidSequence,
+    },
+    manifest::Manifest,
+    rules::{
+        Origin,
+        ResolveError,
+        RuleNode,
+        Rules,
+    },
+    graph::InheritsGraph,
+    lint::{
+        builtin_lints,
+        default_reference_fields,
+        BinaryFile,
+        Definition,
+        DefinitionKind,
+        Diagnostic,
+        DuplicateKey,
+        FileKind,
+        FileLocation,
+        Finding,
+        Fix,
+        IndentStyle,
+        InvalidUtf8,
+        InvalidWeapon,
+        Lint,
+        LintConfig,
+        LintContext,
+        LintFile,
+        Linter,
+        MapActors,
+        MapDimensions,
+        MapOverrides,
+        MapPlayers,
+        MapTiles,
+        MissingSeparator,
+        MixedIndentation,
+        ModFiles,
+        ReferenceField,
+        SequenceField,
+        SequenceSprites,
+        Severity,
+        SourceFile,
+        UndefinedReference,
+        UndefinedSequence,
+        UnknownTerrainType,
+        UnknownTrait,
+        UnreferencedActor,
+        UnusedAbstract,
+        ENGINE_ACTORS,
+        PROJECTILE_TYPES,
+        SEQUENCE_FIELDS,
+        TERRAIN_TYPE_FIELDS,
+        UNUSED_SUPPRESSION,
+        WARHEAD_TYPES,
+        WEAPON_FIELDS,
+    },
+    map::{
+        is_map_package,
+        is_mod_entry,
+        overrides_of,
+        MapError,
+        MapErrorKind,
+        MapOverride,
+        MapPackage,
+        MAP_BIN,
+        MAP_YAML,
+        OVERRIDE_SECTIONS,
+    },
+    map_bin::{
+        MapBin,
+        MapBinError,
+        ResourceTile,
+        TerrainTile,
+    },
+    tileset::{
+        Template,
+        Tileset,
+    },
+    sequences::{
+        Image,
+        Sequence,
+        SequenceLength,
+    },
+    assets::{
+        frame_count_of,
+        package_paths_of,
+        Asset,
+        Assets,
+        UnreadPackage,
+        SPRITE_EXTENSIONS,
+    },
+    weapons::{
+        Weapon,
+        WeaponField,
+        WeaponPart,
+    },
+    schema::Schema,
+};
+
+#[cfg(feature = "json")]
+pub use {
+    convert::{
+        json_of_tree,
+        miniyaml_of_json,
+        miniyaml_of_json_indented,
+        yaml_of_tree,
+        ConvertError,
+        VALUE_KEY,
+    },
+};
+
+#[cfg(feature = "config")]
+pub use {
+    config::{
+        Config,
+        ConfigError,
+        ConfigErrorKind,
+        CONFIG_FILE_NAME,
     },
 };
 
 /// low-inclusive, high-exclusive span of absolute byte indices
 #[derive(Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct AbsByteIdxSpan {
     start: AbsByteIdx,
     end: AbsByteIdx,
@@ -40,6 +239,7 @@ pub struct AbsByteIdxSpan {
 
 /// absolute byte index
 #[derive(Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct AbsByteIdx(usize);
 
 // ----- external trait impls --------------------------------------------------
@@ -0,0 +1,156 @@
+// oraide - tools for OpenRA-based mod/game development
+// get the source code at https://github.com/Phrohdoh/oraide
+//
+// copyright (c)
+// - 2020 Taryn "Phrohdoh" Hill
+
+//! the lint reporting sibling nodes with the same key
+
+use {
+    std::collections::HashMap,
+    crate::{
+        lint::{
+            Finding,
+            Fix,
+            Lint,
+            LintContext,
+            LintFile,
+            Severity,
+        },
+        AbsByteIdxSpan,
+        Node,
+        TextEdit,
+    },
+};
+
+// ----- public interface ------------------------------------------------------
+
+/// Reports nodes whose key is the same as that of an earlier sibling in the
+/// same file (`duplicate-key`), which the engine refuses to load.
+///
+/// A duplicate whose entire block is identical to the first is fixed by
+/// removing it.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct DuplicateKey;
+
+// ----- external trait impls --------------------------------------------------
+
+impl Lint for DuplicateKey {
+    fn code(&self) -> &'static str {
+        "duplicate-key"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, _cx: &LintContext, file: &LintFile) -> Vec<Finding> {
+        let mut findings = duplicates_among(file, &file.tree.nodes);
+        for node in file.tree.iter() {
+            findings.extend(duplicates_among(file, &node.children));
+        }
+
+        findings
+    }
+}
+
+// ----- private implementation details ----------------------------------------
+
+/// a finding for every node of `siblings` whose key is that of an earlier
+/// node of `siblings`
+fn duplicates_among(file: &LintFile, siblings: &[Node]) -> Vec<Finding> {
+    let mut first_by_key = HashMap::new();
+    let mut findings = vec![];
+
+    for node in siblings {
+        let key_txt = file.text[node.key].trim_end();
+
+        let first = match first_by_key.get(key_txt) {
+            Some(first) => first,
+            None => {
+                first_by_key.insert(key_txt, node);
+                continue;
+            },
+        };
+
+        let finding = Finding::new(
+            node.key,
+            format!("`{}` is already defined on line {}", key_txt, first.line_idx + 1),
+        );
+
+        let block_span = block_span_of(file, node);
+        let is_identical = block_txt_of(file, first) == block_txt_of(file, node);
+
+        findings.push(if is_identical {
+            finding.with_fix(Fix::new(
+                format!("remove the duplicate `{}`", key_txt),
+                TextEdit::new(block_span, ""),
+            ))
+        } else {
+            finding
+        });
+    }
+
+    findings
+}
+
+/// span of every line of `node`'s block, including the last line's
+/// terminator
+fn block_span_of(file: &LintFile, node: &Node) -> AbsByteIdxSpan {
+    let (start, _) = file.lines[node.line_idx].raw.into();
+    let (_, end) = file.lines[node.last_line_idx].raw.into();
+    (start, end).into()
+}
+
+/// text of `node`'s block, sans the last line's terminator (which the last
+/// line of a file may lack)
+fn block_txt_of<'files>(file: &LintFile<'files>, node: &Node) -> &'files str {
+    file.text[block_span_of(file, node)].trim_end_matches(['\r', '\n'])
+}
+
+// ----- tests -----------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::lint::{
+            tests::source_file,
+            Linter,
+            ModFiles,
+        },
+    };
+
+    #[test]
+    fn duplicate_keys_and_their_fixes() {
+        // arrange
+        let mut files = ModFiles {
+            rules: vec![
+                source_file("rules.yaml", "E1:\n\tHealth:\n\t\tHP: 100\n\tHealth:\n\t\tHP: 100\n\tMobile:\n\tMobile:\n\t\tSpeed: 1\nE2:\n\tBuildable:\nE2:\n\tBuildable:\n"),
+            ],
+            ..ModFiles::default()
+        };
+
+        let mut linter = Linter::empty();
+        linter.add_lint(DuplicateKey);
+
+        // act
+        let diagnostics = linter.run(&files);
+        let fix_count = linter.fix(&mut files);
+
+        // assert
+        assert_eq!(
+            vec![
+                ("`Health` is already defined on line 2", true),
+                ("`Mobile` is already defined on line 6", false),
+                ("`E2` is already defined on line 9", true),
+            ],
+            diagnostics.iter()
+                .map(|diagnostic| (diagnostic.message.as_str(), diagnostic.fix.is_some()))
+                .collect::<Vec<_>>(),
+        );
+
+        assert_eq!(2, fix_count);
+        assert_eq!("E1:\n\tHealth:\n\t\tHP: 100\n\tMobile:\n\tMobile:\n\t\tSpeed: 1\nE2:\n\tBuildable:\n", files.rules[0].text);
+    }
+}
@@ -0,0 +1,93 @@
+// oraide - tools for OpenRA-based mod/game development
+// get the source code at https://github.com/Phrohdoh/oraide
+//
+// copyright (c)
+// - 2020 Taryn "Phrohdoh" Hill
+
+//! the lint reporting bytes which aren't valid UTF-8
+
+use {
+    crate::lint::{
+        Finding,
+        Lint,
+        LintContext,
+        LintFile,
+        Severity,
+    },
+};
+
+// ----- public interface ------------------------------------------------------
+
+/// Reports each sequence of bytes of a file which isn't valid UTF-8
+/// (`invalid-utf8`), at the `U+FFFD REPLACEMENT CHARACTER` which replaced it
+/// when the file was read.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct InvalidUtf8;
+
+// ----- external trait impls --------------------------------------------------
+
+impl Lint for InvalidUtf8 {
+    fn code(&self) -> &'static str {
+        "invalid-utf8"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, _cx: &LintContext, file: &LintFile) -> Vec<Finding> {
+        let replacement_len = char::REPLACEMENT_CHARACTER.len_utf8();
+
+        file.invalid_sequences.iter()
+            .filter(|invalid_sequence| file.text.get(invalid_sequence.text_idx..)
+                .is_some_and(|txt| txt.starts_with(char::REPLACEMENT_CHARACTER)))
+            .map(|invalid_sequence| Finding::new(
+                (invalid_sequence.text_idx, invalid_sequence.text_idx + replacement_len).into(),
+                format!(
+                    "invalid UTF-8 at byte offset {} (replaced with U+FFFD)",
+                    invalid_sequence.byte_idx,
+                ),
+            ))
+            .collect()
+    }
+}
+
+// ----- tests -----------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::lint::{
+            tests::describe,
+            Linter,
+            ModFiles,
+            SourceFile,
+        },
+    };
+
+    #[test]
+    fn invalid_sequences() {
+        // arrange
+        let files = ModFiles {
+            rules: vec![SourceFile::decode("rules.yaml", b"E1:\n\tTooltip:\n\t\tName: \xFFfficer\n\t\tDescription: \xE8\xAF\n")],
+            ..ModFiles::default()
+        };
+
+        let mut linter = Linter::empty();
+        linter.add_lint(InvalidUtf8);
+
+        // act
+        let actual = describe(linter.run(&files));
+
+        // assert
+        let finding = |message: &str| ("rules.yaml".to_owned(), "invalid-utf8", message.to_owned());
+        assert_eq!(
+            vec![
+                finding("invalid UTF-8 at byte offset 22 (replaced with U+FFFD)"),
+                finding("invalid UTF-8 at byte offset 45 (replaced with U+FFFD)"),
+            ],
+            actual,
+        );
+    }
+}
@@ -0,0 +1,230 @@
+// oraide - tools for OpenRA-based mod/game development
+// get the source code at https://github.com/Phrohdoh/oraide
+//
+// copyright (c)
+// - 2020 Taryn "Phrohdoh" Hill
+
+//! the lint reporting indentation inconsistent with the rest of its file
+
+use {
+    std::str::FromStr,
+    crate::{
+        lint::{
+            Finding,
+            Fix,
+            Lint,
+            LintContext,
+            LintFile,
+            Severity,
+        },
+        indent_level_of,
+        TextEdit,
+        SPACES_PER_LEVEL,
+    },
+};
+
+// ----- public interface ------------------------------------------------------
+
+/// How each level of nesting of written MiniYaml is indented.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub enum IndentStyle {
+    /// a single tab per level
+    #[default]
+    Tabs,
+
+    /// the given number of spaces per level
+    Spaces(usize),
+}
+
+impl IndentStyle {
+    /// The text of a single level of indentation.
+    pub fn txt(self) -> String {
+        match self {
+            IndentStyle::Tabs => "\t".to_owned(),
+            IndentStyle::Spaces(count) => " ".repeat(count),
+        }
+    }
+}
+
+/// Reports the indentation of lines which mixes tabs and spaces, or which
+/// uses tabs in a file indented with spaces (or vice versa)
+/// (`mixed-indentation`), where a file is indented in the configured style
+/// or, if none is configured, in the style of its first indented line.
+///
+/// Indentation whose level is unambiguous (i.e. whose spaces come in runs of
+/// [`SPACES_PER_LEVEL`]) is fixed by converting it to the file's style.
+///
+/// [`SPACES_PER_LEVEL`]: ../constant.SPACES_PER_LEVEL.html
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct MixedIndentation {
+    /// the style every file is expected to be indented in, if configured
+    pub indent: Option<IndentStyle>,
+}
+
+impl MixedIndentation {
+    /// Expect every file to be indented with `indent`.
+    pub fn new(indent: IndentStyle) -> Self {
+        Self { indent: Some(indent) }
+    }
+}
+
+// ----- external trait impls --------------------------------------------------
+
+impl FromStr for IndentStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "tab" | "tabs" => Ok(IndentStyle::Tabs),
+            _ => match s.parse::<usize>() {
+                Ok(count) if count > 0 => Ok(IndentStyle::Spaces(count)),
+                _ => Err(format!("indent {:?} not supported (expected `tabs` or a number of spaces)", s)),
+            },
+        }
+    }
+}
+
+impl Lint for MixedIndentation {
+    fn code(&self) -> &'static str {
+        "mixed-indentation"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, _cx: &LintContext, file: &LintFile) -> Vec<Finding> {
+        let indent_spans = file.lines.iter()
+            .filter(|line| line.key.is_some())
+            .filter_map(|line| line.indent)
+            .filter(|&span| !file.text[span].is_empty());
+
+        let mut opt_file_style = self.indent;
+        let mut findings = vec![];
+        let whose_style = if self.indent.is_some() { "the project" } else { "this file" };
+
+        for indent_span in indent_spans {
+            let indent_txt = &file.text[indent_span];
+            let has_tabs = indent_txt.contains('\t');
+            let has_spaces = indent_txt.contains(' ');
+
+            let file_style = *opt_file_style.get_or_insert(if has_tabs {
+                IndentStyle::Tabs
+            } else {
+                IndentStyle::Spaces(SPACES_PER_LEVEL)
+            });
+
+            let message = match file_style {
+                _ if has_tabs && has_spaces => "indentation mixes tabs and spaces".to_owned(),
+                IndentStyle::Tabs if has_spaces => {
+                    format!("indented with spaces, but {} is indented with tabs", whose_style)
+                },
+                IndentStyle::Spaces(_) if has_tabs => {
+                    format!("indented with tabs, but {} is indented with spaces", whose_style)
+                },
+                _ => continue,
+            };
+
+            let finding = Finding::new(indent_span, message);
+
+            let space_count = indent_txt.chars().filter(|&ch| ch == ' ').count();
+            if space_count % SPACES_PER_LEVEL != 0 {
+                findings.push(finding);
+                continue;
+            }
+
+            let level = indent_level_of(indent_txt);
+            let description = match file_style {
+                IndentStyle::Tabs => "indent with tabs",
+                IndentStyle::Spaces(_) => "indent with spaces",
+            };
+
+            findings.push(finding.with_fix(Fix::new(
+                description,
+                TextEdit::new(indent_span, file_style.txt().repeat(level)),
+            )));
+        }
+
+        findings
+    }
+}
+
+// ----- tests -----------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::lint::{
+            tests::{
+                describe,
+                source_file,
+            },
+            Linter,
+            ModFiles,
+        },
+    };
+
+    #[test]
+    fn mixed_indentation_and_its_fixes() {
+        // arrange
+        let mut files = ModFiles {
+            rules: vec![
+                source_file("tabs.yaml", "E1:\n\tHealth:\n        HP: 100\n\t  Wat: 1\n    \tMobile:\n"),
+                source_file("spaces.yaml", "E2:\n    Health:\n\t\tHP: 100\n"),
+            ],
+            ..ModFiles::default()
+        };
+
+        let mut linter = Linter::empty();
+        linter.add_lint(MixedIndentation::default());
+
+        // act
+        let diagnostics = linter.run(&files);
+        let fix_count = linter.fix(&mut files);
+
+        // assert
+        assert_eq!(
+            vec![
+                ("indented with spaces, but this file is indented with tabs", true),
+                ("indentation mixes tabs and spaces", false),
+                ("indentation mixes tabs and spaces", true),
+                ("indented with tabs, but this file is indented with spaces", true),
+            ],
+            diagnostics.iter()
+                .map(|diagnostic| (diagnostic.message.as_str(), diagnostic.fix.is_some()))
+                .collect::<Vec<_>>(),
+        );
+
+        assert_eq!(3, fix_count);
+        assert_eq!("E1:\n\tHealth:\n\t\tHP: 100\n\t  Wat: 1\n\t\tMobile:\n", files.rules[0].text);
+        assert_eq!("E2:\n    Health:\n        HP: 100\n", files.rules[1].text);
+    }
+
+    #[test]
+    fn configured_indentation() {
+        // arrange
+        let mut files = ModFiles {
+            rules: vec![
+                source_file("tabs.yaml", "E1:\n\tHealth:\n\t\tHP: 100\n"),
+            ],
+            ..ModFiles::default()
+        };
+
+        let mut linter = Linter::empty();
+        linter.add_lint(MixedIndentation::new(IndentStyle::Spaces(2)));
+
+        // act
+        let actual = describe(linter.run(&files));
+        let fix_count = linter.fix(&mut files);
+
+        // assert
+        assert_eq!(
+            vec![("tabs.yaml".to_owned(), "mixed-indentation", "indented with tabs, but the project is indented with spaces".to_owned()); 2],
+            actual,
+        );
+
+        assert_eq!(2, fix_count);
+        assert_eq!("E1:\n  Health:\n    HP: 100\n", files.rules[0].text);
+    }
+}
@@ -0,0 +1,774 @@
+// oraide - tools for OpenRA-based mod/game development
+// get the source code at https://github.com/Phrohdoh/oraide
+//
+// copyright (c)
+// - 2020 Taryn "Phrohdoh" Hill
+
+//! lints reporting mistakes in the `map.yaml` of maps
+
+use {
+    std::collections::{
+        BTreeMap,
+        HashSet,
+    },
+    crate::{
+        lint::{
+            references::{
+                closest_name,
+                closest_of,
+                finding_with_suggestion,
+            },
+            DefinitionKind,
+            FileKind,
+            Finding,
+            Lint,
+            LintContext,
+            LintFile,
+            Severity,
+        },
+        is_mod_entry,
+        list_items_of,
+        overrides_of,
+        parse_ints,
+        parse_key,
+        AbsByteIdxSpan,
+        MapBin,
+        Node,
+        Rules,
+    },
+};
+
+// ----- public interface ------------------------------------------------------
+
+/// Reports, in each `map.yaml`, override files which aren't in the map
+/// package, and actors or weapons which the map defines (or redefines) but
+/// which can't be resolved once its `Rules` or `Weapons` are merged on top of
+/// the mod's (`map-override`).
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct MapOverrides;
+
+/// Reports, in each `map.yaml`, `PlayerReference`s without a `Name`, with a
+/// `Faction` which the mod's `World` doesn't define, or with `Allies` or
+/// `Enemies` which aren't players of the map (`map-player`).
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct MapPlayers;
+
+/// Reports, in each `map.yaml`, `Actors` whose type is neither defined by the
+/// mod nor by the map's `Rules`, whose `Owner` isn't a player of the map, or
+/// whose `Location` is outside of the map's `MapSize` (`map-actor`).
+///
+/// Types are compared ignoring case, as the engine does.  Locations outside
+/// of the map's `Bounds` (but within its size) aren't reported, as actors
+/// placed there (e.g. the spawn points of reinforcements) are valid.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct MapActors;
+
+/// Reports, in each `map.yaml`, a `map.bin` which can't be read or whose size
+/// isn't the map's `MapSize`, and `Bounds` which aren't within said size
+/// (`map-dimensions`).
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct MapDimensions;
+
+/// Reports, in each `map.yaml`, a `Tileset` which isn't the `Id` of any
+/// tileset of the mod, and tiles of its `map.bin` whose template said tileset
+/// doesn't define, or whose index is outside of their template (`map-tile`).
+///
+/// Tiles are reported once per template (or template and index), along with
+/// how many cells use it, as a map painted with the wrong tileset would
+/// otherwise be reported once per cell.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct MapTiles;
+
+// ----- external trait impls --------------------------------------------------
+
+impl Lint for MapOverrides {
+    fn code(&self) -> &'static str {
+        "map-override"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, cx: &LintContext, file: &LintFile) -> Vec<Finding> {
+        if file.kind != FileKind::Map {
+            return vec![];
+        }
+
+        let doc = file.text;
+        let package_dir = file.path.parent().unwrap_or(file.path);
+        let mut findings = vec![];
+
+        for map_override in overrides_of(doc, &file.tree) {
+            let mut rules = match map_override.section {
+                "Rules" => cx.rules().clone(),
+                "Weapons" => cx.weapons().clone(),
+                _ => Rules::new(),
+            };
+
+            // the name of each definition of each override file, and the
+            // file's entry and its span
+            let mut file_definitions = vec![];
+
+            for &entry_span in &map_override.files {
+                let entry = &doc[entry_span];
+                if is_mod_entry(entry) {
+                    continue;
+                }
+
+                let entry_path = package_dir.join(entry);
+                let override_file = cx.files().iter()
+                    .filter(|other| other.kind == FileKind::MapOverride)
+                    .find(|other| other.path == entry_path);
+
+                match override_file {
+                    Some(override_file) => {
+                        rules.add_file(override_file.path, override_file.text);
+                        file_definitions.extend(
+                            names_of(override_file.text, &override_file.tree.nodes)
+                                .map(|name| (name, entry, entry_span))
+                        );
+                    },
+                    None => findings.push(Finding::new(
+                        entry_span,
+                        format!("`{}` is not in the map package", entry),
+                    )),
+                }
+            }
+
+            if map_override.section != "Rules" && map_override.section != "Weapons" {
+                continue;
+            }
+
+            rules.add_nodes(file.path, doc, &map_override.definitions);
+
+            for (name, entry, entry_span) in file_definitions {
+                if let Err(err) = rules.resolve(name) {
+                    findings.push(Finding::new(
+                        entry_span,
+                        format!("`{}`, defined in `{}`, can't be resolved: {}", name, entry, err),
+                    ));
+                }
+            }
+
+            for node in &map_override.definitions {
+                if parse_key(doc, node.key).is_removal() {
+                    continue;
+                }
+
+                let name = doc[node.key].trim_end();
+                if let Err(err) = rules.resolve(name) {
+                    findings.push(Finding::new(
+                        node.key,
+                        format!("`{}` can't be resolved: {}", name, err),
+                    ));
+                }
+            }
+        }
+
+        findings
+    }
+}
+
+impl Lint for MapPlayers {
+    fn code(&self) -> &'static str {
+        "map-player"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, cx: &LintContext, file: &LintFile) -> Vec<Finding> {
+        if file.kind != FileKind::Map {
+            return vec![];
+        }
+
+        let doc = file.text;
+        let player_references = player_references_of(file);
+        let player_names = player_names_of(file);
+        let factions = factions_of(cx);
+        let mut findings = vec![];
+
+        for player_reference in player_references {
+            if value_of(doc, player_reference, "Name").is_none() {
+                findings.push(Finding::new(
+                    player_reference.key,
+                    format!("`{}` has no `Name`", doc[player_reference.key].trim_end()),
+                ));
+            }
+
+            if let Some(faction_span) = value_of(doc, player_reference, "Faction") {
+                let faction = &doc[faction_span];
+
+                if !factions.is_empty() && !factions.iter().any(|name| name == faction) {
+                    findings.push(Finding::new(
+                        faction_span,
+                        format!("`{}` is not a faction defined by `World`", faction),
+                    ));
+                }
+            }
+
+            for key in &["Allies", "Enemies"] {
+                let item_spans = player_reference.child(doc, key)
+                    .and_then(|child| child.value)
+                    .map(|value_span| list_items_of(doc, value_span))
+                    .unwrap_or_default();
+
+                for item_span in item_spans {
+                    if !player_names.contains(&doc[item_span]) {
+                        findings.push(Finding::new(
+                            item_span,
+                            format!("`{}` is not a player of this map", &doc[item_span]),
+                        ));
+                    }
+                }
+            }
+        }
+
+        findings
+    }
+}
+
+impl Lint for MapActors {
+    fn code(&self) -> &'static str {
+        "map-actor"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, cx: &LintContext, file: &LintFile) -> Vec<Finding> {
+        if file.kind != FileKind::Map {
+            return vec![];
+        }
+
+        let doc = file.text;
+        let actors = match file.tree.nodes.iter().find(|node| doc[node.key].trim_end() == "Actors") {
+            Some(actors) => &actors.children,
+            None => return vec![],
+        };
+
+        let has_players = file.tree.nodes.iter().any(|node| doc[node.key].trim_end() == "Players");
+        let player_names = player_names_of(file);
+        let map_actor_names = map_actor_names_of(cx, file);
+        let opt_size = map_size_of(file);
+        let mut findings = vec![];
+
+        for actor in actors {
+            let opt_type_span = actor.value
+                .and_then(|value_span| list_items_of(doc, value_span).into_iter().next());
+
+            if let Some(type_span) = opt_type_span {
+                let type_txt = &doc[type_span];
+
+                let is_defined = cx.definition_of(DefinitionKind::Actor, type_txt).is_some()
+                    || map_actor_names.contains(&type_txt.to_lowercase());
+
+                if !is_defined {
+                    findings.push(finding_with_suggestion(
+                        type_span,
+                        format!("`{}` is not a defined actor", type_txt),
+                        closest_name(type_txt, cx.definitions_of(DefinitionKind::Actor)),
+                    ));
+                }
+            }
+
+            if let Some(owner_span) = value_of(doc, actor, "Owner").filter(|_| has_players) {
+                if !player_names.contains(&doc[owner_span]) {
+                    findings.push(Finding::new(
+                        owner_span,
+                        format!("`{}` is not a player of this map", &doc[owner_span]),
+                    ));
+                }
+            }
+
+            let opt_location_span = value_of(doc, actor, "Location");
+            if let (Some(location_span), Some((width, height))) = (opt_location_span, opt_size) {
+                let is_inside = match parse_ints(&doc[location_span]).as_deref() {
+                    Some(&[x, y]) => (0..width).contains(&x) && (0..height).contains(&y),
+                    // not a location, which the engine will report
+                    _ => true,
+                };
+
+                if !is_inside {
+                    findings.push(Finding::new(
+                        location_span,
+                        format!(
+                            "`{}` is outside of the map's `MapSize` ({},{})",
+                            &doc[location_span], width, height,
+                        ),
+                    ));
+                }
+            }
+        }
+
+        findings
+    }
+}
+
+impl Lint for MapDimensions {
+    fn code(&self) -> &'static str {
+        "map-dimensions"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, cx: &LintContext, file: &LintFile) -> Vec<Finding> {
+        if file.kind != FileKind::Map {
+            return vec![];
+        }
+
+        let doc = file.text;
+        let opt_size_span = top_level_value_of(file, "MapSize");
+        let opt_size = map_size_of(file);
+
+        let mut findings = vec![];
+
+        match (cx.map_bin_of(file), opt_size_span.zip(opt_size)) {
+            (Some(Err(err)), _) => findings.push(Finding::new(
+                opt_size_span.unwrap_or_else(|| (0, 0).into()),
+                format!("`map.bin` can't be read: {}", err),
+            )),
+            (Some(Ok(map_bin)), Some((size_span, size)))
+                if (i64::from(map_bin.width), i64::from(map_bin.height)) != size =>
+            {
+                findings.push(Finding::new(
+                    size_span,
+                    format!(
+                        "`{}` is not the size of `map.bin` ({},{})",
+                        &doc[size_span],
+                        map_bin.width,
+                        map_bin.height,
+                    ),
+                ));
+            },
+            _ => {},
+        }
+
+        let opt_bounds_span = top_level_value_of(file, "Bounds");
+        if let (Some(bounds_span), Some((width, height))) = (opt_bounds_span, opt_size) {
+            let is_within = match parse_ints(&doc[bounds_span]).as_deref() {
+                Some(&[left, top, bounds_width, bounds_height]) => {
+                    left >= 0 && top >= 0
+                        && bounds_width > 0 && bounds_height > 0
+                        && left.checked_add(bounds_width).is_some_and(|right| right <= width)
+                        && top.checked_add(bounds_height).is_some_and(|bottom| bottom <= height)
+                },
+                // not a rectangle, which the engine will report
+                _ => true,
+            };
+
+            if !is_within {
+                findings.push(Finding::new(
+                    bounds_span,
+                    format!("`{}` is not within `MapSize` ({},{})", &doc[bounds_span], width, height),
+                ));
+            }
+        }
+
+        findings
+    }
+}
+
+impl Lint for MapTiles {
+    fn code(&self) -> &'static str {
+        "map-tile"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, cx: &LintContext, file: &LintFile) -> Vec<Finding> {
+        if file.kind != FileKind::Map || cx.tilesets().is_empty() {
+            return vec![];
+        }
+
+        let doc = file.text;
+        let tileset_span = match top_level_value_of(file, "Tileset") {
+            Some(tileset_span) => tileset_span,
+            None => return vec![],
+        };
+
+        let tileset_id = &doc[tileset_span];
+        let tileset = match cx.tileset_of(tileset_id) {
+            Some(tileset) => tileset,
+            None => {
+                let ids = cx.tilesets().iter().filter_map(|tileset| tileset.id.as_deref());
+
+                return vec![finding_with_suggestion(
+                    tileset_span,
+                    format!("`{}` is not a tileset of the mod", tileset_id),
+                    closest_of(tileset_id, ids),
+                )];
+            },
+        };
+
+        let (map_bin, tiles) = match cx.map_bin_of(file) {
+            Some(Ok(map_bin @ MapBin { tiles: Some(tiles), .. })) => (map_bin, tiles),
+            _ => return vec![],
+        };
+
+        // how many cells use each undefined template (or index of a
+        // template), and the first of them
+        let mut undefined_templates = BTreeMap::new();
+        let mut undefined_indices = BTreeMap::new();
+
+        for ((x, y), tile) in map_bin.cells().zip(tiles) {
+            let uses = match tileset.templates.get(&tile.template_id) {
+                None => undefined_templates.entry(tile.template_id)
+                    .or_insert((0, (x, y))),
+                Some(template) if !template.has_index(tile.index) => {
+                    undefined_indices.entry((tile.template_id, tile.index))
+                        .or_insert((0, (x, y)))
+                },
+                Some(_) => continue,
+            };
+
+            uses.0 += 1;
+        }
+
+        let undefined_template_findings = undefined_templates.into_iter()
+            .map(|(template_id, (cell_count, (x, y)))| Finding::new(
+                tileset_span,
+                format!(
+                    "template {} is not defined by `{}`, but is used by {} cell(s) of `map.bin`, the first at {},{}",
+                    template_id, tileset_id, cell_count, x, y,
+                ),
+            ));
+
+        let undefined_index_findings = undefined_indices.into_iter()
+            .map(|((template_id, index), (cell_count, (x, y)))| Finding::new(
+                tileset_span,
+                format!(
+                    "template {} has no tile {}, but it is used by {} cell(s) of `map.bin`, the first at {},{}",
+                    template_id, index, cell_count, x, y,
+                ),
+            ));
+
+        undefined_template_findings.chain(undefined_index_findings).collect()
+    }
+}
+
+// ----- private implementation details ----------------------------------------
+
+/// the width and height of the map's `MapSize`, if it has a valid one
+fn map_size_of(file: &LintFile) -> Option<(i64, i64)> {
+    let size_span = top_level_value_of(file, "MapSize")?;
+
+    match parse_ints(&file.text[size_span]).as_deref() {
+        Some(&[width, height]) => Some((width, height)),
+        _ => None,
+    }
+}
+
+/// the span, without trailing whitespace, of the value of the map's top-level
+/// node `key`, if it has one
+fn top_level_value_of(file: &LintFile, key: &str) -> Option<AbsByteIdxSpan> {
+    let doc = file.text;
+    let value_span = file.tree.nodes.iter()
+        .find(|node| doc[node.key].trim_end() == key)?
+        .value?;
+
+    let (start, _) = value_span.into();
+    Some((start, start + doc[value_span].trim_end().len()).into())
+}
+
+/// the span, without trailing whitespace, of the value of `node`'s child
+/// `key`, if it has one
+fn value_of(doc: &str, node: &Node, key: &str) -> Option<AbsByteIdxSpan> {
+    let value_span = node.child(doc, key)?.value?;
+    let (start, _) = value_span.into();
+
+    Some((start, start + doc[value_span].trim_end().len()).into())
+}
+
+/// every `PlayerReference` of the map's `Players`
+fn player_references_of<'file>(file: &'file LintFile) -> Vec<&'file Node> {
+    let doc = file.text;
+
+    file.tree.nodes.iter()
+        .filter(|node| doc[node.key].trim_end() == "Players")
+        .flat_map(|players| players.children.iter())
+        .filter(|child| {
+            let key_txt = doc[child.key].trim_end();
+            key_txt == "PlayerReference" || key_txt.starts_with("PlayerReference@")
+        })
+        .collect()
+}
+
+/// the `Name` of every `PlayerReference` of the map
+fn player_names_of<'file>(file: &'file LintFile) -> HashSet<&'file str> {
+    player_references_of(file).into_iter()
+        .filter_map(|player_reference| value_of(file.text, player_reference, "Name"))
+        .map(|name_span| &file.text[name_span])
+        .collect()
+}
+
+/// the `InternalName` of every `Faction` of the mod's `World`
+fn factions_of(cx: &LintContext) -> Vec<String> {
+    let world = match cx.rules().resolve("World") {
+        Ok(world) => world,
+        Err(_) => return vec![],
+    };
+
+    world.children.iter()
+        .filter(|child| child.key == "Faction" || child.key.starts_with("Faction@"))
+        .flat_map(|faction| faction.children.iter())
+        .filter(|field| field.key == "InternalName")
+        .filter_map(|field| field.value.clone())
+        .collect()
+}
+
+/// the lowercase name of every actor the map's `Rules` define, whether in its
+/// `map.yaml` or in any override file of its package
+fn map_actor_names_of(cx: &LintContext, file: &LintFile) -> HashSet<String> {
+    let doc = file.text;
+    let package_dir = file.path.parent().unwrap_or(file.path);
+
+    let inline_names = overrides_of(doc, &file.tree).into_iter()
+        .filter(|map_override| map_override.section == "Rules")
+        .flat_map(|map_override| map_override.definitions)
+        .map(|node| doc[node.key].trim_end().to_lowercase());
+
+    let override_file_names = cx.files().iter()
+        .filter(|other| other.kind == FileKind::MapOverride && other.path.parent() == Some(package_dir))
+        .flat_map(|other| names_of(other.text, &other.tree.nodes))
+        .map(str::to_lowercase);
+
+    inline_names.chain(override_file_names).collect()
+}
+
+/// the key of every one of `nodes` which isn't a removal
+fn names_of<'a>(doc: &'a str, nodes: &'a [Node]) -> impl Iterator<Item = &'a str> + 'a {
+    nodes.iter()
+        .filter(move |node| !parse_key(doc, node.key).is_removal())
+        .map(move |node| doc[node.key].trim_end())
+}
+
+// ----- tests -----------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::lint::{
+            tests::{
+                describe,
+                source_file,
+                suggestions_of,
+            },
+            BinaryFile,
+            Linter,
+            ModFiles,
+        },
+    };
+
+    /// a `map.bin` of format 1, `width` by `height` cells, without resources
+    fn map_bin_file(path: &str, width: u8, height: u8) -> BinaryFile {
+        let mut bytes = vec![1, width, 0, height, 0];
+        bytes.resize(bytes.len() + width as usize * height as usize * 3, 0);
+
+        BinaryFile {
+            path: path.into(),
+            bytes,
+        }
+    }
+
+    #[test]
+    fn unresolvable_map_overrides() {
+        // arrange
+        let files = ModFiles {
+            rules: vec![source_file("rules.yaml", "^Infantry:\nE1:\n\tInherits: ^Infantry\n")],
+            weapons: vec![source_file("weapons.yaml", "M1Carbine:\n")],
+            maps: vec![source_file("maps/island/map.yaml", "Rules: rules.yaml, missing.yaml, ra|rules/campaign.yaml\n\tE2:\n\t\tInherits: ^Soldier\n\t-E1:\nWeapons:\n\tM60mg:\n\t\tInherits: M1Carbine\n")],
+            map_overrides: vec![source_file("maps/island/rules.yaml", "E3:\n\tInherits: ^Infantry\nE4:\n\tInherits: ^Nope\n")],
+            ..ModFiles::default()
+        };
+
+        let mut linter = Linter::empty();
+        linter.add_lint(MapOverrides);
+
+        // act
+        let actual = describe(linter.run(&files));
+
+        // assert
+        assert_eq!(
+            vec![
+                ("maps/island/map.yaml".to_owned(), "map-override", "`E4`, defined in `rules.yaml`, can't be resolved: maps/island/rules.yaml:4: parent \"^Nope\" is not defined".to_owned()),
+                ("maps/island/map.yaml".to_owned(), "map-override", "`missing.yaml` is not in the map package".to_owned()),
+                ("maps/island/map.yaml".to_owned(), "map-override", "`E2` can't be resolved: maps/island/map.yaml:3: parent \"^Soldier\" is not defined".to_owned()),
+            ],
+            actual,
+        );
+    }
+
+    #[test]
+    fn invalid_players_and_actors() {
+        // arrange
+        let mut files = ModFiles {
+            rules: vec![source_file("rules.yaml", "World:\n\tFaction@0:\n\t\tInternalName: allies\n\tFaction@1:\n\t\tInternalName: soviet\nMINE:\nE1:\n")],
+            maps: vec![source_file("maps/island/map.yaml", [
+                "MapSize: 64,64\n",
+                "Bounds: 2,2,60,60\n",
+                "Players:\n",
+                "\tPlayerReference@Neutral:\n",
+                "\t\tName: Neutral\n",
+                "\t\tFaction: allies\n",
+                "\tPlayerReference@Multi0:\n",
+                "\t\tName: Multi0\n",
+                "\t\tFaction: nod\n",
+                "\t\tEnemies: Neutral, Multi1\n",
+                "\tPlayerReference@Creeps:\n",
+                "Actors:\n",
+                "\tActor0: mine\n",
+                "\t\tOwner: Neutral\n",
+                "\t\tLocation: 2,61\n",
+                "\tActor1: e2 # typo\n",
+                "\t\tOwner: Multi0\n",
+                "\t\tLocation: 1,20\n",
+                "\tActor2: crate\n",
+                "\t\tOwner: Multi2\n",
+                "\t\tLocation: 64,20\n",
+                "Rules:\n",
+                "\tCRATE:\n",
+            ].concat().as_str())],
+            ..ModFiles::default()
+        };
+
+        let mut linter = Linter::empty();
+        linter.add_lint(MapPlayers);
+        linter.add_lint(MapActors);
+
+        // act
+        let diagnostics = linter.run(&files);
+        let fix_count = linter.fix(&mut files);
+
+        // assert
+        let finding = |code, message: &str| ("maps/island/map.yaml".to_owned(), code, message.to_owned());
+        assert_eq!(
+            vec![
+                finding("map-player", "`nod` is not a faction defined by `World`"),
+                finding("map-player", "`Multi1` is not a player of this map"),
+                finding("map-player", "`PlayerReference@Creeps` has no `Name`"),
+                finding("map-actor", "`e2` is not a defined actor (did you mean `E1`?)"),
+                finding("map-actor", "`Multi2` is not a player of this map"),
+                finding("map-actor", "`64,20` is outside of the map's `MapSize` (64,64)"),
+            ],
+            describe(diagnostics.clone()),
+        );
+
+        assert_eq!(vec!["E1"], suggestions_of(&diagnostics));
+        assert_eq!(0, fix_count);
+    }
+
+    #[test]
+    fn mismatched_dimensions() {
+        // arrange
+        let mut truncated = map_bin_file("maps/desert/map.bin", 2, 2);
+        truncated.bytes.pop();
+
+        let files = ModFiles {
+            maps: vec![
+                source_file("maps/desert/map.yaml", "MapSize: 2,2\n"),
+                source_file("maps/island/map.yaml", "MapSize: 4,4\nBounds: 1,1,4,2\n"),
+                source_file("maps/tundra/map.yaml", "MapSize: 3,2\nBounds: 1,1,2,1\n"),
+            ],
+            map_bins: vec![
+                truncated,
+                map_bin_file("maps/island/map.bin", 4, 2),
+                map_bin_file("maps/tundra/map.bin", 3, 2),
+            ],
+            ..ModFiles::default()
+        };
+
+        let mut linter = Linter::empty();
+        linter.add_lint(MapDimensions);
+
+        // act
+        let actual = describe(linter.run(&files));
+
+        // assert
+        assert_eq!(
+            vec![
+                ("maps/desert/map.yaml".to_owned(), "map-dimensions", "`map.bin` can't be read: ends at byte 16, within its tile layer".to_owned()),
+                ("maps/island/map.yaml".to_owned(), "map-dimensions", "`4,4` is not the size of `map.bin` (4,2)".to_owned()),
+                ("maps/island/map.yaml".to_owned(), "map-dimensions", "`1,1,4,2` is not within `MapSize` (4,4)".to_owned()),
+            ],
+            actual,
+        );
+    }
+
+    #[test]
+    fn overflowing_bounds() {
+        // arrange
+        let files = ModFiles {
+            rules: vec![source_file("rules.yaml", "E1:\n")],
+            maps: vec![source_file("maps/desert/map.yaml", &[
+                "MapSize: 2,2\n",
+                "Bounds: 9223372036854775807,0,1,1\n",
+                "Actors:\n",
+                "\tActor0: e1\n",
+                "\t\tLocation: 1,0\n",
+            ].concat())],
+            ..ModFiles::default()
+        };
+
+        let mut linter = Linter::empty();
+        linter.add_lint(MapActors);
+        linter.add_lint(MapDimensions);
+
+        // act
+        let actual = describe(linter.run(&files));
+
+        // assert
+        assert_eq!(
+            vec![
+                ("maps/desert/map.yaml".to_owned(), "map-dimensions", "`9223372036854775807,0,1,1` is not within `MapSize` (2,2)".to_owned()),
+            ],
+            actual,
+        );
+    }
+
+    #[test]
+    fn undefined_tiles() {
+        // arrange
+        let mut map_bin = map_bin_file("maps/island/map.bin", 4, 1);
+        map_bin.bytes.truncate(5);
+        map_bin.bytes.extend(&[1, 0, 0, 9, 0, 0, 1, 0, 3, 9, 0, 1]);
+
+        let mut files = ModFiles {
+            tilesets: vec![source_file("tilesets/temperat.yaml", "General:\n\tId: TEMPERAT\nTemplates:\n\tTemplate@1:\n\t\tId: 1\n\t\tSize: 1,1\n")],
+            maps: vec![
+                source_file("maps/desert/map.yaml", "Tileset: TEMPERATE\n"),
+                source_file("maps/island/map.yaml", "MapSize: 4,1\nTileset: TEMPERAT\n"),
+            ],
+            map_bins: vec![map_bin],
+            ..ModFiles::default()
+        };
+
+        let mut linter = Linter::empty();
+        linter.add_lint(MapTiles);
+
+        // act
+        let diagnostics = linter.run(&files);
+        let fix_count = linter.fix(&mut files);
+
+        // assert
+        assert_eq!(
+            vec![
+                ("maps/desert/map.yaml".to_owned(), "map-tile", "`TEMPERATE` is not a tileset of the mod (did you mean `TEMPERAT`?)".to_owned()),
+                ("maps/island/map.yaml".to_owned(), "map-tile", "template 9 is not defined by `TEMPERAT`, but is used by 2 cell(s) of `map.bin`, the first at 1,0".to_owned()),
+                ("maps/island/map.yaml".to_owned(), "map-tile", "template 1 has no tile 3, but it is used by 1 cell(s) of `map.bin`, the first at 2,0".to_owned()),
+            ],
+            describe(diagnostics.clone()),
+        );
+
+        assert_eq!(vec!["TEMPERAT"], suggestions_of(&diagnostics));
+        assert_eq!(0, fix_count);
+    }
+}
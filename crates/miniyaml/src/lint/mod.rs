@@ -0,0 +1,1209 @@
+// oraide - tools for OpenRA-based mod/game development
+// get the source code at https://github.com/Phrohdoh/oraide
+//
+// copyright (c)
+// - 2020 Taryn "Phrohdoh" Hill
+
+#![deny(missing_docs)]
+
+//! This [module] exposes lints, which find likely mistakes across all of the
+//! files of a mod, and the [`Linter`] which runs them and reports their
+//! findings as [`Diagnostic`]s.
+//!
+//! Every lint implements [`Lint`], so lints outside of this crate (e.g. a
+//! team's house-style rules) run exactly like the built-in ones.
+//!
+//! Findings can be suppressed with comments, either on the line of the
+//! finding or, for the whole file, before its first node:
+//!
+//! ```text
+//! # oraide-ignore-file: unreferenced-actor
+//! ^Unused: # oraide-ignore: unused-abstract
+//! ```
+//!
+//! A suppression which no longer hides any finding (or never could, as it
+//! names an unknown lint or is misplaced) is itself reported
+//! ([`UNUSED_SUPPRESSION`]).
+//!
+//! Findings with an obvious remedy carry a [`Fix`], which [`Linter::fix`]
+//! applies, while those with only a likely remedy (e.g. the most similar
+//! name to a misspelled one) carry a suggestion, which it doesn't.
+//!
+//! [module]: https://doc.rust-lang.org/book/ch07-02-defining-modules-to-control-scope-and-privacy.html
+//! [`Linter`]: struct.Linter.html
+//! [`Diagnostic`]: struct.Diagnostic.html
+//! [`Lint`]: trait.Lint.html
+//! [`UNUSED_SUPPRESSION`]: constant.UNUSED_SUPPRESSION.html
+//! [`Fix`]: struct.Fix.html
+//! [`Linter::fix`]: struct.Linter.html#method.fix
+
+mod duplicates;
+mod encoding;
+mod indent;
+mod maps;
+mod references;
+mod separators;
+mod sequences;
+mod sprites;
+mod suppress;
+mod terrain;
+mod traits;
+mod unused;
+mod weapons;
+
+pub use {
+    duplicates::DuplicateKey,
+    encoding::InvalidUtf8,
+    indent::{
+        IndentStyle,
+        MixedIndentation,
+    },
+    maps::{
+        MapActors,
+        MapDimensions,
+        MapOverrides,
+        MapPlayers,
+        MapTiles,
+    },
+    references::{
+        default_reference_fields,
+        ReferenceField,
+        UndefinedReference,
+    },
+    separators::MissingSeparator,
+    sequences::{
+        SequenceField,
+        UndefinedSequence,
+        SEQUENCE_FIELDS,
+    },
+    sprites::SequenceSprites,
+    terrain::{
+        UnknownTerrainType,
+        TERRAIN_TYPE_FIELDS,
+    },
+    traits::UnknownTrait,
+    unused::{
+        UnreferencedActor,
+        UnusedAbstract,
+        ENGINE_ACTORS,
+    },
+    weapons::{
+        InvalidWeapon,
+        PROJECTILE_TYPES,
+        WARHEAD_TYPES,
+        WEAPON_FIELDS,
+    },
+};
+
+use {
+    std::{
+        collections::{
+            HashMap,
+            HashSet,
+        },
+        path::{
+            Path,
+            PathBuf,
+        },
+    },
+    crate::{
+        list_items_of,
+        parse_key,
+        span_lines_of,
+        tree_of,
+        apply_edits,
+        decode_lossy,
+        AbsByteIdxSpan,
+        Assets,
+        InvalidSequence,
+        InheritsGraph,
+        MapBin,
+        MapBinError,
+        Node,
+        Tileset,
+        Rules,
+        Schema,
+        SpannedLine,
+        TextEdit,
+        Tree,
+    },
+};
+
+// ----- public interface ------------------------------------------------------
+
+/// How important a [`Diagnostic`] is.
+///
+/// [`Diagnostic`]: struct.Diagnostic.html
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Severity {
+    /// something which will fail to load
+    Error,
+
+    /// something which is very likely a mistake
+    Warning,
+
+    /// something which may be a mistake
+    Info,
+}
+
+/// A finding of a lint.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Diagnostic {
+    /// path of the file the finding is in
+    pub path: PathBuf,
+
+    /// span, in said file, of the text the finding is about
+    pub span: AbsByteIdxSpan,
+
+    /// how important the finding is
+    pub severity: Severity,
+
+    /// short, stable, kebab-case identifier of the lint (e.g.
+    /// `unused-abstract`)
+    pub code: &'static str,
+
+    /// human-readable description of the finding
+    pub message: String,
+
+    /// the edits, to said file, which remedy the finding, if it has an
+    /// obvious remedy
+    pub fix: Option<Fix>,
+}
+
+/// Edits, all to the same file, which remedy a finding.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Fix {
+    /// human-readable description of the fix (e.g. "remove the suppression")
+    pub description: String,
+
+    /// the edits, each spanning text of the file before any of them are
+    /// applied
+    pub edits: Vec<TextEdit>,
+
+    /// `true` if the fix is a guess (e.g. replacing a misspelled name with
+    /// the most similar defined one), which is offered but never applied by
+    /// [`Linter::fix`]
+    ///
+    /// [`Linter::fix`]: struct.Linter.html#method.fix
+    pub is_suggestion: bool,
+}
+
+impl Fix {
+    /// Create a fix consisting of the single edit `edit`.
+    pub fn new(description: impl Into<String>, edit: TextEdit) -> Self {
+        Self {
+            description: description.into(),
+            edits: vec![edit],
+            is_suggestion: false,
+        }
+    }
+
+    /// Create a suggestion (a fix which is only a guess) consisting of the
+    /// single edit `edit`.
+    pub fn suggestion(description: impl Into<String>, edit: TextEdit) -> Self {
+        Self {
+            is_suggestion: true,
+            ..Self::new(description, edit)
+        }
+    }
+}
+
+/// Where a [`SourceFile`] was read from, which decides whether it can be
+/// written back.
+///
+/// [`SourceFile`]: struct.SourceFile.html
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub enum FileLocation {
+    /// nowhere (e.g. the file was created from text), so the file's path
+    /// needn't exist
+    #[default]
+    Memory,
+
+    /// the file at the file's path
+    Disk,
+
+    /// an entry of a zip package (e.g. an `.oramap`), in which case the
+    /// file's path (that of the entry within the package) isn't on disk
+    Zip {
+        /// path of the zip package
+        package: PathBuf,
+
+        /// the entry's `/`-separated path relative to the package
+        entry: String,
+    },
+}
+
+/// A file and its contents.
+#[derive(Clone, PartialEq, Debug)]
+pub struct SourceFile {
+    /// path of the file, as reported in diagnostics
+    pub path: PathBuf,
+
+    /// where the file was read from
+    pub location: FileLocation,
+
+    /// contents of the file
+    pub text: String,
+
+    /// the bytes of the file which aren't valid UTF-8, each of which was
+    /// replaced in `text` (see [`SourceFile::decode`])
+    ///
+    /// [`SourceFile::decode`]: struct.SourceFile.html#method.decode
+    pub invalid_sequences: Vec<InvalidSequence>,
+}
+
+impl SourceFile {
+    /// Create a file whose contents are `text`.
+    pub fn new(path: impl Into<PathBuf>, text: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            location: FileLocation::Memory,
+            text: text.into(),
+            invalid_sequences: vec![],
+        }
+    }
+
+    /// Create a file whose contents are `bytes`, decoded lossily (see
+    /// [`decode_lossy`]).
+    ///
+    /// [`decode_lossy`]: fn.decode_lossy.html
+    pub fn decode(path: impl Into<PathBuf>, bytes: &[u8]) -> Self {
+        let decoded = decode_lossy(bytes);
+
+        Self {
+            path: path.into(),
+            location: FileLocation::Memory,
+            text: decoded.text,
+            invalid_sequences: decoded.invalid_sequences,
+        }
+    }
+
+    /// Record that this file was read from `location`.
+    pub fn with_location(self, location: FileLocation) -> Self {
+        Self { location, ..self }
+    }
+}
+
+/// A binary file and its contents.
+#[derive(Clone, PartialEq, Debug)]
+pub struct BinaryFile {
+    /// path of the file
+    pub path: PathBuf,
+
+    /// contents of the file
+    pub bytes: Vec<u8>,
+}
+
+/// The files of a mod which lints inspect, each kind in load order.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct ModFiles {
+    /// files defining actors (the manifest's `Rules`)
+    pub rules: Vec<SourceFile>,
+
+    /// files defining weapons (the manifest's `Weapons`)
+    pub weapons: Vec<SourceFile>,
+
+    /// files defining the sprite sequences of images (the manifest's
+    /// `Sequences`)
+    pub sequences: Vec<SourceFile>,
+
+    /// files defining the terrain types and templates of each theater (the
+    /// manifest's `TileSets`)
+    pub tilesets: Vec<SourceFile>,
+
+    /// the `map.yaml` of each map
+    pub maps: Vec<SourceFile>,
+
+    /// the files of each map package which its `map.yaml` lists as
+    /// overriding the mod's definitions (e.g. in its `Rules`)
+    pub map_overrides: Vec<SourceFile>,
+
+    /// the `map.bin` of each map, which (not being text) aren't linted
+    /// themselves, but which lints of each `map.yaml` inspect
+    pub map_bins: Vec<BinaryFile>,
+
+    /// the files of the packages the mod mounts (the manifest's `Packages`),
+    /// which sequences are drawn from
+    pub assets: Assets,
+
+    /// what the engine the mod runs on defines, if known
+    pub schema: Option<Schema>,
+}
+
+impl ModFiles {
+    /// The files defining `kind`.
+    pub fn files_of(&self, kind: DefinitionKind) -> &[SourceFile] {
+        match kind {
+            DefinitionKind::Actor => &self.rules,
+            DefinitionKind::Weapon => &self.weapons,
+            DefinitionKind::Sequence => &self.sequences,
+        }
+    }
+
+    /// Every file, and what it is, in the order they are loaded in (rules,
+    /// weapons, sequences, tilesets, maps, then map overrides).
+    pub fn iter(&self) -> impl Iterator<Item = (FileKind, &SourceFile)> {
+        with_kind(FileKind::Rules, &self.rules)
+            .chain(with_kind(FileKind::Weapons, &self.weapons))
+            .chain(with_kind(FileKind::Sequences, &self.sequences))
+            .chain(with_kind(FileKind::Tileset, &self.tilesets))
+            .chain(with_kind(FileKind::Map, &self.maps))
+            .chain(with_kind(FileKind::MapOverride, &self.map_overrides))
+    }
+
+    /// Every file, mutably, in the order they are loaded in (see [`iter`]).
+    ///
+    /// [`iter`]: struct.ModFiles.html#method.iter
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut SourceFile> {
+        self.rules.iter_mut()
+            .chain(self.weapons.iter_mut())
+            .chain(self.sequences.iter_mut())
+            .chain(self.tilesets.iter_mut())
+            .chain(self.maps.iter_mut())
+            .chain(self.map_overrides.iter_mut())
+    }
+
+    /// Merge every rules file into [`Rules`].
+    ///
+    /// [`Rules`]: struct.Rules.html
+    pub fn merged_rules(&self) -> Rules {
+        let mut rules = Rules::new();
+        for file in &self.rules {
+            rules.add_file(file.path.clone(), &file.text);
+        }
+
+        rules
+    }
+
+    /// Merge every weapons file into [`Rules`], from which [`Weapon`]s are
+    /// resolved.
+    ///
+    /// [`Rules`]: struct.Rules.html
+    /// [`Weapon`]: struct.Weapon.html
+    pub fn merged_weapons(&self) -> Rules {
+        let mut weapons = Rules::new();
+        for file in &self.weapons {
+            weapons.add_file(file.path.clone(), &file.text);
+        }
+
+        weapons
+    }
+
+    /// Merge every sequences file into [`Rules`], from which [`Image`]s are
+    /// resolved.
+    ///
+    /// [`Rules`]: struct.Rules.html
+    /// [`Image`]: struct.Image.html
+    pub fn merged_sequences(&self) -> Rules {
+        let mut sequences = Rules::new();
+        for file in &self.sequences {
+            sequences.add_file(file.path.clone(), &file.text);
+        }
+
+        sequences
+    }
+}
+
+/// What a file of a mod is.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum FileKind {
+    /// one of the manifest's `Rules`
+    Rules,
+
+    /// one of the manifest's `Weapons`
+    Weapons,
+
+    /// one of the manifest's `Sequences`
+    Sequences,
+
+    /// one of the manifest's `TileSets`
+    Tileset,
+
+    /// the `map.yaml` of a map
+    Map,
+
+    /// a file of a map package which its `map.yaml` lists as overriding the
+    /// mod's definitions
+    MapOverride,
+}
+
+impl FileKind {
+    /// What the top-level nodes of a file of this kind define, if they are
+    /// definitions.
+    pub fn definition_kind(self) -> Option<DefinitionKind> {
+        match self {
+            FileKind::Rules => Some(DefinitionKind::Actor),
+            FileKind::Weapons => Some(DefinitionKind::Weapon),
+            FileKind::Sequences => Some(DefinitionKind::Sequence),
+            FileKind::Tileset | FileKind::Map | FileKind::MapOverride => None,
+        }
+    }
+}
+
+/// What a top-level node of a file defines, which depends on what the file is
+/// listed as in the manifest.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum DefinitionKind {
+    /// an actor (or actor template), defined in a rules file
+    Actor,
+
+    /// a weapon, defined in a weapons file
+    Weapon,
+
+    /// the sequences of an image, defined in a sequences file
+    Sequence,
+}
+
+/// The first definition of a top-level node with a given name.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Definition<'files> {
+    /// the node's key, sans trailing whitespace
+    pub name: &'files str,
+
+    /// path of the file defining the node
+    pub path: &'files Path,
+
+    /// span, in said file, of the node's key
+    pub key: AbsByteIdxSpan,
+}
+
+/// A file being linted, along with the lines and tree derived from it.
+pub struct LintFile<'files> {
+    /// what the file is
+    pub kind: FileKind,
+
+    /// path of the file
+    pub path: &'files Path,
+
+    /// contents of the file
+    pub text: &'files str,
+
+    /// the bytes of the file which aren't valid UTF-8
+    pub invalid_sequences: &'files [InvalidSequence],
+
+    /// the spanned lines of `text`
+    pub lines: Vec<SpannedLine>,
+
+    /// the tree of `lines`
+    pub tree: Tree,
+}
+
+/// Everything a [`Lint`] may need to know about the mod being linted.
+///
+/// [`Lint`]: trait.Lint.html
+pub struct LintContext<'files> {
+    files: Vec<LintFile<'files>>,
+    rules: Rules,
+    weapons: Rules,
+    sequences: Rules,
+
+    /// indexed by `DefinitionKind as usize`
+    definitions: [Vec<Definition<'files>>; 3],
+
+    /// lowercase names of every node named by an `Inherits`
+    inherited_names: HashSet<String>,
+
+    /// lowercase text of every item of every value of every rules, weapons,
+    /// and map file (except those of `Inherits`)
+    referenced_names: HashSet<String>,
+
+    /// the path and layers of every `map.bin`
+    map_bins: Vec<(&'files Path, Result<MapBin, MapBinError>)>,
+
+    tilesets: Vec<Tileset>,
+    assets: &'files Assets,
+    schema: Option<&'files Schema>,
+}
+
+impl<'files> LintContext<'files> {
+    /// Derive everything lints need to know from `mod_files`.
+    pub fn new(mod_files: &'files ModFiles) -> Self {
+        let files = mod_files.iter()
+            .map(|(kind, file)| {
+                let lines = span_lines_of(&file.text);
+                let tree = tree_of(&file.text, &lines);
+
+                LintFile {
+                    kind,
+                    path: &file.path,
+                    text: &file.text,
+                    invalid_sequences: &file.invalid_sequences,
+                    lines,
+                    tree,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let rules = mod_files.merged_rules();
+        let weapons = mod_files.merged_weapons();
+        let sequences = mod_files.merged_sequences();
+
+        let inherited_names = InheritsGraph::of_rules(&rules).edges()
+            .map(|(_, parent)| parent.to_lowercase())
+            .collect();
+
+        let mut definitions = [vec![], vec![], vec![]];
+        let mut seen_names = [HashSet::new(), HashSet::new(), HashSet::new()];
+        let mut referenced_names = HashSet::new();
+
+        for file in &files {
+            if let Some(kind) = file.kind.definition_kind() {
+                for node in &file.tree.nodes {
+                    let name = file.text[node.key].trim_end();
+
+                    if parse_key(file.text, node.key).is_removal()
+                        || !seen_names[kind as usize].insert(name.to_lowercase())
+                    {
+                        continue;
+                    }
+
+                    definitions[kind as usize].push(Definition {
+                        name,
+                        path: file.path,
+                        key: node.key,
+                    });
+                }
+            }
+
+            if file.kind != FileKind::Sequences && file.kind != FileKind::Tileset {
+                referenced_names.extend(referenced_names_of(file));
+            }
+        }
+
+        let tilesets = files.iter()
+            .filter(|file| file.kind == FileKind::Tileset)
+            .map(|file| Tileset::of_tree(file.text, &file.tree))
+            .collect();
+
+        let map_bins = mod_files.map_bins.iter()
+            .map(|file| (file.path.as_path(), MapBin::parse(&file.bytes)))
+            .collect();
+
+        Self {
+            files,
+            rules,
+            weapons,
+            sequences,
+            definitions,
+            inherited_names,
+            referenced_names,
+            map_bins,
+            tilesets,
+            assets: &mod_files.assets,
+            schema: mod_files.schema.as_ref(),
+        }
+    }
+
+    /// Every file of the mod, in load order.
+    pub fn files(&self) -> &[LintFile<'files>] {
+        &self.files
+    }
+
+    /// The merged rules of the mod.
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    /// The merged weapons of the mod.
+    pub fn weapons(&self) -> &Rules {
+        &self.weapons
+    }
+
+    /// The merged sequences of the mod.
+    pub fn sequences(&self) -> &Rules {
+        &self.sequences
+    }
+
+    /// The first definition of every distinct (ignoring case) name of `kind`,
+    /// in load order.
+    pub fn definitions_of(&self, kind: DefinitionKind) -> &[Definition<'files>] {
+        &self.definitions[kind as usize]
+    }
+
+    /// The first definition of `name`, ignoring case, of `kind`.
+    pub fn definition_of(&self, kind: DefinitionKind, name: &str) -> Option<&Definition<'files>> {
+        self.definitions_of(kind).iter()
+            .find(|definition| definition.name.eq_ignore_ascii_case(name))
+    }
+
+    /// `true` if `node`, a top-level node of `file`, is the first definition
+    /// of its name of `kind`.
+    pub fn is_first_definition(&self, kind: DefinitionKind, file: &LintFile, node: &Node) -> bool {
+        self.definition_of(kind, file.text[node.key].trim_end())
+            .is_some_and(|definition| definition.path == file.path && definition.key == node.key)
+    }
+
+    /// `true` if any `Inherits` names `name`, ignoring case.
+    pub fn is_inherited(&self, name: &str) -> bool {
+        self.inherited_names.contains(&name.to_lowercase())
+    }
+
+    /// `true` if any item of any value (other than that of an `Inherits`) of
+    /// any rules, weapons, or map file is `name`, ignoring case.
+    pub fn is_referenced(&self, name: &str) -> bool {
+        self.referenced_names.contains(&name.to_lowercase())
+    }
+
+    /// Every tileset of the mod, in load order.
+    pub fn tilesets(&self) -> &[Tileset] {
+        &self.tilesets
+    }
+
+    /// The files of the packages the mod mounts.
+    pub fn assets(&self) -> &Assets {
+        self.assets
+    }
+
+    /// What the engine the mod runs on defines, if known.
+    pub fn schema(&self) -> Option<&Schema> {
+        self.schema
+    }
+
+    /// The tileset whose `Id` is `id`.
+    pub fn tileset_of(&self, id: &str) -> Option<&Tileset> {
+        self.tilesets.iter().find(|tileset| tileset.id.as_deref() == Some(id))
+    }
+
+    /// The layers of the `map.bin` beside `map_yaml` (i.e. in the same map
+    /// package), or why they could not be read, if there is such a file.
+    pub fn map_bin_of(&self, map_yaml: &LintFile) -> Option<&Result<MapBin, MapBinError>> {
+        let package_dir = map_yaml.path.parent()?;
+
+        self.map_bins.iter()
+            .find(|(path, _)| path.parent() == Some(package_dir))
+            .map(|(_, map_bin)| map_bin)
+    }
+}
+
+/// Something a [`Lint`] found, which the [`Linter`] turns into a
+/// [`Diagnostic`].
+///
+/// [`Lint`]: trait.Lint.html
+/// [`Linter`]: struct.Linter.html
+/// [`Diagnostic`]: struct.Diagnostic.html
+#[derive(Clone, PartialEq, Debug)]
+pub struct Finding {
+    /// span, in the file being checked, of the text the finding is about
+    pub span: AbsByteIdxSpan,
+
+    /// human-readable description of the finding
+    pub message: String,
+
+    /// the edits, to the file being checked, which remedy the finding, if it
+    /// has an obvious remedy
+    pub fix: Option<Fix>,
+}
+
+impl Finding {
+    /// Create a finding without a fix.
+    pub fn new(span: AbsByteIdxSpan, message: impl Into<String>) -> Self {
+        Self {
+            span,
+            message: message.into(),
+            fix: None,
+        }
+    }
+
+    /// Attach `fix` to this finding.
+    pub fn with_fix(self, fix: Fix) -> Self {
+        Self {
+            fix: Some(fix),
+            ..self
+        }
+    }
+}
+
+/// A check which finds likely mistakes.
+pub trait Lint {
+    /// Short, stable, kebab-case identifier of the lint (e.g.
+    /// `unused-abstract`), by which it is configured.
+    fn code(&self) -> &'static str;
+
+    /// How important the lint's findings are, unless configured otherwise.
+    fn default_severity(&self) -> Severity;
+
+    /// Check a single file of the mod described by `cx`.
+    fn check(&self, cx: &LintContext, file: &LintFile) -> Vec<Finding>;
+}
+
+/// How a single lint is configured.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct LintConfig {
+    /// `false` if the lint should not be run
+    pub is_enabled: bool,
+
+    /// how important the lint's findings are, if not its default severity
+    pub severity: Option<Severity>,
+}
+
+impl Default for LintConfig {
+    fn default() -> Self {
+        Self {
+            is_enabled: true,
+            severity: None,
+        }
+    }
+}
+
+/// The code of findings about suppression directives which don't hide any
+/// finding (a warning, unless configured otherwise), including those naming
+/// an unknown lint code and `oraide-ignore-file` directives after a file's
+/// first node.
+pub const UNUSED_SUPPRESSION: &str = "unused-suppression";
+
+/// The built-in lints, each with its default configuration.
+pub fn builtin_lints() -> Vec<Box<dyn Lint>> {
+    vec![
+        Box::new(UnusedAbstract),
+        Box::new(UnreferencedActor),
+        Box::new(UndefinedReference::default()),
+        Box::new(UnknownTrait),
+        Box::new(DuplicateKey),
+        Box::new(MissingSeparator),
+        Box::new(InvalidUtf8),
+        Box::new(MixedIndentation::default()),
+        Box::new(MapOverrides),
+        Box::new(MapPlayers),
+        Box::new(MapActors),
+        Box::new(MapDimensions),
+        Box::new(MapTiles),
+        Box::new(UnknownTerrainType),
+        Box::new(UndefinedSequence),
+        Box::new(SequenceSprites),
+        Box::new(InvalidWeapon::default()),
+    ]
+}
+
+/// A set of lints, and their configuration, to run against a mod.
+pub struct Linter {
+    lints: Vec<Box<dyn Lint>>,
+    configs: HashMap<String, LintConfig>,
+}
+
+impl Linter {
+    /// Create a linter which runs every built-in lint.
+    pub fn new() -> Self {
+        Self {
+            lints: builtin_lints(),
+            configs: HashMap::new(),
+        }
+    }
+
+    /// Create a linter which runs no lints.
+    pub fn empty() -> Self {
+        Self {
+            lints: vec![],
+            configs: HashMap::new(),
+        }
+    }
+
+    /// Add `lint`, replacing any lint with the same code.
+    pub fn add_lint(&mut self, lint: impl Lint + 'static) {
+        self.lints.retain(|existing| existing.code() != lint.code());
+        self.lints.push(Box::new(lint));
+    }
+
+    /// The lints, in the order they run in.
+    pub fn lints(&self) -> impl Iterator<Item = &dyn Lint> {
+        self.lints.iter().map(|lint| lint.as_ref())
+    }
+
+    /// Configure the lint with the code `code` (whether or not it has been
+    /// added yet).
+    pub fn configure(&mut self, code: impl Into<String>, config: LintConfig) {
+        self.configs.insert(code.into(), config);
+    }
+
+    /// The configuration of the lint with the code `code`.
+    pub fn config_of(&self, code: &str) -> LintConfig {
+        self.configs.get(code)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Run every enabled lint against every file of `files`, returning their
+    /// unsuppressed findings grouped by file (in load order) and ordered by
+    /// position.
+    pub fn run(&self, files: &ModFiles) -> Vec<Diagnostic> {
+        let cx = LintContext::new(files);
+        let mut diagnostics = vec![];
+
+        let enabled_lints = self.lints.iter()
+            .filter(|lint| self.config_of(lint.code()).is_enabled)
+            .collect::<Vec<_>>();
+
+        for file in cx.files() {
+            let mut suppressions = suppress::suppressions_of(file);
+            let mut file_diagnostics = vec![];
+
+            for lint in &enabled_lints {
+                let severity = self.config_of(lint.code()).severity
+                    .unwrap_or_else(|| lint.default_severity());
+
+                for finding in lint.check(&cx, file) {
+                    let (start, _) = finding.span.into();
+                    let line_idx = suppress::line_idx_of(file, start);
+
+                    let opt_suppression = suppressions.iter_mut()
+                        .find(|suppression| suppression.hides(lint.code(), line_idx));
+
+                    if let Some(suppression) = opt_suppression {
+                        suppression.is_used = true;
+                        continue;
+                    }
+
+                    file_diagnostics.push(Diagnostic {
+                        path: file.path.to_path_buf(),
+                        span: finding.span,
+                        severity,
+                        code: lint.code(),
+                        message: finding.message,
+                        fix: finding.fix,
+                    });
+                }
+            }
+
+            let unused_suppression_config = self.config_of(UNUSED_SUPPRESSION);
+            if unused_suppression_config.is_enabled {
+                for suppression in suppressions.iter().filter(|suppression| !suppression.is_used) {
+                    let code = suppression.code.as_str();
+                    let is_known = code == UNUSED_SUPPRESSION || self.lints().any(|lint| lint.code() == code);
+
+                    let finding = if suppression.is_misplaced {
+                        Finding::new(
+                            suppression.span,
+                            format!(
+                                "suppression of `{}` doesn't hide any finding, as `oraide-ignore-file` must precede the file's first node",
+                                code,
+                            ),
+                        )
+                    } else if !is_known {
+                        let known_codes = self.lints()
+                            .map(|lint| lint.code())
+                            .chain(std::iter::once(UNUSED_SUPPRESSION));
+
+                        references::finding_with_suggestion(
+                            suppression.span,
+                            format!("`{}` is not the code of any lint", code),
+                            references::closest_of(code, known_codes),
+                        )
+                    } else if enabled_lints.iter().any(|lint| lint.code() == code) {
+                        let finding = Finding::new(
+                            suppression.span,
+                            format!("suppression of `{}` doesn't hide any finding", code),
+                        );
+
+                        match suppression.removal.clone() {
+                            Some(edit) => finding.with_fix(Fix::new("remove the suppression", edit)),
+                            None => finding,
+                        }
+                    } else {
+                        // suppressions of lints which didn't run can't be
+                        // judged
+                        continue;
+                    };
+
+                    file_diagnostics.push(Diagnostic {
+                        path: file.path.to_path_buf(),
+                        span: finding.span,
+                        severity: unused_suppression_config.severity.unwrap_or(Severity::Warning),
+                        code: UNUSED_SUPPRESSION,
+                        message: finding.message,
+                        fix: finding.fix,
+                    });
+                }
+            }
+
+            file_diagnostics.sort_by_key(|diagnostic| {
+                let (start, _) = diagnostic.span.into();
+                start
+            });
+
+            diagnostics.extend(file_diagnostics);
+        }
+
+        diagnostics
+    }
+
+    /// Apply the fix of every diagnostic which has one (other than
+    /// suggestions) to `files`, repeating until no fixes remain (or a fixed
+    /// number of passes have been made, in case fixes keep producing
+    /// findings), returning how many fixes were applied.
+    ///
+    /// Within a file, a fix overlapping one closer to the start of the file
+    /// is left for the next pass, and text no fix touches is unchanged.
+    /// Files which aren't valid UTF-8 are never fixed, as their text can't be
+    /// written back without changing their invalid bytes.
+    pub fn fix(&self, files: &mut ModFiles) -> usize {
+        let mut fix_count = 0;
+
+        for _ in 0..MAX_FIX_PASSES {
+            let diagnostics = self.run(files);
+            let mut pass_fix_count = 0;
+
+            for file in files.iter_mut().filter(|file| file.invalid_sequences.is_empty()) {
+                let mut edits = vec![];
+
+                let fixes = diagnostics.iter()
+                    .filter(|diagnostic| diagnostic.path == file.path)
+                    .filter_map(|diagnostic| diagnostic.fix.as_ref())
+                    .filter(|fix| !fix.is_suggestion);
+
+                for fix in fixes {
+                    // a fix is applied entirely or not at all
+                    let overlaps_earlier_fix = fix.edits.iter()
+                        .any(|edit| edits.iter().any(|existing| overlaps(existing, edit)));
+
+                    if !overlaps_earlier_fix {
+                        edits.extend(fix.edits.iter().cloned());
+                        pass_fix_count += 1;
+                    }
+                }
+
+                if !edits.is_empty() {
+                    file.text = apply_edits(&file.text, &edits);
+                }
+            }
+
+            if pass_fix_count == 0 {
+                break;
+            }
+
+            fix_count += pass_fix_count;
+        }
+
+        fix_count
+    }
+}
+
+impl Default for Linter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ----- private implementation details ----------------------------------------
+
+/// how many times [`Linter::fix`] re-lints and re-fixes a mod
+///
+/// [`Linter::fix`]: struct.Linter.html#method.fix
+const MAX_FIX_PASSES: usize = 8;
+
+/// `true` if `a` and `b` replace any of the same text, or insert at the same
+/// position
+fn overlaps(a: &TextEdit, b: &TextEdit) -> bool {
+    let (a_start, a_end) = a.replaced.into();
+    let (b_start, b_end) = b.replaced.into();
+
+    (a_start < b_end && b_start < a_end) || a_start == b_start
+}
+
+fn with_kind(
+    kind: FileKind,
+    files: &[SourceFile],
+) -> impl Iterator<Item = (FileKind, &SourceFile)> {
+    files.iter().map(move |file| (kind, file))
+}
+
+/// the lowercase text of every item of every value of `file` (except those of
+/// `Inherits`, which are inheritance rather than references)
+fn referenced_names_of<'a>(file: &'a LintFile) -> impl Iterator<Item = String> + 'a {
+    let doc = file.text;
+
+    file.lines.iter()
+        .filter(move |line| {
+            let key_txt = line.key.map(|span| doc[span].trim_end()).unwrap_or("");
+            key_txt != "Inherits" && !key_txt.starts_with("Inherits@")
+        })
+        .filter_map(|line| line.value)
+        .flat_map(move |value_span| list_items_of(doc, value_span))
+        .map(move |item_span| doc[item_span].to_lowercase())
+}
+
+// ----- tests -----------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    pub(super) fn source_file(path: &str, text: &str) -> SourceFile {
+        SourceFile::new(path, text)
+    }
+
+    /// `(path, code, message)` of every diagnostic
+    pub(super) fn describe(diagnostics: Vec<Diagnostic>) -> Vec<(String, &'static str, String)> {
+        diagnostics.into_iter()
+            .map(|diagnostic| (
+                diagnostic.path.to_str().unwrap().to_owned(),
+                diagnostic.code,
+                diagnostic.message,
+            ))
+            .collect()
+    }
+
+    /// the replacement text of every suggestion (see [`Fix::is_suggestion`])
+    ///
+    /// [`Fix::is_suggestion`]: ../struct.Fix.html#structfield.is_suggestion
+    pub(super) fn suggestions_of(diagnostics: &[Diagnostic]) -> Vec<&str> {
+        diagnostics.iter()
+            .filter_map(|diagnostic| diagnostic.fix.as_ref())
+            .filter(|fix| fix.is_suggestion)
+            .flat_map(|fix| fix.edits.iter().map(|edit| edit.new_text.as_str()))
+            .collect()
+    }
+
+    /// reports every top-level node named `Todo`
+    struct NoTodo;
+
+    impl Lint for NoTodo {
+        fn code(&self) -> &'static str {
+            "no-todo"
+        }
+
+        fn default_severity(&self) -> Severity {
+            Severity::Warning
+        }
+
+        fn check(&self, _cx: &LintContext, file: &LintFile) -> Vec<Finding> {
+            file.tree.nodes.iter()
+                .filter(|node| file.text[node.key].trim_end() == "Todo")
+                .map(|node| Finding::new(node.key, "remove this"))
+                .collect()
+        }
+    }
+
+    #[test]
+    fn runs_custom_lints_with_configured_severities() {
+        // arrange
+        let files = ModFiles {
+            rules: vec![source_file("rules.yaml", "Todo:\n^Unused:\n")],
+            weapons: vec![source_file("weapons.yaml", "Todo:\n")],
+            ..ModFiles::default()
+        };
+
+        let mut linter = Linter::new();
+        linter.add_lint(NoTodo);
+        linter.configure("no-todo", LintConfig { is_enabled: true, severity: Some(Severity::Error) });
+        linter.configure("unreferenced-actor", LintConfig { is_enabled: false, severity: None });
+
+        // act
+        let actual = linter.run(&files).into_iter()
+            .map(|diagnostic| (diagnostic.path.to_str().unwrap().to_owned(), diagnostic.code, diagnostic.severity))
+            .collect::<Vec<_>>();
+
+        // assert
+        assert_eq!(
+            vec![
+                ("rules.yaml".to_owned(), "no-todo", Severity::Error),
+                ("rules.yaml".to_owned(), "unused-abstract", Severity::Warning),
+                ("weapons.yaml".to_owned(), "no-todo", Severity::Error),
+            ],
+            actual,
+        );
+    }
+
+    #[test]
+    fn suppressions() {
+        // arrange
+        let files = ModFiles {
+            rules: vec![
+                source_file("a.yaml", "# oraide-ignore-file: unreferenced-actor\n^A: # oraide-ignore: unused-abstract\n^B:\nC:\n"),
+                source_file("b.yaml", "D: # oraide-ignore: unused-abstract, unreferenced-actor\n# oraide-ignore-file: unreferenced-actor\nE:\n"),
+            ],
+            ..ModFiles::default()
+        };
+
+        // act
+        let actual = Linter::new().run(&files).into_iter()
+            .map(|diagnostic| (
+                diagnostic.path.to_str().unwrap().to_owned(),
+                &files.rules[if diagnostic.path == Path::new("a.yaml") { 0 } else { 1 }].text[diagnostic.span],
+                diagnostic.code,
+            ))
+            .collect::<Vec<_>>();
+
+        // assert
+        assert_eq!(
+            vec![
+                ("a.yaml".to_owned(), "^B", "unused-abstract"),
+                ("b.yaml".to_owned(), "unused-abstract", UNUSED_SUPPRESSION),
+                ("b.yaml".to_owned(), "unreferenced-actor", UNUSED_SUPPRESSION),
+                ("b.yaml".to_owned(), "E", "unreferenced-actor"),
+            ],
+            actual,
+        );
+    }
+
+    #[test]
+    fn unknown_and_misplaced_suppressions() {
+        // arrange
+        let files = ModFiles {
+            rules: vec![
+                source_file("rules.yaml", "^A: # oraide-ignore: unused-abstrct, no-such-lint
+# oraide-ignore-file: unused-abstract
+^B:
+"),
+            ],
+            ..ModFiles::default()
+        };
+
+        let mut linter = Linter::empty();
+        linter.add_lint(UnusedAbstract);
+
+        // act
+        let diagnostics = linter.run(&files);
+
+        // assert
+        let finding = |code, message: &str| ("rules.yaml".to_owned(), code, message.to_owned());
+        assert_eq!(
+            vec![
+                finding("unused-abstract", "abstract actor `^A` is never inherited from"),
+                finding(UNUSED_SUPPRESSION, "`unused-abstrct` is not the code of any lint (did you mean `unused-abstract`?)"),
+                finding(UNUSED_SUPPRESSION, "`no-such-lint` is not the code of any lint"),
+                finding(UNUSED_SUPPRESSION, "suppression of `unused-abstract` doesn't hide any finding, as `oraide-ignore-file` must precede the file's first node"),
+                finding("unused-abstract", "abstract actor `^B` is never inherited from"),
+            ],
+            describe(diagnostics.clone()),
+        );
+
+        assert_eq!(vec!["unused-abstract"], suggestions_of(&diagnostics));
+    }
+
+    #[test]
+    fn fixes_unused_suppressions() {
+        // arrange
+        let mut files = ModFiles {
+            rules: vec![
+                source_file("rules.yaml", "# oraide-ignore-file: unused-abstract\n^A: # oraide-ignore: unreferenced-actor\nB: # oraide-ignore: unused-abstract, unreferenced-actor\n\tInherits: ^A\n"),
+            ],
+            ..ModFiles::default()
+        };
+
+        // act
+        let fix_count = Linter::new().fix(&mut files);
+
+        // assert
+        assert_eq!(2, fix_count);
+        assert_eq!(
+            "^A:\nB: # oraide-ignore: unused-abstract, unreferenced-actor\n\tInherits: ^A\n",
+            files.rules[0].text,
+        );
+    }
+
+    #[test]
+    fn never_fixes_files_which_are_not_valid_utf8() {
+        // arrange
+        let mut files = ModFiles {
+            rules: vec![SourceFile::decode("rules.yaml", b"^A: # oraide-ignore: unreferenced-actor\nB: \xFF\n\tInherits: ^A\n")],
+            ..ModFiles::default()
+        };
+        let original_text = files.rules[0].text.clone();
+
+        // act
+        let fix_count = Linter::new().fix(&mut files);
+
+        // assert
+        assert_eq!(0, fix_count);
+        assert_eq!(original_text, files.rules[0].text);
+    }
+}
@@ -0,0 +1,330 @@
+// oraide - tools for OpenRA-based mod/game development
+// get the source code at https://github.com/Phrohdoh/oraide
+//
+// copyright (c)
+// - 2020 Taryn "Phrohdoh" Hill
+
+//! the lint reporting references to definitions which don't exist
+
+use {
+    crate::{
+        lint::{
+            Definition,
+            DefinitionKind,
+            Finding,
+            Fix,
+            Lint,
+            LintContext,
+            LintFile,
+            Severity,
+        },
+        list_items_of,
+        parse_key,
+        AbsByteIdxSpan,
+        TextEdit,
+    },
+};
+
+// ----- public interface ------------------------------------------------------
+
+/// A field whose value (or each item of whose comma-separated value) names a
+/// top-level definition.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ReferenceField {
+    /// the field's key, sans `@` suffix (so `Inherits` also matches
+    /// `Inherits@experience`)
+    pub key: String,
+
+    /// what the value names, where `None` means a definition of the same
+    /// kind as the file the field is in (as with `Inherits`)
+    pub kind: Option<DefinitionKind>,
+}
+
+/// The fields which [`UndefinedReference`] checks when the mod's [`Schema`]
+/// doesn't list its own.
+///
+/// [`UndefinedReference`]: struct.UndefinedReference.html
+/// [`Schema`]: ../struct.Schema.html
+pub fn default_reference_fields() -> Vec<ReferenceField> {
+    let field = |key: &str, kind| ReferenceField { key: key.to_owned(), kind };
+
+    vec![
+        field("Inherits", None),
+        field("Weapon", Some(DefinitionKind::Weapon)),
+        field("EmptyWeapon", Some(DefinitionKind::Weapon)),
+        field("Image", Some(DefinitionKind::Sequence)),
+        field("Actor", Some(DefinitionKind::Actor)),
+    ]
+}
+
+/// Reports each item of the value of a reference field, in any rules,
+/// weapons, or sequences file, which doesn't name a definition of the
+/// expected kind (`undefined-reference`), suggesting similarly-named
+/// definitions.
+///
+/// The reference fields are those listed by the mod's [`Schema`], if it lists
+/// any, else the lint's own.  Names are compared ignoring case, as the engine
+/// does.
+///
+/// [`Schema`]: ../struct.Schema.html
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct UndefinedReference {
+    /// the fields to check, unless the mod's schema lists its own
+    pub fields: Vec<ReferenceField>,
+}
+
+impl UndefinedReference {
+    /// Check the values of `fields`.
+    pub fn new(fields: Vec<ReferenceField>) -> Self {
+        Self { fields }
+    }
+}
+
+// ----- external trait impls --------------------------------------------------
+
+impl Default for UndefinedReference {
+    fn default() -> Self {
+        Self::new(default_reference_fields())
+    }
+}
+
+impl Lint for UndefinedReference {
+    fn code(&self) -> &'static str {
+        "undefined-reference"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, cx: &LintContext, file: &LintFile) -> Vec<Finding> {
+        let file_kind = match file.kind.definition_kind() {
+            Some(file_kind) => file_kind,
+            None => return vec![],
+        };
+
+        let fields = cx.schema()
+            .and_then(|schema| schema.references.as_ref())
+            .unwrap_or(&self.fields);
+
+        let doc = file.text;
+        let mut findings = vec![];
+
+        // the name of the top-level node the line is in
+        let mut enclosing_name = "";
+
+        for line in &file.lines {
+            let is_top_level = line.indent.is_none_or(|indent_span| doc[indent_span].is_empty());
+            if let (Some(key_span), true) = (line.key, is_top_level) {
+                enclosing_name = doc[key_span].trim_end();
+            }
+
+            let (key_span, value_span) = match (line.key, line.value) {
+                (Some(key_span), Some(value_span)) => (key_span, value_span),
+                _ => continue,
+            };
+
+            let key_parts = parse_key(doc, key_span);
+            let field = fields.iter()
+                .find(|field| field.key == doc[key_parts.name]);
+
+            let kind = match field {
+                Some(field) => field.kind.unwrap_or(file_kind),
+                None => continue,
+            };
+
+            for item_span in list_items_of(doc, value_span) {
+                let item_txt = &doc[item_span];
+                if cx.definition_of(kind, item_txt).is_some() {
+                    continue;
+                }
+
+                // suggesting a definition refer to itself (e.g. inherit from
+                // itself) would only replace one mistake with another
+                let names = cx.definitions_of(kind).iter()
+                    .map(|definition| definition.name)
+                    .filter(|name| kind != file_kind || !name.eq_ignore_ascii_case(enclosing_name));
+
+                findings.push(finding_with_suggestion(
+                    item_span,
+                    format!("`{}` is not a defined {}", item_txt, kind_txt(kind)),
+                    closest_of(item_txt, names),
+                ));
+            }
+        }
+
+        findings
+    }
+}
+
+// ----- private implementation details ----------------------------------------
+
+fn kind_txt(kind: DefinitionKind) -> &'static str {
+    match kind {
+        DefinitionKind::Actor => "actor",
+        DefinitionKind::Weapon => "weapon",
+        DefinitionKind::Sequence => "image",
+    }
+}
+
+/// a finding at `span` described by `message`, followed by `opt_suggestion`
+/// (the name most likely meant instead of the text at `span`, if any), which
+/// is offered as a suggestion rather than a fix as it is only a guess
+pub(super) fn finding_with_suggestion(
+    span: AbsByteIdxSpan,
+    message: String,
+    opt_suggestion: Option<&str>,
+) -> Finding {
+    match opt_suggestion {
+        Some(suggestion) => Finding::new(span, format!("{} (did you mean `{}`?)", message, suggestion))
+            .with_fix(Fix::suggestion(
+                format!("replace with `{}`", suggestion),
+                TextEdit::new(span, suggestion),
+            )),
+        None => Finding::new(span, message),
+    }
+}
+
+/// the name of the definition most similar to `txt`, see [`closest_of`]
+///
+/// [`closest_of`]: fn.closest_of.html
+pub(super) fn closest_name<'files>(txt: &str, definitions: &[Definition<'files>]) -> Option<&'files str> {
+    closest_of(txt, definitions.iter().map(|definition| definition.name))
+}
+
+/// the name, of `names`, most similar to `txt`, if any is similar enough to be
+/// a likely typo (within an edit distance of a third of `txt`'s length, at
+/// least 1)
+pub(super) fn closest_of<'a>(txt: &str, names: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    let max_distance = (txt.chars().count() / 3).max(1);
+    let lowercase_txt = txt.to_lowercase();
+
+    names.into_iter()
+        .map(|name| (edit_distance(&lowercase_txt, &name.to_lowercase()), name))
+        .filter(|&(distance, _)| distance <= max_distance)
+        .min_by_key(|&(distance, _)| distance)
+        .map(|(_, name)| name)
+}
+
+/// the Levenshtein distance between `a` and `b`, in `char`s
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b_chars = b.chars().collect::<Vec<_>>();
+
+    // distances between the processed prefix of `a` and every prefix of `b`
+    let mut prev_row = (0..=b_chars.len()).collect::<Vec<_>>();
+
+    for (a_idx, a_ch) in a.chars().enumerate() {
+        let mut row = vec![a_idx + 1];
+
+        for (b_idx, &b_ch) in b_chars.iter().enumerate() {
+            let substitution_cost = if a_ch == b_ch { 0 } else { 1 };
+
+            row.push(
+                (prev_row[b_idx] + substitution_cost)
+                    .min(prev_row[b_idx + 1] + 1)
+                    .min(row[b_idx] + 1)
+            );
+        }
+
+        prev_row = row;
+    }
+
+    prev_row[b_chars.len()]
+}
+
+// ----- tests -----------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            lint::{
+                tests::{
+                    describe,
+                    source_file,
+                },
+                Linter,
+                ModFiles,
+            },
+            Schema,
+        },
+    };
+
+    #[test]
+    fn undefined_references_with_suggestions() {
+        // arrange
+        let mut files = ModFiles {
+            rules: vec![
+                source_file("rules/infantry.yaml", "^Soldier:\nE1:\n\tInherits: ^Soldeir\n\tArmament@primary:\n\t\tWeapon: m1carbine\n\tRenderSprites:\n\t\tImage: e1\nE2:\n\tInherits: ^Soldier\n\tArmament:\n\t\tWeapon: Grenade, Nope\n"),
+            ],
+            weapons: vec![
+                source_file("weapons/smallcaliber.yaml", "^LightMG:\nM1Carbine:\n\tInherits: ^LightMG\nGrenade:\n\tInherits: ^Grenade\n"),
+            ],
+            sequences: vec![
+                source_file("sequences/infantry.yaml", "e1:\n\tstand:\n"),
+            ],
+            ..ModFiles::default()
+        };
+
+        let mut linter = Linter::empty();
+        linter.add_lint(UndefinedReference::default());
+
+        // act
+        let diagnostics = linter.run(&files);
+        let fix_count = linter.fix(&mut files);
+
+        // assert
+        assert_eq!(
+            vec![
+                ("rules/infantry.yaml".to_owned(), "undefined-reference", "`^Soldeir` is not a defined actor (did you mean `^Soldier`?)".to_owned()),
+                ("rules/infantry.yaml".to_owned(), "undefined-reference", "`Nope` is not a defined weapon".to_owned()),
+                ("weapons/smallcaliber.yaml".to_owned(), "undefined-reference", "`^Grenade` is not a defined weapon".to_owned()),
+            ],
+            describe(diagnostics.clone()),
+        );
+
+        let suggestion = diagnostics[0].fix.as_ref().unwrap();
+        assert!(suggestion.is_suggestion);
+        assert_eq!("replace with `^Soldier`", suggestion.description);
+
+        // suggestions are only guesses, so are never applied
+        assert_eq!(0, fix_count);
+        assert!(files.rules[0].text.starts_with("^Soldier:\nE1:\n\tInherits: ^Soldeir\n"));
+    }
+
+    #[test]
+    fn reference_fields_of_the_schema() {
+        // arrange
+        let files = ModFiles {
+            rules: vec![
+                source_file("rules.yaml", "E1:\n\tInherits: ^Soldier\n\tArmament:\n\t\tWeapon: Nope\n\tSpawnActorOnDeath:\n\t\tActor: e1.husk\n"),
+            ],
+            schema: Some(Schema {
+                references: Some(vec![ReferenceField { key: "Actor".to_owned(), kind: Some(DefinitionKind::Actor) }]),
+                ..Schema::default()
+            }),
+            ..ModFiles::default()
+        };
+
+        let mut linter = Linter::empty();
+        linter.add_lint(UndefinedReference::default());
+
+        // act
+        let actual = describe(linter.run(&files));
+
+        // assert
+        assert_eq!(
+            vec![("rules.yaml".to_owned(), "undefined-reference", "`e1.husk` is not a defined actor".to_owned())],
+            actual,
+        );
+    }
+
+    #[test]
+    fn edit_distances() {
+        assert_eq!(0, edit_distance("", ""));
+        assert_eq!(3, edit_distance("", "abc"));
+        assert_eq!(2, edit_distance("soldier", "soldeir"));
+        assert_eq!(3, edit_distance("kitten", "sitting"));
+    }
+}
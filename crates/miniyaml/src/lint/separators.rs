@@ -0,0 +1,129 @@
+// oraide - tools for OpenRA-based mod/game development
+// get the source code at https://github.com/Phrohdoh/oraide
+//
+// copyright (c)
+// - 2020 Taryn "Phrohdoh" Hill
+
+//! the lint reporting keys which aren't followed by a `:`
+
+use {
+    crate::{
+        lint::{
+            Finding,
+            Fix,
+            Lint,
+            LintContext,
+            LintFile,
+            Severity,
+        },
+        TextEdit,
+    },
+};
+
+// ----- public interface ------------------------------------------------------
+
+/// Reports each line which has a key but no `:` separator
+/// (`missing-separator`), fixed by inserting one after the key.
+///
+/// If the key contains whitespace (e.g. `Inherits ^Soldier`), replacing the
+/// first run of whitespace with `: ` is offered as a suggestion instead, as
+/// the key was most likely (but not certainly) meant to end there.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct MissingSeparator;
+
+// ----- external trait impls --------------------------------------------------
+
+impl Lint for MissingSeparator {
+    fn code(&self) -> &'static str {
+        "missing-separator"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, _cx: &LintContext, file: &LintFile) -> Vec<Finding> {
+        let doc = file.text;
+
+        file.lines.iter()
+            .filter(|line| line.key_sep.is_none())
+            .filter_map(|line| line.key)
+            .map(|key_span| {
+                let (key_start, _) = key_span.into();
+                let key_txt = doc[key_span].trim_end();
+                let finding = Finding::new(key_span, format!("`{}` has no `:` separator", key_txt));
+
+                let fix = match key_txt.find(char::is_whitespace) {
+                    Some(whitespace_idx) => {
+                        let whitespace_len = key_txt[whitespace_idx..].len()
+                            - key_txt[whitespace_idx..].trim_start().len();
+
+                        let whitespace_start = key_start + whitespace_idx;
+                        Fix::suggestion(
+                            format!("end the key at `{}`", &key_txt[..whitespace_idx]),
+                            TextEdit::new((whitespace_start, whitespace_start + whitespace_len), ": "),
+                        )
+                    },
+                    None => {
+                        let key_end = key_start + key_txt.len();
+                        Fix::new("insert `:`", TextEdit::new((key_end, key_end), ":"))
+                    },
+                };
+
+                finding.with_fix(fix)
+            })
+            .collect()
+    }
+}
+
+// ----- tests -----------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::lint::{
+            tests::{
+                describe,
+                source_file,
+            },
+            Linter,
+            ModFiles,
+        },
+    };
+
+    #[test]
+    fn missing_separators_and_their_fixes() {
+        // arrange
+        let mut files = ModFiles {
+            rules: vec![
+                source_file("rules.yaml", "E1:\n\tInherits  ^Soldier\n\tHealth # no fields\n\tMobile:\n\t\tSpeed: 56\n"),
+            ],
+            ..ModFiles::default()
+        };
+
+        let mut linter = Linter::empty();
+        linter.add_lint(MissingSeparator);
+
+        // act
+        let diagnostics = linter.run(&files);
+        let fix_count = linter.fix(&mut files);
+
+        // assert
+        assert_eq!(
+            vec![
+                ("rules.yaml".to_owned(), "missing-separator", "`Inherits  ^Soldier` has no `:` separator".to_owned()),
+                ("rules.yaml".to_owned(), "missing-separator", "`Health` has no `:` separator".to_owned()),
+            ],
+            describe(diagnostics.clone()),
+        );
+
+        let suggestion = diagnostics[0].fix.as_ref().unwrap();
+        assert!(suggestion.is_suggestion);
+        assert_eq!("end the key at `Inherits`", suggestion.description);
+
+        // only the certain fix is applied
+        assert_eq!(1, fix_count);
+        assert_eq!("E1:\n\tInherits  ^Soldier\n\tHealth: # no fields\n\tMobile:\n\t\tSpeed: 56\n", files.rules[0].text);
+    }
+}
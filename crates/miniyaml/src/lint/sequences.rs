@@ -0,0 +1,301 @@
+// oraide - tools for OpenRA-based mod/game development
+// get the source code at https://github.com/Phrohdoh/oraide
+//
+// copyright (c)
+// - 2020 Taryn "Phrohdoh" Hill
+
+//! the lint reporting sequences which actors play but their images don't
+//! define
+
+use {
+    std::collections::HashSet,
+    crate::{
+        lint::{
+            references::{
+                closest_of,
+                finding_with_suggestion,
+            },
+            DefinitionKind,
+            FileKind,
+            Finding,
+            Lint,
+            LintContext,
+            LintFile,
+            Severity,
+        },
+        list_items_of,
+        parse_key,
+        AbsByteIdxSpan,
+        Image,
+        ResolveError,
+        RuleNode,
+    },
+};
+
+// ----- public interface ------------------------------------------------------
+
+/// A field of a trait whose value (or each item of whose comma-separated
+/// value) names a sequence of the actor's image.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct SequenceField {
+    /// the trait's name, sans `@` suffix
+    pub trait_name: &'static str,
+
+    /// the field's key
+    pub key: &'static str,
+
+    /// the sequence the trait plays if the field isn't given, if any
+    pub default: Option<&'static str>,
+}
+
+/// The fields which [`UndefinedSequence`] checks.
+///
+/// [`UndefinedSequence`]: struct.UndefinedSequence.html
+pub const SEQUENCE_FIELDS: &[SequenceField] = &[
+    SequenceField { trait_name: "WithSpriteBody", key: "Sequence", default: Some("idle") },
+    SequenceField { trait_name: "WithFacingSpriteBody", key: "Sequence", default: Some("idle") },
+    SequenceField { trait_name: "WithInfantryBody", key: "MoveSequence", default: Some("run") },
+    SequenceField { trait_name: "WithInfantryBody", key: "StandSequences", default: Some("stand") },
+    SequenceField { trait_name: "WithInfantryBody", key: "DefaultAttackSequence", default: None },
+    SequenceField { trait_name: "WithInfantryBody", key: "IdleSequences", default: None },
+    SequenceField { trait_name: "WithMakeAnimation", key: "Sequence", default: Some("make") },
+    SequenceField { trait_name: "WithIdleOverlay", key: "Sequence", default: Some("idle") },
+    SequenceField { trait_name: "WithSpriteTurret", key: "Sequence", default: Some("turret") },
+    SequenceField { trait_name: "WithMoveAnimation", key: "MoveSequence", default: Some("move") },
+    SequenceField { trait_name: "WithAttackAnimation", key: "AttackSequence", default: None },
+    SequenceField { trait_name: "WithAttackAnimation", key: "AimSequence", default: None },
+];
+
+/// Reports, for each actor with `RenderSprites`, sequences which its traits
+/// play (see [`SEQUENCE_FIELDS`]) but which its image doesn't define, and
+/// images which no sequences file defines (`undefined-sequence`), either of
+/// which crashes the game once the actor is created.
+///
+/// The image is the trait's `Image`, if it has one, else that of
+/// `RenderSprites`, else the actor's name in lowercase.  Sequences which an
+/// actor inherits are reported at its key.
+///
+/// [`SEQUENCE_FIELDS`]: constant.SEQUENCE_FIELDS.html
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct UndefinedSequence;
+
+// ----- external trait impls --------------------------------------------------
+
+impl Lint for UndefinedSequence {
+    fn code(&self) -> &'static str {
+        "undefined-sequence"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, cx: &LintContext, file: &LintFile) -> Vec<Finding> {
+        let has_sequences = cx.files().iter().any(|other| other.kind == FileKind::Sequences);
+        if file.kind != FileKind::Rules || !has_sequences {
+            return vec![];
+        }
+
+        let doc = file.text;
+        let mut findings = vec![];
+
+        for node in &file.tree.nodes {
+            let name = doc[node.key].trim_end();
+
+            if name.starts_with('^') || !cx.is_first_definition(DefinitionKind::Actor, file, node) {
+                continue;
+            }
+
+            let actor = match cx.rules().resolve(name) {
+                Ok(actor) => actor,
+                Err(_) => continue,
+            };
+
+            let render_sprites = match actor.children.iter().find(|child| is_trait(child, "RenderSprites")) {
+                Some(render_sprites) => render_sprites,
+                None => continue,
+            };
+
+            let actor_image_name = value_of(render_sprites, "Image")
+                .unwrap_or(name)
+                .to_lowercase();
+
+            let mut reported_image_names = HashSet::new();
+
+            for (trait_node, field) in sequence_fields_of(&actor) {
+                let image_name = value_of(trait_node, "Image")
+                    .map(str::to_lowercase)
+                    .unwrap_or_else(|| actor_image_name.clone());
+
+                let image = match Image::resolve(cx.sequences(), &image_name) {
+                    Ok(image) => image,
+                    Err(ResolveError::NotDefined { .. }) => {
+                        if reported_image_names.insert(image_name.clone()) {
+                            findings.push(Finding::new(
+                                node.key,
+                                format!("`{}` uses image `{}`, which no sequences file defines", name, image_name),
+                            ));
+                        }
+
+                        continue;
+                    },
+                    Err(_) => continue,
+                };
+
+                for (sequence_name, opt_span) in sequence_names_of(file, trait_node, field) {
+                    if image.sequence(&sequence_name).is_some() {
+                        continue;
+                    }
+
+                    // a default sequence name (which isn't written) can't be
+                    // replaced
+                    let opt_suggestion = opt_span
+                        .and_then(|_| closest_of(&sequence_name, image.sequences.keys().map(String::as_str)));
+
+                    findings.push(finding_with_suggestion(
+                        opt_span.unwrap_or(node.key),
+                        format!(
+                            "`{}` is not a sequence of image `{}` (`{}` of `{}`'s `{}`)",
+                            sequence_name,
+                            image_name,
+                            field.key,
+                            name,
+                            trait_node.key,
+                        ),
+                        opt_suggestion,
+                    ));
+                }
+            }
+        }
+
+        findings
+    }
+}
+
+// ----- private implementation details ----------------------------------------
+
+/// `true` if `node` is an instance of the trait `trait_name`
+fn is_trait(node: &RuleNode, trait_name: &str) -> bool {
+    match node.key.strip_prefix(trait_name) {
+        Some(suffix) => suffix.is_empty() || suffix.starts_with('@'),
+        None => false,
+    }
+}
+
+/// the value of `node`'s child `key`, if it has one
+fn value_of<'node>(node: &'node RuleNode, key: &str) -> Option<&'node str> {
+    node.children.iter()
+        .find(|child| child.key == key)
+        .and_then(|child| child.value.as_deref())
+}
+
+/// every trait of `actor` with a sequence field, along with said field
+fn sequence_fields_of(actor: &RuleNode) -> impl Iterator<Item = (&RuleNode, &'static SequenceField)> {
+    actor.children.iter()
+        .flat_map(|trait_node| SEQUENCE_FIELDS.iter()
+            .filter(move |field| is_trait(trait_node, field.trait_name))
+            .map(move |field| (trait_node, field)))
+}
+
+/// the sequences `trait_node` plays through `field`, each with its span in
+/// `file`, if it is written there (rather than inherited or defaulted)
+fn sequence_names_of(
+    file: &LintFile,
+    trait_node: &RuleNode,
+    field: &SequenceField,
+) -> Vec<(String, Option<AbsByteIdxSpan>)> {
+    let doc = file.text;
+
+    let field_node = match trait_node.children.iter().find(|child| child.key == field.key) {
+        Some(field_node) => field_node,
+        None => return field.default
+            .map(|default| vec![(default.to_owned(), None)])
+            .unwrap_or_default(),
+    };
+
+    let opt_line = file.lines.get(field_node.origin.line_idx)
+        .filter(|_| field_node.origin.path == file.path);
+
+    let opt_value_span = opt_line
+        .filter(|line| line.key.is_some_and(|key_span| doc[parse_key(doc, key_span).name] == *field.key))
+        .and_then(|line| line.value);
+
+    match opt_value_span {
+        Some(value_span) => list_items_of(doc, value_span).into_iter()
+            .map(|item_span| (doc[item_span].to_owned(), Some(item_span)))
+            .collect(),
+        None => field_node.value.as_deref().unwrap_or("")
+            .split(',')
+            .map(str::trim)
+            .filter(|item_txt| !item_txt.is_empty())
+            .map(|item_txt| (item_txt.to_owned(), None))
+            .collect(),
+    }
+}
+
+// ----- tests -----------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::lint::{
+            tests::{
+                describe,
+                source_file,
+                suggestions_of,
+            },
+            Linter,
+            ModFiles,
+        },
+    };
+
+    #[test]
+    fn undefined_sequences() {
+        // arrange
+        let mut files = ModFiles {
+            rules: vec![source_file("rules.yaml", [
+                "^Infantry:\n",
+                "\tRenderSprites:\n",
+                "\tWithInfantryBody:\n",
+                "\t\tMoveSequence: walk\n",
+                "E1:\n",
+                "\tInherits: ^Infantry\n",
+                "E2:\n",
+                "\tInherits: ^Infantry\n",
+                "\tRenderSprites:\n",
+                "\t\tImage: e1\n",
+                "\tWithInfantryBody:\n",
+                "\t\tMoveSequence: run\n",
+                "\t\tStandSequences: stand, stnd\n",
+                "E3:\n",
+                "\tInherits: ^Infantry\n",
+                "World:\n",
+                "\tWithSpriteBody:\n",
+            ].concat().as_str())],
+            sequences: vec![source_file("sequences.yaml", "e1:\n\tstand:\n\trun:\n")],
+            ..ModFiles::default()
+        };
+
+        let mut linter = Linter::empty();
+        linter.add_lint(UndefinedSequence);
+
+        // act
+        let diagnostics = linter.run(&files);
+        let fix_count = linter.fix(&mut files);
+
+        // assert
+        let finding = |message: &str| ("rules.yaml".to_owned(), "undefined-sequence", message.to_owned());
+        assert_eq!(
+            vec![
+                finding("`walk` is not a sequence of image `e1` (`MoveSequence` of `E1`'s `WithInfantryBody`)"),
+                finding("`stnd` is not a sequence of image `e1` (`StandSequences` of `E2`'s `WithInfantryBody`) (did you mean `stand`?)"),
+                finding("`E3` uses image `e3`, which no sequences file defines"),
+            ],
+            describe(diagnostics.clone()),
+        );
+
+        assert_eq!(vec!["stand"], suggestions_of(&diagnostics));
+        assert_eq!(0, fix_count);
+    }
+}
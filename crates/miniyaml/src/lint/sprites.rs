@@ -0,0 +1,252 @@
+// oraide - tools for OpenRA-based mod/game development
+// get the source code at https://github.com/Phrohdoh/oraide
+//
+// copyright (c)
+// - 2020 Taryn "Phrohdoh" Hill
+
+//! the lint reporting sequences whose sprites don't exist or are too short
+
+use {
+    crate::{
+        lint::{
+            DefinitionKind,
+            FileKind,
+            Finding,
+            Lint,
+            LintContext,
+            LintFile,
+            Severity,
+        },
+        AbsByteIdxSpan,
+        Image,
+        Sequence,
+    },
+};
+
+// ----- public interface ------------------------------------------------------
+
+/// Reports sequences whose file isn't in any package the mod mounts, or has
+/// fewer frames than the sequence needs (see [`frame_count_needed`])
+/// (`sequence-sprite`).
+///
+/// Missing files are only reported if every package which the mod requires
+/// could be read (see [`Assets::is_complete`]), and sequences whose file
+/// depends on the map's tileset aren't checked.
+///
+/// [`frame_count_needed`]: ../struct.Sequence.html#method.frame_count_needed
+/// [`Assets::is_complete`]: ../struct.Assets.html#method.is_complete
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct SequenceSprites;
+
+// ----- external trait impls --------------------------------------------------
+
+impl Lint for SequenceSprites {
+    fn code(&self) -> &'static str {
+        "sequence-sprite"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, cx: &LintContext, file: &LintFile) -> Vec<Finding> {
+        if file.kind != FileKind::Sequences {
+            return vec![];
+        }
+
+        let doc = file.text;
+        let mut findings = vec![];
+
+        for node in &file.tree.nodes {
+            let name = doc[node.key].trim_end();
+
+            if name.starts_with('^') || !cx.is_first_definition(DefinitionKind::Sequence, file, node) {
+                continue;
+            }
+
+            let image = match Image::resolve(cx.sequences(), name) {
+                Ok(image) => image,
+                Err(_) => continue,
+            };
+
+            for sequence in image.sequences.values() {
+                let span = key_span_of(file, sequence).unwrap_or(node.key);
+
+                let sprites = if sequence.combine.is_empty() {
+                    vec![sequence]
+                } else {
+                    sequence.combine.iter().collect()
+                };
+
+                for sprite in sprites.into_iter().filter(|sprite| !sprite.is_tileset_specific) {
+                    match cx.assets().sprite(&sprite.filename) {
+                        Some((sprite_name, asset)) => {
+                            let frame_count_needed = sprite.frame_count_needed();
+                            let opt_frame_count = asset.frame_count
+                                .filter(|&frame_count| (frame_count as i64) < frame_count_needed);
+
+                            if let Some(frame_count) = opt_frame_count {
+                                findings.push(Finding::new(
+                                    span,
+                                    format!(
+                                        "sequence `{}` of `{}` needs {} frame(s), but `{}` has {}",
+                                        sequence.name, name, frame_count_needed, sprite_name, frame_count,
+                                    ),
+                                ));
+                            }
+                        },
+                        None if cx.assets().is_complete() => {
+                            let packages = if cx.assets().unread_packages.is_empty() {
+                                "any package"
+                            } else {
+                                "any package which could be read"
+                            };
+
+                            findings.push(Finding::new(
+                                span,
+                                format!(
+                                    "`{}`, the file of sequence `{}` of `{}`, is not in {}",
+                                    sprite.filename, sequence.name, name, packages,
+                                ),
+                            ));
+                        },
+                        None => {},
+                    }
+                }
+            }
+        }
+
+        findings
+    }
+}
+
+// ----- private implementation details ----------------------------------------
+
+/// the span of the key of `sequence`, if it is defined in `file`
+fn key_span_of(file: &LintFile, sequence: &Sequence) -> Option<AbsByteIdxSpan> {
+    if sequence.origin.path != file.path {
+        return None;
+    }
+
+    let key_span = file.lines.get(sequence.origin.line_idx)?.key?;
+    let (start, _) = key_span.into();
+
+    Some((start, start + file.text[key_span].trim_end().len()).into())
+}
+
+// ----- tests -----------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            lint::{
+                tests::{
+                    describe,
+                    source_file,
+                },
+                Linter,
+                ModFiles,
+            },
+            Asset,
+            Assets,
+            UnreadPackage,
+        },
+    };
+
+    #[test]
+    fn missing_and_short_sprites() {
+        // arrange
+        let mut assets = Assets::default();
+        for (name, frame_count) in &[("e1.shp", Some(64)), ("deaths.png", Some(4)), ("icons.shp", None)] {
+            assets.insert(*name, Asset { package: "bits".into(), frame_count: *frame_count });
+        }
+
+        let files = ModFiles {
+            sequences: vec![source_file("sequences.yaml", [
+                "e1:\n",
+                "\tDefaults:\n",
+                "\t\tFacings: 8\n",
+                "\tstand:\n",
+                "\trun:\n",
+                "\t\tStart: 16\n",
+                "\t\tLength: 8\n",
+                "\tdie1: deaths.png\n",
+                "\t\tLength: *\n",
+                "\t\tFacings: 1\n",
+                "\tdie2: deaths.png\n",
+                "\t\tStart: 3\n",
+                "\t\tLength: 2\n",
+                "\t\tFacings: 1\n",
+                "\ticon:\n",
+                "\t\tCombine:\n",
+                "\t\t\ticons:\n",
+                "\t\t\t\tLength: 90\n",
+                "\t\t\tchevron:\n",
+                "\tidle: e1-snow\n",
+                "\t\tUseTilesetExtension: true\n",
+            ].concat().as_str())],
+            assets,
+            ..ModFiles::default()
+        };
+
+        let mut linter = Linter::empty();
+        linter.add_lint(SequenceSprites);
+
+        // act
+        let actual = describe(linter.run(&files));
+
+        // assert
+        let finding = |message: &str| ("sequences.yaml".to_owned(), "sequence-sprite", message.to_owned());
+        assert_eq!(
+            vec![
+                finding("sequence `run` of `e1` needs 80 frame(s), but `e1.shp` has 64"),
+                finding("sequence `die2` of `e1` needs 5 frame(s), but `deaths.png` has 4"),
+                finding("`chevron`, the file of sequence `icon` of `e1`, is not in any package"),
+            ],
+            actual,
+        );
+    }
+
+    #[test]
+    fn unread_packages() {
+        // arrange
+        let sequences = || vec![source_file("sequences.yaml", "e1:\n\tstand:\n")];
+        let assets = |is_optional| {
+            let mut assets = Assets::default();
+            assets.unread_packages.push(UnreadPackage { entry: "conquer.mix".to_owned(), is_optional });
+            assets
+        };
+
+        let optional_files = ModFiles {
+            sequences: sequences(),
+            assets: assets(true),
+            ..ModFiles::default()
+        };
+
+        let required_files = ModFiles {
+            sequences: sequences(),
+            assets: assets(false),
+            ..ModFiles::default()
+        };
+
+        let mut linter = Linter::empty();
+        linter.add_lint(SequenceSprites);
+
+        // act
+        let optional_actual = describe(linter.run(&optional_files));
+        let required_actual = describe(linter.run(&required_files));
+
+        // assert
+        assert_eq!(
+            vec![(
+                "sequences.yaml".to_owned(),
+                "sequence-sprite",
+                "`e1`, the file of sequence `stand` of `e1`, is not in any package which could be read".to_owned(),
+            )],
+            optional_actual,
+        );
+        assert!(required_actual.is_empty());
+    }
+}
@@ -0,0 +1,154 @@
+// oraide - tools for OpenRA-based mod/game development
+// get the source code at https://github.com/Phrohdoh/oraide
+//
+// copyright (c)
+// - 2020 Taryn "Phrohdoh" Hill
+
+//! suppression directives in comments, which hide the findings of lints
+//!
+//! - `# oraide-ignore: <code>, ...` hides findings on the same line
+//! - `# oraide-ignore-file: <code>, ...`, in the comments preceding the first
+//!   node of a file, hides findings anywhere in the file (and, anywhere else,
+//!   hides nothing)
+
+use crate::{
+    lint::LintFile,
+    AbsByteIdxSpan,
+    TextEdit,
+};
+
+const LINE_DIRECTIVE: &str = "oraide-ignore:";
+const FILE_DIRECTIVE: &str = "oraide-ignore-file:";
+
+/// a single lint code named by a suppression directive
+pub(super) struct Suppression {
+    /// the lint code
+    pub(super) code: String,
+
+    /// span of the lint code in the directive
+    pub(super) span: AbsByteIdxSpan,
+
+    /// index of the line whose findings are hidden, or `None` if those of the
+    /// whole file are
+    pub(super) line_idx: Option<usize>,
+
+    /// `true` once the suppression has hidden any finding
+    pub(super) is_used: bool,
+
+    /// `true` if the suppression is of a file directive after the file's
+    /// first node, which hides nothing
+    pub(super) is_misplaced: bool,
+
+    /// the edit removing the directive, if it names only this code
+    pub(super) removal: Option<TextEdit>,
+}
+
+impl Suppression {
+    /// `true` if this hides a finding of the lint `code` on line `line_idx`
+    pub(super) fn hides(&self, code: &str, line_idx: usize) -> bool {
+        let is_on_line = match self.line_idx {
+            Some(idx) => idx == line_idx,
+            None => true,
+        };
+
+        !self.is_misplaced && self.code == code && is_on_line
+    }
+}
+
+/// every suppression of `file`, in document order
+pub(super) fn suppressions_of(file: &LintFile) -> Vec<Suppression> {
+    let mut suppressions = vec![];
+    let mut is_before_first_node = true;
+
+    for (line_idx, line) in file.lines.iter().enumerate() {
+        if line.key.is_some() {
+            is_before_first_node = false;
+        }
+
+        let comment_span = match line.comment {
+            Some(comment_span) => comment_span,
+            None => continue,
+        };
+
+        let (comment_start, _) = comment_span.into();
+        let comment_txt = &file.text[comment_span];
+
+        // skip the comment character and any whitespace following it
+        let directive_rbx = comment_txt.char_indices()
+            .skip(1)
+            .find(|(_, ch)| !ch.is_whitespace())
+            .map(|(rbx, _)| rbx)
+            .unwrap_or(comment_txt.len());
+        let directive_txt = &comment_txt[directive_rbx..];
+
+        // file directives elsewhere would be easy to miss, so are misplaced
+        let (codes_rbx, opt_line_idx, is_misplaced) = if directive_txt.starts_with(FILE_DIRECTIVE) {
+            (directive_rbx + FILE_DIRECTIVE.len(), None, !is_before_first_node)
+        } else if directive_txt.starts_with(LINE_DIRECTIVE) {
+            (directive_rbx + LINE_DIRECTIVE.len(), Some(line_idx), false)
+        } else {
+            continue;
+        };
+
+        let code_count = comment_txt[codes_rbx..].split(',')
+            .filter(|code_txt| !code_txt.trim().is_empty())
+            .count();
+
+        let removal = if code_count == 1 {
+            Some(TextEdit::new(removed_span_of(file, line_idx), ""))
+        } else {
+            None
+        };
+
+        let mut code_rbx = codes_rbx;
+        for code_txt in comment_txt[codes_rbx..].split(',') {
+            let leading_ws_len = code_txt.len() - code_txt.trim_start().len();
+            let code = code_txt.trim();
+
+            if !code.is_empty() {
+                let start = comment_start + code_rbx + leading_ws_len;
+
+                suppressions.push(Suppression {
+                    code: code.to_owned(),
+                    span: (start, start + code.len()).into(),
+                    line_idx: opt_line_idx,
+                    is_used: false,
+                    is_misplaced,
+                    removal: removal.clone(),
+                });
+            }
+
+            code_rbx += code_txt.len() + ','.len_utf8();
+        }
+    }
+
+    suppressions
+}
+
+/// index of the line of `file` containing the absolute byte index `abs_idx`
+pub(super) fn line_idx_of(file: &LintFile, abs_idx: usize) -> usize {
+    file.lines
+        .partition_point(|line| {
+            let (raw_start, _) = line.raw.into();
+            raw_start <= abs_idx
+        })
+        .saturating_sub(1)
+}
+
+/// span of the text to remove to remove the comment of line `line_idx` of
+/// `file`, which is the entire line if it is nothing but the comment
+fn removed_span_of(file: &LintFile, line_idx: usize) -> AbsByteIdxSpan {
+    let line = &file.lines[line_idx];
+    if line.is_comment_only() {
+        return line.raw;
+    }
+
+    let (raw_start, _) = line.raw.into();
+    let (comment_start, comment_end) = line.comment
+        .expect("only lines with comments have directives")
+        .into();
+
+    // also remove the whitespace separating the comment from what precedes it
+    let start = raw_start + file.text[raw_start..comment_start].trim_end().len();
+    (start, comment_end).into()
+}
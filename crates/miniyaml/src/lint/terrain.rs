@@ -0,0 +1,193 @@
+// oraide - tools for OpenRA-based mod/game development
+// get the source code at https://github.com/Phrohdoh/oraide
+//
+// copyright (c)
+// - 2020 Taryn "Phrohdoh" Hill
+
+//! the lint reporting terrain types which no tileset defines
+
+use {
+    crate::{
+        lint::{
+            references::{
+                closest_of,
+                finding_with_suggestion,
+            },
+            FileKind,
+            Finding,
+            Lint,
+            LintContext,
+            LintFile,
+            Severity,
+        },
+        list_items_of,
+        parse_key,
+        AbsByteIdxSpan,
+        Tileset,
+    },
+};
+
+// ----- public interface ------------------------------------------------------
+
+/// Reports terrain types which aren't defined by any tileset of the mod
+/// (`unknown-terrain-type`), whether used by the tiles of a tileset, as a key
+/// of a `TerrainSpeeds` (e.g. of a `Locomotor` or `Mobile`), or in the value
+/// of a field listing terrain types (see [`TERRAIN_TYPE_FIELDS`]).
+///
+/// The tiles of a tileset must use its own terrain types, which the engine
+/// refuses to load otherwise, while rules may use those of any tileset (as
+/// they apply to every map, whatever its tileset).
+///
+/// [`TERRAIN_TYPE_FIELDS`]: constant.TERRAIN_TYPE_FIELDS.html
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct UnknownTerrainType;
+
+/// Fields of traits whose value is a list of terrain types.
+pub const TERRAIN_TYPE_FIELDS: &[&str] = &[
+    "TerrainTypes",
+    "AllowedTerrainTypes",
+    "LandableTerrainTypes",
+];
+
+// ----- external trait impls --------------------------------------------------
+
+impl Lint for UnknownTerrainType {
+    fn code(&self) -> &'static str {
+        "unknown-terrain-type"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, cx: &LintContext, file: &LintFile) -> Vec<Finding> {
+        let doc = file.text;
+
+        match file.kind {
+            FileKind::Tileset => {
+                let tileset = Tileset::of_tree(doc, &file.tree);
+
+                let tile_spans = file.tree.iter()
+                    .filter(|node| doc[node.key].trim_end() == "Tiles")
+                    .flat_map(|tiles| tiles.children.iter())
+                    .filter_map(|tile| tile.value)
+                    .map(|value_span| trimmed(doc, value_span));
+
+                tile_spans
+                    .filter(|&span| !tileset.is_terrain_type(&doc[span]))
+                    .map(|span| finding_of(doc, span, &tileset.terrain_types, "this tileset"))
+                    .collect()
+            },
+            FileKind::Rules | FileKind::Map | FileKind::MapOverride => {
+                if cx.tilesets().is_empty() {
+                    return vec![];
+                }
+
+                let terrain_types = cx.tilesets().iter()
+                    .flat_map(|tileset| tileset.terrain_types.iter().cloned())
+                    .collect::<Vec<_>>();
+
+                let mut terrain_type_spans = vec![];
+                for node in file.tree.iter() {
+                    let key_txt = doc[node.key].trim_end();
+
+                    if key_txt == "TerrainSpeeds" {
+                        terrain_type_spans.extend(node.children.iter()
+                            .filter(|child| !parse_key(doc, child.key).is_removal())
+                            .map(|child| trimmed(doc, child.key)));
+                    } else if TERRAIN_TYPE_FIELDS.contains(&key_txt) {
+                        terrain_type_spans.extend(node.value
+                            .map(|value_span| list_items_of(doc, value_span))
+                            .unwrap_or_default());
+                    }
+                }
+
+                terrain_type_spans.into_iter()
+                    .filter(|&span| !terrain_types.iter().any(|terrain_type| terrain_type == &doc[span]))
+                    .map(|span| finding_of(doc, span, &terrain_types, "any tileset"))
+                    .collect()
+            },
+            FileKind::Weapons | FileKind::Sequences => vec![],
+        }
+    }
+}
+
+// ----- private implementation details ----------------------------------------
+
+/// `span` without trailing whitespace
+fn trimmed(doc: &str, span: AbsByteIdxSpan) -> AbsByteIdxSpan {
+    let (start, _) = span.into();
+    (start, start + doc[span].trim_end().len()).into()
+}
+
+/// a finding for the unknown terrain type at `span`, suggesting the most
+/// similar of `terrain_types` (which are those of `definer`)
+fn finding_of(doc: &str, span: AbsByteIdxSpan, terrain_types: &[String], definer: &str) -> Finding {
+    let txt = &doc[span];
+
+    finding_with_suggestion(
+        span,
+        format!("`{}` is not a terrain type of {}", txt, definer),
+        closest_of(txt, terrain_types.iter().map(String::as_str)),
+    )
+}
+
+// ----- tests -----------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::lint::{
+            tests::{
+                describe,
+                source_file,
+                suggestions_of,
+            },
+            Linter,
+            ModFiles,
+        },
+    };
+
+    #[test]
+    fn unknown_terrain_types() {
+        // arrange
+        let mut files = ModFiles {
+            rules: vec![source_file("rules.yaml", [
+                "World:\n",
+                "\tLocomotor@FOOT:\n",
+                "\t\tTerrainSpeeds:\n",
+                "\t\t\tClear: 90\n",
+                "\t\t\tRogh: 80\n",
+                "\t\t\t-Lava:\n",
+                "\tResourceType@ore:\n",
+                "\t\tAllowedTerrainTypes: Clear, Road, Tiberium\n",
+            ].concat().as_str())],
+            tilesets: vec![
+                source_file("tilesets/temperat.yaml", "Terrain:\n\tTerrainType@Clear:\n\t\tType: Clear\n\tTerrainType@Rough:\n\t\tType: Rough\nTemplates:\n\tTemplate@1:\n\t\tId: 1\n\t\tTiles:\n\t\t\t0: Clear\n\t\t\t1: Road\n"),
+                source_file("tilesets/snow.yaml", "Terrain:\n\tTerrainType@Road:\n\t\tType: Road\n"),
+            ],
+            ..ModFiles::default()
+        };
+
+        let mut linter = Linter::empty();
+        linter.add_lint(UnknownTerrainType);
+
+        // act
+        let diagnostics = linter.run(&files);
+        let fix_count = linter.fix(&mut files);
+
+        // assert
+        assert_eq!(
+            vec![
+                ("rules.yaml".to_owned(), "unknown-terrain-type", "`Rogh` is not a terrain type of any tileset (did you mean `Rough`?)".to_owned()),
+                ("rules.yaml".to_owned(), "unknown-terrain-type", "`Tiberium` is not a terrain type of any tileset".to_owned()),
+                ("tilesets/temperat.yaml".to_owned(), "unknown-terrain-type", "`Road` is not a terrain type of this tileset".to_owned()),
+            ],
+            describe(diagnostics.clone()),
+        );
+
+        assert_eq!(vec!["Rough"], suggestions_of(&diagnostics));
+        assert_eq!(0, fix_count);
+    }
+}
@@ -0,0 +1,161 @@
+// oraide - tools for OpenRA-based mod/game development
+// get the source code at https://github.com/Phrohdoh/oraide
+//
+// copyright (c)
+// - 2020 Taryn "Phrohdoh" Hill
+
+//! the lint reporting traits which the engine doesn't define
+
+use {
+    crate::{
+        lint::{
+            references::{
+                closest_of,
+                finding_with_suggestion,
+            },
+            FileKind,
+            Finding,
+            Lint,
+            LintContext,
+            LintFile,
+            Severity,
+        },
+        parse_key,
+    },
+};
+
+// ----- public interface ------------------------------------------------------
+
+/// Reports each trait of an actor, in any rules file, which the mod's
+/// [`Schema`] doesn't describe (`unknown-trait`), suggesting the most similar
+/// trait which it does.
+///
+/// Mods without a schema (or whose schema doesn't describe traits) aren't
+/// checked.
+///
+/// [`Schema`]: ../struct.Schema.html
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct UnknownTrait;
+
+// ----- external trait impls --------------------------------------------------
+
+impl Lint for UnknownTrait {
+    fn code(&self) -> &'static str {
+        "unknown-trait"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, cx: &LintContext, file: &LintFile) -> Vec<Finding> {
+        let traits = match cx.schema().and_then(|schema| schema.traits.as_ref()) {
+            Some(traits) if file.kind == FileKind::Rules => traits,
+            _ => return vec![],
+        };
+
+        let doc = file.text;
+
+        file.tree.nodes.iter()
+            .filter(|actor| !parse_key(doc, actor.key).is_removal())
+            .flat_map(|actor| actor.children.iter())
+            .map(|trait_node| parse_key(doc, trait_node.key).name)
+            .filter(|&name_span| {
+                let name = &doc[name_span];
+                name != "Inherits" && !traits.contains_key(name)
+            })
+            .map(|name_span| {
+                let name = &doc[name_span];
+
+                finding_with_suggestion(
+                    name_span,
+                    format!("`{}` is not a trait", name),
+                    closest_of(name, traits.keys().map(String::as_str)),
+                )
+            })
+            .collect()
+    }
+}
+
+// ----- tests -----------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            lint::{
+                tests::{
+                    describe,
+                    source_file,
+                    suggestions_of,
+                },
+                Linter,
+                ModFiles,
+            },
+            Schema,
+        },
+    };
+
+    #[test]
+    fn unknown_traits() {
+        // arrange
+        let traits = ["Armament", "Buildable", "Health"].iter()
+            .map(|&name| (name.to_owned(), vec![]))
+            .collect();
+
+        let files = ModFiles {
+            rules: vec![source_file("rules.yaml", &[
+                "E1:\n",
+                "\tInherits@1: ^Soldier\n",
+                "\tArmamant@PRIMARY:\n",
+                "\t\tWeapon: M1Carbine\n",
+                "\t-Buildable:\n",
+                "\tHealth:\n",
+                "\tWobble:\n",
+                "-E2:\n",
+                "\tNope:\n",
+            ].concat())],
+            schema: Some(Schema {
+                traits: Some(traits),
+                ..Schema::default()
+            }),
+            ..ModFiles::default()
+        };
+
+        let mut linter = Linter::empty();
+        linter.add_lint(UnknownTrait);
+
+        // act
+        let diagnostics = linter.run(&files);
+
+        // assert
+        assert_eq!(
+            vec![
+                ("rules.yaml".to_owned(), "unknown-trait", "`Armamant` is not a trait (did you mean `Armament`?)".to_owned()),
+                ("rules.yaml".to_owned(), "unknown-trait", "`Wobble` is not a trait".to_owned()),
+            ],
+            describe(diagnostics.clone()),
+        );
+
+        assert_eq!(vec!["Armament"], suggestions_of(&diagnostics));
+    }
+
+    #[test]
+    fn no_schema() {
+        // arrange
+        let files = ModFiles {
+            rules: vec![source_file("rules.yaml", "E1:\n\tWobble:\n")],
+            ..ModFiles::default()
+        };
+
+        let mut linter = Linter::empty();
+        linter.add_lint(UnknownTrait);
+
+        // act
+        let actual = linter.run(&files);
+
+        // assert
+        assert!(actual.is_empty());
+    }
+}
@@ -0,0 +1,168 @@
+// oraide - tools for OpenRA-based mod/game development
+// get the source code at https://github.com/Phrohdoh/oraide
+//
+// copyright (c)
+// - 2020 Taryn "Phrohdoh" Hill
+
+//! lints reporting actors which nothing uses
+
+use {
+    crate::{
+        lint::{
+            DefinitionKind,
+            Finding,
+            Lint,
+            LintContext,
+            LintFile,
+            Severity,
+        },
+        parse_key,
+        Node,
+    },
+};
+
+// ----- public interface ------------------------------------------------------
+
+/// Actors the engine itself requires, which need not be referenced.
+pub const ENGINE_ACTORS: [&str; 4] = ["World", "Player", "EditorWorld", "EditorPlayer"];
+
+/// Reports abstract (`^`) top-level actors which nothing inherits from
+/// (`unused-abstract`).
+///
+/// Actor names are compared ignoring case, as the engine does.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct UnusedAbstract;
+
+/// Reports concrete actors which are neither buildable, inherited from, nor
+/// referenced by any value of any rules, weapons, or map file
+/// (`unreferenced-actor`).
+///
+/// Actor names are compared ignoring case, as the engine does.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct UnreferencedActor;
+
+// ----- external trait impls --------------------------------------------------
+
+impl Lint for UnusedAbstract {
+    fn code(&self) -> &'static str {
+        "unused-abstract"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, cx: &LintContext, file: &LintFile) -> Vec<Finding> {
+        first_definitions_of(cx, file)
+            .filter(|(name, _)| name.starts_with('^') && !cx.is_inherited(name))
+            .map(|(name, node)| Finding::new(
+                node.key,
+                format!("abstract actor `{}` is never inherited from", name),
+            ))
+            .collect()
+    }
+}
+
+impl Lint for UnreferencedActor {
+    fn code(&self) -> &'static str {
+        "unreferenced-actor"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Info
+    }
+
+    fn check(&self, cx: &LintContext, file: &LintFile) -> Vec<Finding> {
+        let is_buildable = |name: &str| match cx.rules().resolve(name) {
+            Ok(resolved) => resolved.children.iter()
+                .any(|child| child.key == "Buildable" || child.key.starts_with("Buildable@")),
+            // can't tell, so don't claim it is unused
+            Err(_) => true,
+        };
+
+        first_definitions_of(cx, file)
+            .filter(|(name, _)| {
+                let is_used = name.starts_with('^')
+                    || cx.is_inherited(name)
+                    || cx.is_referenced(name)
+                    || ENGINE_ACTORS.iter().any(|engine_name| engine_name.eq_ignore_ascii_case(name))
+                    || is_buildable(name);
+
+                !is_used
+            })
+            .map(|(name, node)| Finding::new(
+                node.key,
+                format!("actor `{}` is not buildable and is never referenced", name),
+            ))
+            .collect()
+    }
+}
+
+// ----- private implementation details ----------------------------------------
+
+/// the name and node of every top-level actor of `file` which is the first
+/// definition of its name (so that each actor is only reported once)
+fn first_definitions_of<'a>(
+    cx: &'a LintContext,
+    file: &'a LintFile,
+) -> impl Iterator<Item = (&'a str, &'a Node)> + 'a {
+    let nodes = match file.kind.definition_kind() {
+        Some(DefinitionKind::Actor) => file.tree.nodes.as_slice(),
+        _ => &[],
+    };
+
+    nodes.iter()
+        .filter(move |node| !parse_key(file.text, node.key).is_removal())
+        .filter(move |node| cx.is_first_definition(DefinitionKind::Actor, file, node))
+        .map(move |node| (file.text[node.key].trim_end(), node))
+}
+
+// ----- tests -----------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use crate::lint::{
+        tests::{
+            describe,
+            source_file,
+        },
+        Linter,
+        ModFiles,
+    };
+
+    #[test]
+    fn unused_abstracts_and_unreferenced_actors() {
+        // arrange
+        let files = ModFiles {
+            rules: vec![
+                source_file("rules/defaults.yaml", "^Soldier:\n\tHealth:\n^Unused:\n\tHealth:\n^Vehicle:\n"),
+                source_file("rules/infantry.yaml", "E1:\n\tInherits: ^Soldier\n\tBuildable:\nE2:\n\tInherits: ^Soldier\nE3:\nE4:\nE5:\nWorld:\n"),
+                source_file("rules/vehicles.yaml", "^Vehicle:\n\tHealth:\n-E1:\n"),
+            ],
+            weapons: vec![
+                source_file("weapons/explosions.yaml", "Bomb:\n\tWarhead@spawn: SpawnActor\n\t\tActors: e3, E4\n"),
+            ],
+            maps: vec![
+                source_file("maps/test/map.yaml", "Actors:\n\tActor0: e5\n"),
+            ],
+            ..ModFiles::default()
+        };
+
+        let mut linter = Linter::empty();
+        linter.add_lint(super::UnusedAbstract);
+        linter.add_lint(super::UnreferencedActor);
+
+        // act
+        let actual = describe(linter.run(&files));
+
+        // assert
+        assert_eq!(
+            vec![
+                ("rules/defaults.yaml".to_owned(), "unused-abstract", "abstract actor `^Unused` is never inherited from".to_owned()),
+                ("rules/defaults.yaml".to_owned(), "unused-abstract", "abstract actor `^Vehicle` is never inherited from".to_owned()),
+                ("rules/infantry.yaml".to_owned(), "unreferenced-actor", "actor `E2` is not buildable and is never referenced".to_owned()),
+            ],
+            actual,
+        );
+    }
+}
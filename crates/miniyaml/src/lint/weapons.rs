@@ -0,0 +1,428 @@
+// oraide - tools for OpenRA-based mod/game development
+// get the source code at https://github.com/Phrohdoh/oraide
+//
+// copyright (c)
+// - 2020 Taryn "Phrohdoh" Hill
+
+//! the lint reporting weapons which the engine can't load
+
+use {
+    std::collections::HashSet,
+    crate::{
+        lint::{
+            references::{
+                closest_of,
+                finding_with_suggestion,
+            },
+            DefinitionKind,
+            FileKind,
+            Finding,
+            Lint,
+            LintContext,
+            LintFile,
+            Severity,
+        },
+        list_items_of,
+        parse_wdist,
+        AbsByteIdxSpan,
+        Origin,
+        ResolveError,
+        Weapon,
+    },
+};
+
+// ----- public interface ------------------------------------------------------
+
+/// The projectile types of the engine's common and C&C mods, which
+/// [`InvalidWeapon`] checks against by default when the mod's schema doesn't
+/// describe projectiles.
+///
+/// [`InvalidWeapon`]: struct.InvalidWeapon.html
+pub const PROJECTILE_TYPES: &[&str] = &[
+    "AreaBeam",
+    "ArcLaserZap",
+    "Bullet",
+    "ElectricBolt",
+    "GravityBomb",
+    "InstantHit",
+    "LaserZap",
+    "Missile",
+    "NukeLaunch",
+    "Railgun",
+    "TeslaZap",
+];
+
+/// The warhead types of the engine's common, C&C, and Dune 2000 mods, which
+/// [`InvalidWeapon`] checks against by default when the mod's schema doesn't
+/// describe warheads.
+///
+/// [`InvalidWeapon`]: struct.InvalidWeapon.html
+pub const WARHEAD_TYPES: &[&str] = &[
+    "ChangeOwner",
+    "CreateEffect",
+    "CreateResource",
+    "DamagesConcrete",
+    "DestroyResource",
+    "FireCluster",
+    "FlashEffect",
+    "FlashPaletteEffect",
+    "GrantExternalCondition",
+    "HealthPercentageDamage",
+    "LeaveSmudge",
+    "PerCellDamage",
+    "ShakeScreen",
+    "SpawnActor",
+    "SpreadDamage",
+    "TargetDamage",
+];
+
+/// Fields of traits (e.g. of `Armament` and `Explodes`) whose value names a
+/// weapon.
+pub const WEAPON_FIELDS: &[&str] = &[
+    "Weapon",
+    "EmptyWeapon",
+];
+
+/// Reports weapons which the engine can't load (`invalid-weapon`): those
+/// whose `Projectile` or `Warhead`s have no type or one which the mod's
+/// [`Schema`] (or, if it doesn't describe them, the lint's own list) doesn't
+/// know, whose `Range` or `MinRange` isn't a distance of at least zero, whose
+/// `ReloadDelay` or `Burst` isn't a positive integer, or which can't be
+/// resolved, as well as values of [`WEAPON_FIELDS`] in rules files naming a
+/// weapon which can't be resolved.
+///
+/// Each problem is reported where it is written if that is in the file being
+/// checked (else at the weapon's key).
+///
+/// [`WEAPON_FIELDS`]: constant.WEAPON_FIELDS.html
+/// [`Schema`]: ../struct.Schema.html
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct InvalidWeapon {
+    /// the known projectile types, unless the mod's schema describes them
+    pub projectile_types: Vec<String>,
+
+    /// the known warhead types, unless the mod's schema describes them
+    pub warhead_types: Vec<String>,
+}
+
+impl InvalidWeapon {
+    /// Check weapons against `projectile_types` and `warhead_types`, unless
+    /// the mod's schema describes them.
+    pub fn new(projectile_types: Vec<String>, warhead_types: Vec<String>) -> Self {
+        Self { projectile_types, warhead_types }
+    }
+}
+
+// ----- external trait impls --------------------------------------------------
+
+impl Default for InvalidWeapon {
+    fn default() -> Self {
+        let owned = |types: &[&str]| types.iter().map(|&type_name| type_name.to_owned()).collect();
+        Self::new(owned(PROJECTILE_TYPES), owned(WARHEAD_TYPES))
+    }
+}
+
+impl Lint for InvalidWeapon {
+    fn code(&self) -> &'static str {
+        "invalid-weapon"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, cx: &LintContext, file: &LintFile) -> Vec<Finding> {
+        match file.kind {
+            FileKind::Weapons => self.check_weapons(cx, file),
+            FileKind::Rules => check_weapon_fields(cx, file),
+            _ => vec![],
+        }
+    }
+}
+
+// ----- private implementation details ----------------------------------------
+
+/// something wrong with a field of a weapon
+struct Problem<'a> {
+    origin: &'a Origin,
+    message: String,
+    suggestion: Option<&'a str>,
+}
+
+impl InvalidWeapon {
+    fn check_weapons(&self, cx: &LintContext, file: &LintFile) -> Vec<Finding> {
+        let doc = file.text;
+        let mut findings = vec![];
+
+        // problems already reported where they are written, as a weapon and
+        // those inheriting from it share them
+        let mut reported = HashSet::new();
+
+        for node in &file.tree.nodes {
+            let name = doc[node.key].trim_end();
+
+            if !cx.is_first_definition(DefinitionKind::Weapon, file, node) {
+                continue;
+            }
+
+            let weapon = match Weapon::resolve(cx.weapons(), name) {
+                Ok(weapon) => weapon,
+                // undefined parents are reported by `undefined-reference`
+                Err(ResolveError::ParentNotDefined { .. }) => continue,
+                Err(err) => {
+                    findings.push(Finding::new(
+                        node.key,
+                        format!("`{}` can't be resolved: {}", name, err),
+                    ));
+
+                    continue;
+                },
+            };
+
+            for problem in self.problems_of(cx, &weapon) {
+                let span = match span_of(file, problem.origin) {
+                    Some(span) => span,
+                    None => {
+                        findings.push(Finding::new(
+                            node.key,
+                            format!("{} (at {})", problem.message, problem.origin),
+                        ));
+
+                        continue;
+                    },
+                };
+
+                if !reported.insert((problem.origin.line_idx, problem.message.clone())) {
+                    continue;
+                }
+
+                findings.push(finding_with_suggestion(span, problem.message, problem.suggestion));
+            }
+        }
+
+        findings
+    }
+
+    /// every problem with the fields, projectile, and warheads of `weapon`
+    fn problems_of<'a>(&'a self, cx: &'a LintContext, weapon: &'a Weapon) -> Vec<Problem<'a>> {
+        let mut problems = vec![];
+
+        let opt_schema = cx.schema();
+        let projectile_types = opt_schema.and_then(|schema| schema.projectiles.as_ref())
+            .unwrap_or(&self.projectile_types);
+        let warhead_types = opt_schema.and_then(|schema| schema.warheads.as_ref())
+            .unwrap_or(&self.warhead_types);
+
+        let parts = weapon.projectile.iter()
+            .map(|projectile| (projectile, "projectile", projectile_types))
+            .chain(weapon.warheads.iter().map(|warhead| (warhead, "warhead", warhead_types)));
+
+        for (part, part_kind, known_types) in parts {
+            match part.type_name.as_deref() {
+                None => problems.push(Problem {
+                    origin: &part.origin,
+                    message: format!("`{}` has no type", part.key),
+                    suggestion: None,
+                }),
+                Some(type_name) if !known_types.iter().any(|known_type| known_type == type_name) => {
+                    problems.push(Problem {
+                        origin: &part.origin,
+                        message: format!("`{}` is not a {} type", type_name, part_kind),
+                        suggestion: closest_of(type_name, known_types.iter().map(String::as_str)),
+                    });
+                },
+                Some(_) => {},
+            }
+        }
+
+        for field in &weapon.fields {
+            let opt_message = match field.key.as_str() {
+                "Range" | "MinRange" => match parse_wdist(&field.value) {
+                    None => Some(format!("`{}` must be a distance (e.g. `4c512`), not `{}`", field.key, field.value)),
+                    Some(distance) if distance < 0 => Some(format!("`{}` can't be negative (`{}`)", field.key, field.value)),
+                    Some(_) => None,
+                },
+                "ReloadDelay" | "Burst" => match field.value.parse::<i64>() {
+                    Ok(count) if count > 0 => None,
+                    _ => Some(format!("`{}` must be a positive integer, not `{}`", field.key, field.value)),
+                },
+                _ => None,
+            };
+
+            if let Some(message) = opt_message {
+                problems.push(Problem {
+                    origin: &field.origin,
+                    message,
+                    suggestion: None,
+                });
+            }
+        }
+
+        problems
+    }
+}
+
+/// findings for each item of each of [`WEAPON_FIELDS`] in `file` naming a
+/// weapon which can't be resolved (names which no weapons file defines are
+/// reported by `undefined-reference`)
+fn check_weapon_fields(cx: &LintContext, file: &LintFile) -> Vec<Finding> {
+    let doc = file.text;
+    let mut findings = vec![];
+
+    let value_spans = file.tree.iter()
+        .filter(|node| WEAPON_FIELDS.contains(&doc[node.key].trim_end()))
+        .filter_map(|node| node.value);
+
+    for value_span in value_spans {
+        for item_span in list_items_of(doc, value_span) {
+            let definition = match cx.definition_of(DefinitionKind::Weapon, &doc[item_span]) {
+                Some(definition) => definition,
+                None => continue,
+            };
+
+            if let Err(err) = Weapon::resolve(cx.weapons(), definition.name) {
+                findings.push(Finding::new(
+                    item_span,
+                    format!("`{}` can't be resolved: {}", &doc[item_span], err),
+                ));
+            }
+        }
+    }
+
+    findings
+}
+
+/// the span, in `file`, of the value (or, lacking one, the key) defined at
+/// `origin`, if it is in `file`
+fn span_of(file: &LintFile, origin: &Origin) -> Option<AbsByteIdxSpan> {
+    if origin.path != file.path {
+        return None;
+    }
+
+    let line = file.lines.get(origin.line_idx)?;
+    let span = line.value.or(line.key)?;
+    let (start, _) = span.into();
+
+    Some((start, start + file.text[span].trim_end().len()).into())
+}
+
+// ----- tests -----------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            lint::{
+                tests::{
+                    describe,
+                    source_file,
+                    suggestions_of,
+                },
+                Linter,
+                ModFiles,
+            },
+            Schema,
+        },
+    };
+
+    #[test]
+    fn invalid_weapons() {
+        // arrange
+        let mut files = ModFiles {
+            rules: vec![source_file("rules.yaml", [
+                "V2RL:\n",
+                "\tArmament:\n",
+                "\t\tWeapon: SCUD, Loop\n",
+                "\tExplodes:\n",
+                "\t\tWeapon: large-barrel-explode\n",
+                "\t\tEmptyWeapon: Nope\n",
+            ].concat().as_str())],
+            weapons: vec![source_file("weapons.yaml", [
+                "^Cannon:\n",
+                "\tReloadDelay: 0\n",
+                "\tRange: 4c768\n",
+                "\tProjectile: Bulet\n",
+                "\tWarhead@1Dam: SpreadDamage\n",
+                "25mm:\n",
+                "\tInherits: ^Cannon\n",
+                "90mm:\n",
+                "\tInherits: ^Cannon\n",
+                "\tRange: far\n",
+                "\tBurst: 2\n",
+                "SCUD:\n",
+                "\tMinRange: -1c0\n",
+                "\tProjectile: Missile\n",
+                "\tWarhead@1Dam:\n",
+                "\tWarhead@2Eff: CreateEfect\n",
+                "Loop:\n",
+                "\tInherits: Loop\n",
+                "large-barrel-explode:\n",
+                "\tInherits: Loop\n",
+            ].concat().as_str())],
+            ..ModFiles::default()
+        };
+
+        let mut linter = Linter::empty();
+        linter.add_lint(InvalidWeapon::default());
+
+        // act
+        let diagnostics = linter.run(&files);
+        let fix_count = linter.fix(&mut files);
+
+        // assert
+        let finding = |path: &str, message: &str| (path.to_owned(), "invalid-weapon", message.to_owned());
+        assert_eq!(
+            vec![
+                finding("rules.yaml", "`Loop` can't be resolved: weapons.yaml:18: parent \"Loop\" was already inherited (possibly via a cycle)"),
+                finding("rules.yaml", "`large-barrel-explode` can't be resolved: weapons.yaml:18: parent \"Loop\" was already inherited (possibly via a cycle)"),
+                finding("weapons.yaml", "`ReloadDelay` must be a positive integer, not `0`"),
+                finding("weapons.yaml", "`Bulet` is not a projectile type (did you mean `Bullet`?)"),
+                finding("weapons.yaml", "`Range` must be a distance (e.g. `4c512`), not `far`"),
+                finding("weapons.yaml", "`MinRange` can't be negative (`-1c0`)"),
+                finding("weapons.yaml", "`Warhead@1Dam` has no type"),
+                finding("weapons.yaml", "`CreateEfect` is not a warhead type (did you mean `CreateEffect`?)"),
+                finding("weapons.yaml", "`Loop` can't be resolved: weapons.yaml:18: parent \"Loop\" was already inherited (possibly via a cycle)"),
+                finding("weapons.yaml", "`large-barrel-explode` can't be resolved: weapons.yaml:18: parent \"Loop\" was already inherited (possibly via a cycle)"),
+            ],
+            describe(diagnostics.clone()),
+        );
+
+        assert_eq!(vec!["Bullet", "CreateEffect"], suggestions_of(&diagnostics));
+        assert_eq!(0, fix_count);
+    }
+    #[test]
+    fn types_described_by_the_schema() {
+        // arrange
+        let files = ModFiles {
+            weapons: vec![source_file("weapons.yaml", [
+                "Zap:\n",
+                "\tProjectile: ElectricBolt\n",
+                "\tWarhead@1Dam: SpreadDamage\n",
+                "Shot:\n",
+                "\tProjectile: Bullet\n",
+                "\tWarhead@1Dam: Blast\n",
+            ].concat().as_str())],
+            schema: Some(Schema {
+                projectiles: Some(vec!["Bullet".to_owned()]),
+                ..Schema::default()
+            }),
+            ..ModFiles::default()
+        };
+
+        let mut linter = Linter::empty();
+        linter.add_lint(InvalidWeapon::default());
+
+        // act
+        let actual = describe(linter.run(&files));
+
+        // assert
+        assert_eq!(
+            vec![
+                ("weapons.yaml".to_owned(), "invalid-weapon", "`ElectricBolt` is not a projectile type".to_owned()),
+                ("weapons.yaml".to_owned(), "invalid-weapon", "`Blast` is not a warhead type".to_owned()),
+            ],
+            actual,
+        );
+    }
+}
@@ -0,0 +1,123 @@
+// oraide - tools for OpenRA-based mod/game development
+// get the source code at https://github.com/Phrohdoh/oraide
+//
+// copyright (c)
+// - 2020 Taryn "Phrohdoh" Hill
+
+#![deny(missing_docs)]
+
+//! This [module] exposes items used to read a mod's manifest (`mod.yaml`),
+//! which lists the files each kind of definition (rules, weapons, etc.) is
+//! loaded from.
+//!
+//! [module]: https://doc.rust-lang.org/book/ch07-02-defining-modules-to-control-scope-and-privacy.html
+
+use {
+    std::{
+        collections::BTreeMap,
+        path::{
+            Path,
+            PathBuf,
+        },
+    },
+    crate::{
+        span_lines_of,
+        tree_of,
+    },
+};
+
+// ----- public interface ------------------------------------------------------
+
+/// The file lists of a mod's manifest.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Manifest {
+    /// the directory containing the manifest
+    pub mod_dir: PathBuf,
+
+    /// the child keys of each top-level node, keyed by the top-level node's
+    /// key (e.g. `Rules`), in document order
+    pub sections: BTreeMap<String, Vec<String>>,
+}
+
+impl Manifest {
+    /// Read the manifest `doc`, which is the content of the file at
+    /// `manifest_path`.
+    pub fn new(manifest_path: &Path, doc: &str) -> Self {
+        let lines = span_lines_of(doc);
+        let tree = tree_of(doc, &lines);
+
+        let sections = tree.nodes.iter()
+            .map(|node| {
+                let entries = node.children.iter()
+                    .map(|child| doc[child.key].trim_end().to_owned())
+                    .collect();
+
+                (doc[node.key].trim_end().to_owned(), entries)
+            })
+            .collect();
+
+        Self {
+            mod_dir: manifest_path.parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_default(),
+            sections,
+        }
+    }
+
+    /// The paths of the files listed in the `section` (e.g. `Rules` or
+    /// `Weapons`) of the manifest, see [`path_of`].
+    ///
+    /// [`path_of`]: struct.Manifest.html#method.path_of
+    pub fn files_of(&self, section: &str) -> Vec<PathBuf> {
+        self.sections.get(section)
+            .map(|entries| entries.iter()
+                .map(|entry| self.path_of(entry))
+                .collect())
+            .unwrap_or_default()
+    }
+
+    /// Resolve a path as written in a manifest.
+    ///
+    /// A path of the form `<mod-id>|<path>` (e.g. `ra|rules/misc.yaml`) is
+    /// relative to the directory of the mod with that id, which is assumed to
+    /// be a sibling of this mod's directory, and any other path is relative to
+    /// this mod's directory.
+    pub fn path_of(&self, entry: &str) -> PathBuf {
+        match entry.find('|') {
+            Some(sep_idx) => {
+                let (mod_id, path) = (&entry[..sep_idx], &entry[sep_idx + 1..]);
+                let mods_dir = self.mod_dir.parent().unwrap_or_else(|| Path::new(""));
+                mods_dir.join(mod_id).join(path)
+            },
+            None => self.mod_dir.join(entry),
+        }
+    }
+}
+
+// ----- tests -----------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn files_of_resolves_package_paths() {
+        // arrange
+        let doc = "Metadata:\n\tTitle: Red Alert\nRules:\n\tra|rules/misc.yaml\n\tcommon|rules/ai.yaml\n\trules/extra.yaml\n";
+
+        // act
+        let manifest = Manifest::new(Path::new("mods/ra/mod.yaml"), doc);
+
+        // assert
+        assert_eq!(
+            vec![
+                PathBuf::from("mods/ra/rules/misc.yaml"),
+                PathBuf::from("mods/common/rules/ai.yaml"),
+                PathBuf::from("mods/ra/rules/extra.yaml"),
+            ],
+            manifest.files_of("Rules"),
+        );
+
+        assert!(manifest.files_of("Weapons").is_empty());
+    }
+}
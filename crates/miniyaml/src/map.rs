@@ -0,0 +1,563 @@
+// oraide - tools for OpenRA-based mod/game development
+// get the source code at https://github.com/Phrohdoh/oraide
+//
+// copyright (c)
+// - 2020 Taryn "Phrohdoh" Hill
+
+#![deny(missing_docs)]
+
+//! This [module] exposes items used to read OpenRA maps, which are packages
+//! (either `.oramap` zip files or plain directories) containing a
+//! [`MAP_YAML`], a [`MAP_BIN`], and any files with which the map overrides
+//! the mod's definitions:
+//!
+//! ```text
+//! Rules: rules.yaml, ra|rules/campaign-rules.yaml
+//!     World:
+//!         -CrateSpawner:
+//! ```
+//!
+//! Each override section's value lists files (in the map package or, if of
+//! the form `<mod-id>|<path>`, in a mod), and its children are definitions,
+//! all of which are merged, in that order, on top of the mod's.
+//!
+//! [module]: https://doc.rust-lang.org/book/ch07-02-defining-modules-to-control-scope-and-privacy.html
+//! [`MAP_YAML`]: constant.MAP_YAML.html
+//! [`MAP_BIN`]: constant.MAP_BIN.html
+
+use {
+    std::{
+        collections::BTreeMap,
+        error,
+        fmt,
+        fs,
+        io,
+        path::{
+            Path,
+            PathBuf,
+        },
+    },
+    crate::{
+        list_items_of,
+        span_lines_of,
+        tree_of,
+        AbsByteIdxSpan,
+        FileLocation,
+        Node,
+        Rules,
+        SourceFile,
+        Tree,
+    },
+};
+
+// ----- public interface ------------------------------------------------------
+
+/// The name of the file, in a map package, describing the map.
+pub const MAP_YAML: &str = "map.yaml";
+
+/// The name of the file, in a map package, holding the map's cell layers.
+pub const MAP_BIN: &str = "map.bin";
+
+/// The top-level nodes of a [`MAP_YAML`] which override the mod's
+/// definitions, named as the manifest's sections they override are.
+///
+/// [`MAP_YAML`]: constant.MAP_YAML.html
+pub const OVERRIDE_SECTIONS: [&str; 7] = [
+    "Rules",
+    "Sequences",
+    "ModelSequences",
+    "Weapons",
+    "Voices",
+    "Music",
+    "Notifications",
+];
+
+/// A top-level node of a [`MAP_YAML`] which overrides the mod's definitions.
+///
+/// [`MAP_YAML`]: constant.MAP_YAML.html
+#[derive(Clone, PartialEq, Debug)]
+pub struct MapOverride {
+    /// the section overridden (one of [`OVERRIDE_SECTIONS`])
+    ///
+    /// [`OVERRIDE_SECTIONS`]: constant.OVERRIDE_SECTIONS.html
+    pub section: &'static str,
+
+    /// span of the node's key
+    pub key: AbsByteIdxSpan,
+
+    /// span of each file listed in the node's value, in order
+    pub files: Vec<AbsByteIdxSpan>,
+
+    /// the node's children, which are definitions in their own right
+    pub definitions: Vec<Node>,
+}
+
+/// Every override of the [`MAP_YAML`] `doc`, whose tree is `tree`, in
+/// document order.
+///
+/// [`MAP_YAML`]: constant.MAP_YAML.html
+pub fn overrides_of(doc: &str, tree: &Tree) -> Vec<MapOverride> {
+    tree.nodes.iter()
+        .filter_map(|node| {
+            let key_txt = doc[node.key].trim_end();
+            let section = OVERRIDE_SECTIONS.iter().find(|section| **section == key_txt)?;
+
+            Some(MapOverride {
+                section,
+                key: node.key,
+                files: node.value
+                    .map(|value_span| list_items_of(doc, value_span))
+                    .unwrap_or_default(),
+                definitions: node.children.clone(),
+            })
+        })
+        .collect()
+}
+
+/// `true` if `entry`, as listed by a [`MapOverride`], names a file of a mod
+/// (`<mod-id>|<path>`) rather than of the map package.
+///
+/// [`MapOverride`]: struct.MapOverride.html
+pub fn is_mod_entry(entry: &str) -> bool {
+    entry.contains('|')
+}
+
+/// The files of a map package.
+#[derive(Clone, PartialEq, Debug)]
+pub struct MapPackage {
+    /// path of the `.oramap` file or directory
+    pub path: PathBuf,
+
+    /// where the package's files were read from
+    kind: PackageKind,
+
+    /// the contents of each file, keyed by its `/`-separated path relative to
+    /// the package
+    entries: BTreeMap<String, Vec<u8>>,
+}
+
+impl MapPackage {
+    /// Read every file of the map package at `path`, which is either a
+    /// directory or a zip file (which requires the `oramap` feature).
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, MapError> {
+        let path = path.into();
+        let to_map_error = |kind| MapError { path: path.clone(), kind };
+
+        let (kind, entries) = if path.is_dir() {
+            let mut entries = BTreeMap::new();
+            read_dir_entries(&path, "", &mut entries)
+                .map_err(|err| to_map_error(MapErrorKind::Io(err.to_string())))?;
+
+            (PackageKind::Directory, entries)
+        } else {
+            (PackageKind::Zip, zip_entries_of(&path).map_err(to_map_error)?)
+        };
+
+        if !entries.contains_key(MAP_YAML) {
+            return Err(to_map_error(MapErrorKind::MissingMapYaml));
+        }
+
+        Ok(Self { path, kind, entries })
+    }
+
+    /// Create a package at `path` consisting of `entries`, each a
+    /// `/`-separated path relative to the package and its contents, none of
+    /// which are on disk.
+    pub fn from_entries(
+        path: impl Into<PathBuf>,
+        entries: impl IntoIterator<Item = (String, Vec<u8>)>,
+    ) -> Self {
+        Self {
+            path: path.into(),
+            kind: PackageKind::Memory,
+            entries: entries.into_iter().collect(),
+        }
+    }
+
+    /// The path of every file of the package, relative to the package, in
+    /// lexicographic order.
+    pub fn entry_names(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(String::as_str)
+    }
+
+    /// The contents of the file `name`, a `/`-separated path relative to the
+    /// package, if it exists.
+    pub fn entry(&self, name: &str) -> Option<&[u8]> {
+        self.entries.get(name).map(Vec::as_slice)
+    }
+
+    /// The path, as reported in diagnostics, of the package's file `name`
+    /// (which, for a zip package, is not a path on disk, see [`location_of`]).
+    ///
+    /// [`location_of`]: struct.MapPackage.html#method.location_of
+    pub fn path_of(&self, name: &str) -> PathBuf {
+        self.path.join(name)
+    }
+
+    /// Where the package's file `name` was read from.
+    pub fn location_of(&self, name: &str) -> FileLocation {
+        match self.kind {
+            PackageKind::Memory => FileLocation::Memory,
+            PackageKind::Directory => FileLocation::Disk,
+            PackageKind::Zip => FileLocation::Zip {
+                package: self.path.clone(),
+                entry: name.to_owned(),
+            },
+        }
+    }
+
+    /// The package's [`MAP_YAML`], decoded lossily.
+    ///
+    /// [`MAP_YAML`]: constant.MAP_YAML.html
+    pub fn map_yaml(&self) -> Option<SourceFile> {
+        self.source_file_of(MAP_YAML)
+    }
+
+    /// The contents of the package's [`MAP_BIN`].
+    ///
+    /// [`MAP_BIN`]: constant.MAP_BIN.html
+    pub fn map_bin(&self) -> Option<&[u8]> {
+        self.entry(MAP_BIN)
+    }
+
+    /// The files of the package which `section` of the map's [`MAP_YAML`]
+    /// lists, in order, each decoded lossily (files of mods, and files which
+    /// don't exist, are skipped).
+    ///
+    /// [`MAP_YAML`]: constant.MAP_YAML.html
+    pub fn override_files_of(&self, section: &str) -> Vec<SourceFile> {
+        let map_yaml = match self.map_yaml() {
+            Some(map_yaml) => map_yaml,
+            None => return vec![],
+        };
+
+        let doc = &map_yaml.text;
+        let lines = span_lines_of(doc);
+        let tree = tree_of(doc, &lines);
+
+        overrides_of(doc, &tree).iter()
+            .filter(|map_override| map_override.section == section)
+            .flat_map(|map_override| map_override.files.iter())
+            .map(|&entry_span| &doc[entry_span])
+            .filter(|entry| !is_mod_entry(entry))
+            .filter_map(|entry| self.source_file_of(entry))
+            .collect()
+    }
+
+    /// `base` (e.g. the mod's rules) with the map's overrides of `section`
+    /// (e.g. `Rules`) merged on top, first those of the package's files then
+    /// those of its [`MAP_YAML`].
+    ///
+    /// [`MAP_YAML`]: constant.MAP_YAML.html
+    pub fn merged_over(&self, section: &str, base: &Rules) -> Rules {
+        let mut rules = base.clone();
+
+        for file in self.override_files_of(section) {
+            rules.add_file(file.path, &file.text);
+        }
+
+        if let Some(map_yaml) = self.map_yaml() {
+            let doc = &map_yaml.text;
+            let lines = span_lines_of(doc);
+            let tree = tree_of(doc, &lines);
+
+            for map_override in overrides_of(doc, &tree) {
+                if map_override.section == section {
+                    rules.add_nodes(&map_yaml.path, doc, &map_override.definitions);
+                }
+            }
+        }
+
+        rules
+    }
+}
+
+/// `true` if `path` looks like a map package, i.e. is either a `.oramap`
+/// file (ignoring the extension's case) or a directory containing a
+/// [`MAP_YAML`].
+///
+/// [`MAP_YAML`]: constant.MAP_YAML.html
+pub fn is_map_package(path: &Path) -> bool {
+    if path.is_dir() {
+        return path.join(MAP_YAML).is_file();
+    }
+
+    path.is_file() && path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("oramap"))
+}
+
+/// Why a map package could not be read.
+#[derive(Clone, PartialEq, Debug)]
+pub struct MapError {
+    /// path of the map package
+    pub path: PathBuf,
+
+    /// what is wrong with it
+    pub kind: MapErrorKind,
+}
+
+/// What is wrong with a map package.
+#[derive(Clone, PartialEq, Debug)]
+pub enum MapErrorKind {
+    /// the package could not be read
+    Io(String),
+
+    /// the package is not a valid zip file
+    Zip(String),
+
+    /// the package is a zip file, but zip support (the `oramap` feature) is
+    /// disabled
+    ZipUnsupported,
+
+    /// the package has no [`MAP_YAML`]
+    ///
+    /// [`MAP_YAML`]: constant.MAP_YAML.html
+    MissingMapYaml,
+}
+
+// ----- external trait impls --------------------------------------------------
+
+impl fmt::Display for MapError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: ", self.path.display())?;
+
+        match &self.kind {
+            MapErrorKind::Io(message) => write!(f, "{}", message),
+            MapErrorKind::Zip(message) => write!(f, "invalid zip file: {}", message),
+            MapErrorKind::ZipUnsupported => write!(f, "zip map packages are not supported by this build"),
+            MapErrorKind::MissingMapYaml => write!(f, "map package has no {}", MAP_YAML),
+        }
+    }
+}
+
+impl error::Error for MapError {}
+
+// ----- private implementation details ----------------------------------------
+
+/// where the files of a map package were read from
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+enum PackageKind {
+    Memory,
+    Directory,
+    Zip,
+}
+
+impl MapPackage {
+    fn source_file_of(&self, name: &str) -> Option<SourceFile> {
+        let bytes = self.entry(name)?;
+
+        Some(SourceFile::decode(self.path_of(name), bytes).with_location(self.location_of(name)))
+    }
+}
+
+/// read every file beneath `dir` into `entries`, keyed by `prefix` followed by
+/// its path relative to `dir`
+fn read_dir_entries(
+    dir: &Path,
+    prefix: &str,
+    entries: &mut BTreeMap<String, Vec<u8>>,
+) -> io::Result<()> {
+    for dir_entry in fs::read_dir(dir)? {
+        let dir_entry = dir_entry?;
+        let name = format!("{}{}", prefix, dir_entry.file_name().to_string_lossy());
+
+        if dir_entry.file_type()?.is_dir() {
+            read_dir_entries(&dir_entry.path(), &format!("{}/", name), entries)?;
+        } else {
+            entries.insert(name, fs::read(dir_entry.path())?);
+        }
+    }
+
+    Ok(())
+}
+
+/// the contents of every file of the zip file at `path`, keyed by their
+/// `/`-separated paths (with any `.` and `..` resolved)
+///
+/// Entries whose paths are absolute or lead outside of the zip file (which
+/// would otherwise be joined onto its path) are skipped.
+#[cfg(feature = "oramap")]
+pub(crate) fn zip_entries_of(path: &Path) -> Result<BTreeMap<String, Vec<u8>>, MapErrorKind> {
+    use std::io::Read;
+
+    let file = fs::File::open(path)
+        .map_err(|err| MapErrorKind::Io(err.to_string()))?;
+    let mut archive = zip::ZipArchive::new(file)
+        .map_err(|err| MapErrorKind::Zip(err.to_string()))?;
+
+    let mut entries = BTreeMap::new();
+    for entry_idx in 0..archive.len() {
+        let mut zip_file = archive.by_index(entry_idx)
+            .map_err(|err| MapErrorKind::Zip(err.to_string()))?;
+
+        let name = match zip_file.enclosed_name() {
+            Some(enclosed_name) if !zip_file.is_dir() => entry_name_of(enclosed_name),
+            _ => continue,
+        };
+
+        let mut bytes = vec![];
+        zip_file.read_to_end(&mut bytes)
+            .map_err(|err| MapErrorKind::Zip(err.to_string()))?;
+
+        entries.insert(name, bytes);
+    }
+
+    Ok(entries)
+}
+
+/// the `/`-separated form of `enclosed_name`, a relative path which doesn't
+/// lead outside of its zip file
+#[cfg(feature = "oramap")]
+fn entry_name_of(enclosed_name: &Path) -> String {
+    use std::path::Component;
+
+    let mut parts = vec![];
+    for component in enclosed_name.components() {
+        match component {
+            Component::Normal(part) => parts.push(part.to_string_lossy()),
+            Component::ParentDir => { parts.pop(); },
+            _ => {},
+        }
+    }
+
+    parts.join("/")
+}
+
+#[cfg(not(feature = "oramap"))]
+pub(crate) fn zip_entries_of(path: &Path) -> Result<BTreeMap<String, Vec<u8>>, MapErrorKind> {
+    match fs::metadata(path) {
+        Ok(_) => Err(MapErrorKind::ZipUnsupported),
+        Err(err) => Err(MapErrorKind::Io(err.to_string())),
+    }
+}
+
+// ----- tests -----------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn package() -> MapPackage {
+        MapPackage::from_entries("maps/island.oramap", vec![
+            (MAP_YAML.to_owned(), b"Title: Island\nRules: rules.yaml, ra|rules/campaign.yaml, missing.yaml\n\tE1:\n\t\tHealth:\n\t\t\tHP: 300\nWeapons:\n\tM1Carbine:\n\t\tReloadDelay: 10\n".to_vec()),
+            ("rules.yaml".to_owned(), b"E1:\n\tValued:\n\t\tCost: 50\n".to_vec()),
+            (MAP_BIN.to_owned(), vec![2, 0, 0]),
+        ])
+    }
+
+    #[test]
+    fn overrides() {
+        // arrange
+        let map_yaml = package().map_yaml().unwrap();
+        let doc = &map_yaml.text;
+        let lines = span_lines_of(doc);
+        let tree = tree_of(doc, &lines);
+
+        // act
+        let overrides = overrides_of(doc, &tree);
+
+        // assert
+        assert_eq!(
+            vec![
+                ("Rules", vec!["rules.yaml", "ra|rules/campaign.yaml", "missing.yaml"], vec!["E1"]),
+                ("Weapons", vec![], vec!["M1Carbine"]),
+            ],
+            overrides.iter()
+                .map(|map_override| (
+                    map_override.section,
+                    map_override.files.iter().map(|&span| &doc[span]).collect::<Vec<_>>(),
+                    map_override.definitions.iter().map(|node| &doc[node.key]).collect::<Vec<_>>(),
+                ))
+                .collect::<Vec<_>>(),
+        );
+
+        assert_eq!(Some(&[2, 0, 0][..]), package().map_bin());
+    }
+
+    #[test]
+    fn merged_over_the_mods_rules() {
+        // arrange
+        let mut mod_rules = Rules::new();
+        mod_rules.add_file("rules/infantry.yaml", "E1:\n\tHealth:\n\t\tHP: 100\n\tValued:\n\t\tCost: 100\n");
+
+        // act
+        let rules = package().merged_over("Rules", &mod_rules);
+        let e1 = rules.resolve("E1").unwrap();
+
+        // assert
+        let field_of = |trait_key: &str| {
+            let trait_node = e1.children.iter().find(|child| child.key == trait_key).unwrap();
+            (trait_node.children[0].value.clone(), trait_node.children[0].origin.to_string())
+        };
+
+        assert_eq!(
+            (Some("300".to_owned()), "maps/island.oramap/map.yaml:5".to_owned()),
+            field_of("Health"),
+        );
+
+        assert_eq!(
+            (Some("50".to_owned()), "maps/island.oramap/rules.yaml:3".to_owned()),
+            field_of("Valued"),
+        );
+    }
+
+    #[cfg(feature = "oramap")]
+    #[test]
+    fn opens_zip_packages() {
+        use std::io::Write;
+
+        // arrange
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("island.ORAMAP");
+
+        let mut writer = zip::ZipWriter::new(fs::File::create(&path).unwrap());
+        for (name, bytes) in &package().entries {
+            writer.start_file(name.as_str(), zip::write::FileOptions::default()).unwrap();
+            writer.write_all(bytes).unwrap();
+        }
+
+        writer.finish().unwrap();
+
+        // act
+        let is_package = is_map_package(&path);
+        let opened = MapPackage::open(&path);
+
+        // assert
+        assert!(is_package);
+
+        let opened = opened.unwrap();
+        assert_eq!(package().entries, opened.entries);
+        assert_eq!(path, opened.path);
+    }
+
+    #[cfg(feature = "oramap")]
+    #[test]
+    fn skips_zip_entries_outside_of_the_package() {
+        use std::io::Write;
+
+        // arrange
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("escape.oramap");
+
+        let mut writer = zip::ZipWriter::new(fs::File::create(&path).unwrap());
+        for name in &["map.yaml", "/etc/rules.yaml", "../rules.yaml", "sub/../rules.yaml"] {
+            writer.start_file(*name, zip::write::FileOptions::default()).unwrap();
+            writer.write_all(name.as_bytes()).unwrap();
+        }
+
+        writer.finish().unwrap();
+
+        // act
+        let opened = MapPackage::open(&path);
+
+        // assert
+        let opened = opened.unwrap();
+        assert_eq!(vec!["map.yaml", "rules.yaml"], opened.entry_names().collect::<Vec<_>>());
+        assert_eq!(Some(&b"sub/../rules.yaml"[..]), opened.entry("rules.yaml"));
+        assert_eq!(
+            FileLocation::Zip { package: path.clone(), entry: "rules.yaml".to_owned() },
+            opened.location_of("rules.yaml"),
+        );
+    }
+}
@@ -0,0 +1,339 @@
+// oraide - tools for OpenRA-based mod/game development
+// get the source code at https://github.com/Phrohdoh/oraide
+//
+// copyright (c)
+// - 2020 Taryn "Phrohdoh" Hill
+
+#![deny(missing_docs)]
+
+//! This [module] exposes items used to read the [`MAP_BIN`] of a map, which
+//! holds the map's terrain tile, height, and resource layers.
+//!
+//! Every format starts with a header of little-endian integers:
+//!
+//! | type  | meaning                      |
+//! |-------|------------------------------|
+//! | `u8`  | format version (`1` or `2`)  |
+//! | `u16` | width, in cells              |
+//! | `u16` | height, in cells             |
+//!
+//! which, in format `2`, is followed by the `u32` offsets of the tile, height,
+//! and resource layers (an offset of `0` meaning the layer is absent).  In
+//! format `1`, the tile layer immediately follows the header and is followed
+//! by the resource layer, and there is no height layer.
+//!
+//! Each layer holds every cell in column-major order (all of the first
+//! column, from top to bottom, then all of the second, and so on), where a
+//! tile is a `u16` template id and a `u8` index into said template, a height
+//! is a `u8`, and a resource is a `u8` type and a `u8` density.
+//!
+//! [module]: https://doc.rust-lang.org/book/ch07-02-defining-modules-to-control-scope-and-privacy.html
+//! [`MAP_BIN`]: constant.MAP_BIN.html
+
+use {
+    std::{
+        error,
+        fmt,
+    },
+};
+
+// ----- public interface ------------------------------------------------------
+
+/// The terrain of a single cell.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct TerrainTile {
+    /// id of the tileset template the tile is from
+    pub template_id: u16,
+
+    /// index of the tile within said template
+    pub index: u8,
+}
+
+/// The resource (e.g. ore) of a single cell.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct ResourceTile {
+    /// the resource type, where `0` is none
+    pub type_id: u8,
+
+    /// how much of the resource is in the cell
+    pub density: u8,
+}
+
+/// The cell layers of a map.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct MapBin {
+    /// the format version
+    pub format: u8,
+
+    /// width of the map, in cells
+    pub width: u16,
+
+    /// height of the map, in cells
+    pub height: u16,
+
+    /// the tile of every cell (in column-major order), if present
+    pub tiles: Option<Vec<TerrainTile>>,
+
+    /// the height of every cell (in column-major order), if present
+    pub heights: Option<Vec<u8>>,
+
+    /// the resource of every cell (in column-major order), if present
+    pub resources: Option<Vec<ResourceTile>>,
+}
+
+impl MapBin {
+    /// Read the layers of the `map.bin` whose contents are `bytes`.
+    pub fn parse(bytes: &[u8]) -> Result<Self, MapBinError> {
+        let mut reader = Reader { bytes, offset: 0 };
+
+        let format = reader.u8("header")?;
+        let width = reader.u16("header")?;
+        let height = reader.u16("header")?;
+        let cell_count = width as usize * height as usize;
+
+        let (tiles_offset, heights_offset, resources_offset) = match format {
+            1 => (HEADER_LEN_V1, 0, HEADER_LEN_V1 + cell_count * 3),
+            2 => (
+                reader.u32("header")? as usize,
+                reader.u32("header")? as usize,
+                reader.u32("header")? as usize,
+            ),
+            _ => return Err(MapBinError::UnknownFormat(format)),
+        };
+
+        let layer_at = |offset: usize| -> Option<Reader> {
+            if offset == 0 { None } else { Some(Reader { bytes, offset }) }
+        };
+
+        let tiles = layer_at(tiles_offset)
+            .map(|mut reader| (0..cell_count)
+                .map(|_| Ok(TerrainTile {
+                    template_id: reader.u16("tile")?,
+                    index: reader.u8("tile")?,
+                }))
+                .collect::<Result<Vec<_>, _>>())
+            .transpose()?;
+
+        let heights = layer_at(heights_offset)
+            .map(|mut reader| (0..cell_count)
+                .map(|_| reader.u8("height"))
+                .collect::<Result<Vec<_>, _>>())
+            .transpose()?;
+
+        // format 1 maps may end after their tile layer
+        let resources = match layer_at(resources_offset) {
+            Some(_) if format == 1 && resources_offset == bytes.len() => None,
+            opt_reader => opt_reader
+                .map(|mut reader| (0..cell_count)
+                    .map(|_| Ok(ResourceTile {
+                        type_id: reader.u8("resource")?,
+                        density: reader.u8("resource")?,
+                    }))
+                    .collect::<Result<Vec<_>, _>>())
+                .transpose()?,
+        };
+
+        Ok(Self {
+            format,
+            width,
+            height,
+            tiles,
+            heights,
+            resources,
+        })
+    }
+
+    /// The tile of the cell at column `x` and row `y`, if the map has a tile
+    /// layer and the cell is on the map.
+    pub fn tile(&self, x: u16, y: u16) -> Option<TerrainTile> {
+        let cell_idx = self.cell_idx_of(x, y)?;
+        self.tiles.as_ref().map(|tiles| tiles[cell_idx])
+    }
+
+    /// The height of the cell at column `x` and row `y`, if the map has a
+    /// height layer and the cell is on the map.
+    pub fn height(&self, x: u16, y: u16) -> Option<u8> {
+        let cell_idx = self.cell_idx_of(x, y)?;
+        self.heights.as_ref().map(|heights| heights[cell_idx])
+    }
+
+    /// The resource of the cell at column `x` and row `y`, if the map has a
+    /// resource layer and the cell is on the map.
+    pub fn resource(&self, x: u16, y: u16) -> Option<ResourceTile> {
+        let cell_idx = self.cell_idx_of(x, y)?;
+        self.resources.as_ref().map(|resources| resources[cell_idx])
+    }
+
+    /// Every cell, as `(x, y)`, in the order layers hold them.
+    pub fn cells(&self) -> impl Iterator<Item = (u16, u16)> {
+        let height = self.height;
+        (0..self.width).flat_map(move |x| (0..height).map(move |y| (x, y)))
+    }
+}
+
+/// Why a `map.bin` could not be read.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum MapBinError {
+    /// the format version is neither `1` nor `2`
+    UnknownFormat(u8),
+
+    /// the file ends before a layer (or the header) does
+    Truncated {
+        /// what was being read (e.g. `tile`)
+        layer: &'static str,
+
+        /// offset, in bytes, at which more bytes were expected
+        offset: usize,
+    },
+}
+
+// ----- external trait impls --------------------------------------------------
+
+impl fmt::Display for MapBinError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MapBinError::UnknownFormat(format) => write!(f, "unknown format {}", format),
+            MapBinError::Truncated { layer, offset } => {
+                write!(f, "ends at byte {}, within its {} layer", offset, layer)
+            },
+        }
+    }
+}
+
+impl error::Error for MapBinError {}
+
+// ----- private implementation details ----------------------------------------
+
+/// the length of the header of format 1, which is followed by the tile layer
+const HEADER_LEN_V1: usize = 5;
+
+impl MapBin {
+    fn cell_idx_of(&self, x: u16, y: u16) -> Option<usize> {
+        if x < self.width && y < self.height {
+            Some(x as usize * self.height as usize + y as usize)
+        } else {
+            None
+        }
+    }
+}
+
+/// reads little-endian integers from `bytes`, starting at `offset`
+struct Reader<'bytes> {
+    bytes: &'bytes [u8],
+    offset: usize,
+}
+
+impl Reader<'_> {
+    fn take<const LEN: usize>(&mut self, layer: &'static str) -> Result<[u8; LEN], MapBinError> {
+        let truncated = MapBinError::Truncated { layer, offset: self.bytes.len().min(self.offset) };
+
+        let taken = self.bytes.get(self.offset..self.offset + LEN).ok_or(truncated)?;
+        self.offset += LEN;
+
+        let mut ret = [0; LEN];
+        ret.copy_from_slice(taken);
+        Ok(ret)
+    }
+
+    fn u8(&mut self, layer: &'static str) -> Result<u8, MapBinError> {
+        self.take::<1>(layer).map(|[byte]| byte)
+    }
+
+    fn u16(&mut self, layer: &'static str) -> Result<u16, MapBinError> {
+        self.take(layer).map(u16::from_le_bytes)
+    }
+
+    fn u32(&mut self, layer: &'static str) -> Result<u32, MapBinError> {
+        self.take(layer).map(u32::from_le_bytes)
+    }
+}
+
+// ----- tests -----------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// a `map.bin` of format 2, `width` by `height` cells, whose tile template
+    /// ids, heights, and resource densities are each cell's index
+    fn v2_of(width: u16, height: u16) -> Vec<u8> {
+        let cell_count = width as usize * height as usize;
+        let tiles_offset = 17;
+        let heights_offset = tiles_offset + cell_count * 3;
+        let resources_offset = heights_offset + cell_count;
+
+        let mut bytes = vec![2];
+        bytes.extend(&width.to_le_bytes());
+        bytes.extend(&height.to_le_bytes());
+        for offset in &[tiles_offset, heights_offset, resources_offset] {
+            bytes.extend(&(*offset as u32).to_le_bytes());
+        }
+
+        for cell_idx in 0..cell_count {
+            bytes.extend(&(cell_idx as u16).to_le_bytes());
+            bytes.push(0);
+        }
+
+        bytes.extend((0..cell_count).map(|cell_idx| cell_idx as u8));
+
+        for cell_idx in 0..cell_count {
+            bytes.extend(&[1, cell_idx as u8]);
+        }
+
+        bytes
+    }
+
+    #[test]
+    fn reads_format_2() {
+        // arrange
+        let bytes = v2_of(3, 2);
+
+        // act
+        let map_bin = MapBin::parse(&bytes).unwrap();
+
+        // assert
+        assert_eq!((2, 3, 2), (map_bin.format, map_bin.width, map_bin.height));
+
+        // column-major, so (1, 0) is the third cell
+        assert_eq!(Some(TerrainTile { template_id: 2, index: 0 }), map_bin.tile(1, 0));
+        assert_eq!(Some(5), map_bin.height(2, 1));
+        assert_eq!(Some(ResourceTile { type_id: 1, density: 1 }), map_bin.resource(0, 1));
+        assert_eq!(None, map_bin.tile(3, 0));
+
+        assert_eq!(
+            vec![(0, 0), (0, 1), (1, 0), (1, 1), (2, 0), (2, 1)],
+            map_bin.cells().collect::<Vec<_>>(),
+        );
+    }
+
+    #[test]
+    fn reads_format_1() {
+        // arrange
+        let mut bytes = vec![1, 1, 0, 2, 0];
+        bytes.extend(&[0xff, 0x00, 3, 0x10, 0x00, 4]);
+
+        // act
+        let without_resources = MapBin::parse(&bytes).unwrap();
+
+        bytes.extend(&[0, 0, 2, 12]);
+        let with_resources = MapBin::parse(&bytes).unwrap();
+
+        // assert
+        assert_eq!(Some(TerrainTile { template_id: 0xff, index: 3 }), without_resources.tile(0, 0));
+        assert_eq!(Some(TerrainTile { template_id: 0x10, index: 4 }), without_resources.tile(0, 1));
+        assert_eq!(None, without_resources.resources);
+        assert_eq!(None, without_resources.heights);
+
+        assert_eq!(Some(ResourceTile { type_id: 2, density: 12 }), with_resources.resource(0, 1));
+    }
+
+    #[test]
+    fn errors() {
+        let mut truncated = v2_of(2, 2);
+        truncated.truncate(truncated.len() - 1);
+
+        assert_eq!(Err(MapBinError::UnknownFormat(9)), MapBin::parse(&[9, 1, 0, 1, 0]));
+        assert_eq!(Err(MapBinError::Truncated { layer: "header", offset: 3 }), MapBin::parse(&[2, 1, 0]));
+        assert_eq!(Err(MapBinError::Truncated { layer: "resource", offset: 40 }), MapBin::parse(&truncated));
+    }
+}
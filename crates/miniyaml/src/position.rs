@@ -0,0 +1,168 @@
+// oraide - tools for OpenRA-based mod/game development
+// get the source code at https://github.com/Phrohdoh/oraide
+//
+// copyright (c)
+// - 2020 Taryn "Phrohdoh" Hill
+
+#![deny(missing_docs)]
+
+//! This [module] exposes items used to map absolute byte indices to
+//! line/column positions (and back), which is how humans and editors refer to
+//! locations in a document.
+//!
+//! [module]: https://doc.rust-lang.org/book/ch07-02-defining-modules-to-control-scope-and-privacy.html
+
+// ----- public interface ------------------------------------------------------
+
+/// A zero-based line and column.
+///
+/// The unit of `col` depends on how the position was computed, see
+/// [`LineIndex`].
+///
+/// [`LineIndex`]: struct.LineIndex.html
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LineCol {
+    /// zero-based line number
+    pub line: usize,
+
+    /// zero-based column
+    pub col: usize,
+}
+
+/// The start of every line of a document, used to convert between absolute
+/// byte indices and [`LineCol`]s.
+///
+/// Lines are terminated the same way the spanner terminates them (by `\n`,
+/// `\r\n`, or a lone `\r`).
+///
+/// [`LineCol`]: struct.LineCol.html
+#[derive(Clone, Debug)]
+pub struct LineIndex {
+    line_start_abs_idxs: Vec<usize>,
+}
+
+impl LineIndex {
+    /// Compute the line starts of `doc`.
+    pub fn new(doc: &str) -> Self {
+        let mut line_start_abs_idxs = vec![0];
+        let bytes = doc.as_bytes();
+
+        for (abs_idx, &byte) in bytes.iter().enumerate() {
+            let is_term_end = match byte {
+                b'\n' => true,
+                b'\r' => bytes.get(abs_idx + 1) != Some(&b'\n'),
+                _ => false,
+            };
+
+            if is_term_end {
+                line_start_abs_idxs.push(abs_idx + 1);
+            }
+        }
+
+        Self {
+            line_start_abs_idxs,
+        }
+    }
+
+    /// The number of lines, which includes the (possibly empty) line following
+    /// a trailing line-terminator.
+    pub fn line_count(&self) -> usize {
+        self.line_start_abs_idxs.len()
+    }
+
+    /// The absolute byte index at which zero-based `line` starts.
+    pub fn line_start(&self, line: usize) -> Option<usize> {
+        self.line_start_abs_idxs.get(line).copied()
+    }
+
+    /// The position of `abs_idx` in `doc`, with `col` counted in `char`s.
+    pub fn line_col(&self, doc: &str, abs_idx: usize) -> LineCol {
+        let (line, line_start_abs_idx) = self.line_and_start_of(abs_idx);
+        let col = doc[line_start_abs_idx..abs_idx].chars().count();
+        LineCol { line, col }
+    }
+
+    /// The position of `abs_idx` in `doc`, with `col` counted in UTF-16 code
+    /// units (as the [Language Server Protocol] does by default).
+    ///
+    /// [Language Server Protocol]: https://microsoft.github.io/language-server-protocol/
+    pub fn line_col_utf16(&self, doc: &str, abs_idx: usize) -> LineCol {
+        let (line, line_start_abs_idx) = self.line_and_start_of(abs_idx);
+        let col = doc[line_start_abs_idx..abs_idx].encode_utf16().count();
+        LineCol { line, col }
+    }
+
+    /// The absolute byte index of `pos` (with `col` counted in UTF-16 code
+    /// units) in `doc`, clamped to the end of the line / document.
+    pub fn abs_idx_of_utf16(&self, doc: &str, pos: LineCol) -> usize {
+        let line_start_abs_idx = match self.line_start(pos.line) {
+            Some(abs_idx) => abs_idx,
+            None => return doc.len(),
+        };
+
+        let mut remaining_units = pos.col;
+        for (rel_idx, ch) in doc[line_start_abs_idx..].char_indices() {
+            if remaining_units == 0 || ch == '\n' || ch == '\r' {
+                return line_start_abs_idx + rel_idx;
+            }
+
+            remaining_units = remaining_units.saturating_sub(ch.len_utf16());
+        }
+
+        doc.len()
+    }
+
+    fn line_and_start_of(&self, abs_idx: usize) -> (usize, usize) {
+        let line = match self.line_start_abs_idxs.binary_search(&abs_idx) {
+            Ok(line) => line,
+            Err(next_line) => next_line - 1,
+        };
+
+        (line, self.line_start_abs_idxs[line])
+    }
+}
+
+// ----- tests -----------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_col_across_all_terminators() {
+        // arrange
+        let doc = "a:\nb:\r\nc\rd请e";
+        let line_index = LineIndex::new(doc);
+
+        // act
+        let actual_positions = [0, 3, 5, 7, 9, 10, 13]
+            .iter()
+            .map(|&abs_idx| line_index.line_col(doc, abs_idx))
+            .map(|LineCol { line, col }| (line, col))
+            .collect::<Vec<_>>();
+
+        // assert
+        assert_eq!(4, line_index.line_count());
+        assert_eq!(
+            vec![(0, 0), (1, 0), (1, 2), (2, 0), (3, 0), (3, 1), (3, 2)],
+            actual_positions,
+        );
+    }
+
+    #[test]
+    fn utf16_roundtrips() {
+        // arrange
+        let doc = "k: 𝄞x\n";
+        let line_index = LineIndex::new(doc);
+        let x_abs_idx = doc.find('x').unwrap();
+
+        // act
+        let pos = line_index.line_col_utf16(doc, x_abs_idx);
+        let actual_abs_idx = line_index.abs_idx_of_utf16(doc, pos);
+
+        // assert
+        assert_eq!(LineCol { line: 0, col: 5 }, pos);
+        assert_eq!(x_abs_idx, actual_abs_idx);
+    }
+}
@@ -0,0 +1,411 @@
+// oraide - tools for OpenRA-based mod/game development
+// get the source code at https://github.com/Phrohdoh/oraide
+//
+// copyright (c)
+// - 2020 Taryn "Phrohdoh" Hill
+
+#![deny(missing_docs)]
+
+//! This [module] exposes items used to merge the definitions of several files
+//! and resolve the final definition of a single top-level node, the same way
+//! OpenRA does when it loads a mod:
+//!
+//! 1. top-level nodes with the same key, from any file, are merged (a later
+//!    value replaces an earlier one, children are merged recursively)
+//! 2. `Inherits` (and `Inherits@<suffix>`) children are replaced with the
+//!    resolved children of the node they name
+//! 3. `-<key>` children remove the previously merged child `<key>`
+//!
+//! [module]: https://doc.rust-lang.org/book/ch07-02-defining-modules-to-control-scope-and-privacy.html
+
+use {
+    std::{
+        error,
+        fmt,
+        path::{
+            Path,
+            PathBuf,
+        },
+    },
+    crate::{
+        span_lines_of,
+        tree_of,
+        Node,
+    },
+};
+
+// ----- public interface ------------------------------------------------------
+
+/// Where a [`RuleNode`] was defined.
+///
+/// [`RuleNode`]: struct.RuleNode.html
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Origin {
+    /// path of the file containing the definition
+    pub path: PathBuf,
+
+    /// index of the line, in said file, of the definition
+    pub line_idx: usize,
+}
+
+/// A node which, unlike a [`Node`], owns its text and may be the result of
+/// merging several definitions.
+///
+/// [`Node`]: struct.Node.html
+#[derive(Clone, PartialEq, Debug)]
+pub struct RuleNode {
+    /// the node's key, sans trailing whitespace
+    pub key: String,
+
+    /// the node's value, sans trailing whitespace, if it has one
+    pub value: Option<String>,
+
+    /// where the node was first defined or, if a later definition replaced
+    /// its value, where that definition is
+    pub origin: Origin,
+
+    /// nodes nested beneath this one
+    pub children: Vec<RuleNode>,
+}
+
+/// Why a node could not be resolved.
+#[derive(Clone, PartialEq, Debug)]
+pub enum ResolveError {
+    /// no top-level node has the requested key
+    NotDefined {
+        /// the requested key
+        name: String,
+    },
+
+    /// the requested top-level node is removed by a `-<key>` node
+    Removed {
+        /// the requested key
+        name: String,
+
+        /// where the removal is
+        origin: Origin,
+    },
+
+    /// an `Inherits` node names a top-level node which does not exist
+    ParentNotDefined {
+        /// the named parent
+        parent: String,
+
+        /// where the `Inherits` node is
+        origin: Origin,
+    },
+
+    /// an `Inherits` node names a top-level node which has already been
+    /// inherited (which includes inheritance cycles)
+    AlreadyInherited {
+        /// the named parent
+        parent: String,
+
+        /// where the `Inherits` node is
+        origin: Origin,
+    },
+
+    /// a `-<key>` node which has no previously merged `<key>` to remove
+    NothingToRemove {
+        /// the key of the removal node, including the `-`
+        key: String,
+
+        /// where the removal node is
+        origin: Origin,
+    },
+}
+
+/// The merged top-level nodes of any number of files.
+#[derive(Clone, Debug, Default)]
+pub struct Rules {
+    nodes: Vec<RuleNode>,
+}
+
+impl Rules {
+    /// Create an empty set of rules.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Merge the nodes of `doc`, the contents of the file at `path`, into the
+    /// rules (files must be added in the order they are loaded in).
+    pub fn add_file(&mut self, path: impl Into<PathBuf>, doc: &str) {
+        let path = path.into();
+        let lines = span_lines_of(doc);
+        let tree = tree_of(doc, &lines);
+
+        let nodes = tree.nodes.iter()
+            .map(|node| rule_node_of(&path, doc, node))
+            .collect();
+
+        merge_partial(&mut self.nodes, nodes);
+    }
+
+    /// Merge `nodes`, which were derived from `doc`, the contents of the file
+    /// at `path`, into the rules as if they were top-level nodes (e.g. the
+    /// children of a map's `Rules` node).
+    pub fn add_nodes(&mut self, path: impl Into<PathBuf>, doc: &str, nodes: &[Node]) {
+        let path = path.into();
+        let nodes = nodes.iter()
+            .map(|node| rule_node_of(&path, doc, node))
+            .collect();
+
+        merge_partial(&mut self.nodes, nodes);
+    }
+
+    /// The merged, but unresolved, top-level nodes.
+    pub fn nodes(&self) -> &[RuleNode] {
+        &self.nodes
+    }
+
+    /// Resolve the final definition of the top-level node `name`.
+    pub fn resolve(&self, name: &str) -> Result<RuleNode, ResolveError> {
+        let removal_key = format!("-{}", name);
+        if let Some(removal) = self.find(&removal_key) {
+            return Err(ResolveError::Removed {
+                name: name.to_owned(),
+                origin: removal.origin.clone(),
+            });
+        }
+
+        let node = self.find(name)
+            .ok_or_else(|| ResolveError::NotDefined { name: name.to_owned() })?;
+
+        Ok(RuleNode {
+            children: self.resolve_inherits(&node.children, &[name.to_owned()])?,
+            ..node.clone()
+        })
+    }
+}
+
+// ----- external trait impls --------------------------------------------------
+
+impl fmt::Display for Origin {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.path.display(), self.line_idx + 1)
+    }
+}
+
+impl fmt::Display for ResolveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ResolveError::NotDefined { name } => write!(f, "{:?} is not defined", name),
+            ResolveError::Removed { name, origin } => {
+                write!(f, "{}: {:?} is removed", origin, name)
+            },
+            ResolveError::ParentNotDefined { parent, origin } => {
+                write!(f, "{}: parent {:?} is not defined", origin, parent)
+            },
+            ResolveError::AlreadyInherited { parent, origin } => {
+                write!(f, "{}: parent {:?} was already inherited (possibly via a cycle)", origin, parent)
+            },
+            ResolveError::NothingToRemove { key, origin } => {
+                write!(f, "{}: there is nothing for {:?} to remove", origin, key)
+            },
+        }
+    }
+}
+
+impl error::Error for ResolveError {}
+
+// ----- private implementation details ----------------------------------------
+
+impl Rules {
+    fn find(&self, key: &str) -> Option<&RuleNode> {
+        self.nodes.iter().find(|node| node.key == key)
+    }
+
+    /// `inherited` is the keys of every top-level node already inherited by
+    /// the node whose children are `nodes` (including that node itself)
+    fn resolve_inherits(
+        &self,
+        nodes: &[RuleNode],
+        inherited: &[String],
+    ) -> Result<Vec<RuleNode>, ResolveError> {
+        let mut inherited = inherited.to_vec();
+        let mut resolved = vec![];
+
+        for node in nodes {
+            if is_inherits(&node.key) {
+                let parent_name = node.value.as_deref().unwrap_or("");
+
+                let parent = self.find(parent_name)
+                    .ok_or_else(|| ResolveError::ParentNotDefined {
+                        parent: parent_name.to_owned(),
+                        origin: node.origin.clone(),
+                    })?;
+
+                if inherited.iter().any(|key| key == parent_name) {
+                    return Err(ResolveError::AlreadyInherited {
+                        parent: parent_name.to_owned(),
+                        origin: node.origin.clone(),
+                    });
+                }
+
+                inherited.push(parent_name.to_owned());
+
+                for parent_child in self.resolve_inherits(&parent.children, &inherited)? {
+                    self.merge_into_resolved(parent_child, &mut resolved, &inherited)?;
+                }
+            } else if let Some(removed_key) = node.key.strip_prefix('-') {
+                let resolved_count = resolved.len();
+                resolved.retain(|resolved_node: &RuleNode| resolved_node.key != removed_key);
+
+                if resolved.len() == resolved_count {
+                    return Err(ResolveError::NothingToRemove {
+                        key: node.key.clone(),
+                        origin: node.origin.clone(),
+                    });
+                }
+            } else {
+                self.merge_into_resolved(node.clone(), &mut resolved, &inherited)?;
+            }
+        }
+
+        Ok(resolved)
+    }
+
+    fn merge_into_resolved(
+        &self,
+        override_node: RuleNode,
+        resolved: &mut Vec<RuleNode>,
+        inherited: &[String],
+    ) -> Result<(), ResolveError> {
+        match resolved.iter_mut().find(|node| node.key == override_node.key) {
+            Some(existing) => {
+                merge_node(existing, override_node);
+                existing.children = self.resolve_inherits(&existing.children, inherited)?;
+            },
+            None => resolved.push(override_node),
+        }
+
+        Ok(())
+    }
+}
+
+fn rule_node_of(path: &Path, doc: &str, node: &Node) -> RuleNode {
+    RuleNode {
+        key: doc[node.key].trim_end().to_owned(),
+        value: node.value.map(|span| doc[span].trim_end().to_owned()),
+        origin: Origin {
+            path: path.to_path_buf(),
+            line_idx: node.line_idx,
+        },
+        children: node.children.iter()
+            .map(|child| rule_node_of(path, doc, child))
+            .collect(),
+    }
+}
+
+fn is_inherits(key: &str) -> bool {
+    key == "Inherits" || key.starts_with("Inherits@")
+}
+
+/// merge each of `overrides` into the node of `existing` with the same key,
+/// or append it if there is no such node
+fn merge_partial(existing: &mut Vec<RuleNode>, overrides: Vec<RuleNode>) {
+    for override_node in overrides {
+        match existing.iter_mut().find(|node| node.key == override_node.key) {
+            Some(existing_node) => merge_node(existing_node, override_node),
+            None => existing.push(override_node),
+        }
+    }
+}
+
+fn merge_node(existing: &mut RuleNode, override_node: RuleNode) {
+    if override_node.value.is_some() {
+        existing.value = override_node.value;
+        existing.origin = override_node.origin;
+    }
+
+    merge_partial(&mut existing.children, override_node.children);
+}
+
+// ----- tests -----------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `key: value @ path:line` for every node, indented by depth
+    fn describe(node: &RuleNode, depth: usize, out: &mut Vec<String>) {
+        out.push(format!(
+            "{}{}: {} @ {}",
+            "  ".repeat(depth),
+            node.key,
+            node.value.as_deref().unwrap_or(""),
+            node.origin,
+        ));
+
+        for child in &node.children {
+            describe(child, depth + 1, out);
+        }
+    }
+
+    #[test]
+    fn merges_files_resolves_inherits_and_applies_removals() {
+        // arrange
+        let mut rules = Rules::new();
+        rules.add_file("defaults.yaml", "^Soldier:\n\tHealth:\n\t\tHP: 50\n\tValued:\n\t\tCost: 100\n\tCrushable:\n");
+        rules.add_file("infantry.yaml", "E2:\n\tInherits: ^Soldier\n\tValued:\n\t\tCost: 200\n\t-Crushable:\n");
+        rules.add_file("balance.yaml", "E2:\n\tHealth:\n\t\tHP: 60\n");
+
+        // act
+        let resolved = rules.resolve("E2").unwrap();
+
+        // assert
+        let mut actual = vec![];
+        describe(&resolved, 0, &mut actual);
+
+        assert_eq!(
+            vec![
+                "E2:  @ infantry.yaml:1",
+                "  Health:  @ defaults.yaml:2",
+                "    HP: 60 @ balance.yaml:3",
+                "  Valued:  @ defaults.yaml:4",
+                "    Cost: 200 @ infantry.yaml:4",
+            ],
+            actual,
+        );
+    }
+
+    #[test]
+    fn errors() {
+        // arrange
+        let mut rules = Rules::new();
+        rules.add_file("a.yaml", "^A:\n\tInherits: ^B\n^B:\n\tInherits: ^A\nC:\n\tInherits: ^Nope\nD:\n\t-Health:\n-E:\n");
+
+        // act & assert
+        assert_eq!(
+            Err(ResolveError::AlreadyInherited {
+                parent: "^A".into(),
+                origin: Origin { path: "a.yaml".into(), line_idx: 3 },
+            }),
+            rules.resolve("^A"),
+        );
+
+        assert_eq!(
+            Err(ResolveError::ParentNotDefined {
+                parent: "^Nope".into(),
+                origin: Origin { path: "a.yaml".into(), line_idx: 5 },
+            }),
+            rules.resolve("C"),
+        );
+
+        assert_eq!(
+            Err(ResolveError::NothingToRemove {
+                key: "-Health".into(),
+                origin: Origin { path: "a.yaml".into(), line_idx: 7 },
+            }),
+            rules.resolve("D"),
+        );
+
+        assert_eq!(
+            Err(ResolveError::Removed {
+                name: "E".into(),
+                origin: Origin { path: "a.yaml".into(), line_idx: 8 },
+            }),
+            rules.resolve("E"),
+        );
+    }
+}
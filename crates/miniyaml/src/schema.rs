@@ -0,0 +1,211 @@
+// oraide - tools for OpenRA-based mod/game development
+// get the source code at https://github.com/Phrohdoh/oraide
+//
+// copyright (c)
+// - 2020 Taryn "Phrohdoh" Hill
+
+#![deny(missing_docs)]
+
+//! This [module] exposes items used to describe what the engine a mod runs on
+//! defines (its traits, projectiles, and warheads, and which fields name
+//! definitions), so that the names a mod uses can be checked against them.
+//!
+//! With the `json` feature enabled, a schema can be read from a JSON document
+//! in which every member is optional:
+//!
+//! ```json
+//! {
+//!     "traits": {
+//!         "Armament": ["Weapon", "LocalOffset"],
+//!         "Health": ["HP"]
+//!     },
+//!     "projectiles": ["Bullet", "Missile"],
+//!     "warheads": ["SpreadDamage", "CreateEffect"],
+//!     "references": {
+//!         "Inherits": null,
+//!         "Weapon": "weapon",
+//!         "Image": "image",
+//!         "Actor": "actor"
+//!     }
+//! }
+//! ```
+//!
+//! where each reference field names an `"actor"`, a `"weapon"`, an `"image"`
+//! (i.e. a sequences definition), or, if `null`, a definition of the same
+//! kind as the file the field is in.
+//!
+//! [module]: https://doc.rust-lang.org/book/ch07-02-defining-modules-to-control-scope-and-privacy.html
+
+use {
+    std::{
+        collections::BTreeMap,
+    },
+    crate::{
+        ReferenceField,
+    },
+};
+
+#[cfg(feature = "json")]
+use {
+    serde_json::Value,
+    crate::DefinitionKind,
+};
+
+// ----- public interface ------------------------------------------------------
+
+/// What an engine defines, each kind of which is `None` if it isn't
+/// described.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Schema {
+    /// the fields of each trait, keyed by the trait's name as written in
+    /// rules (i.e. without its `Info` suffix, e.g. `Armament`)
+    pub traits: Option<BTreeMap<String, Vec<String>>>,
+
+    /// the name of each projectile type (e.g. `Bullet`)
+    pub projectiles: Option<Vec<String>>,
+
+    /// the name of each warhead type (e.g. `SpreadDamage`)
+    pub warheads: Option<Vec<String>>,
+
+    /// the fields whose values name definitions (e.g. `Weapon`)
+    pub references: Option<Vec<ReferenceField>>,
+}
+
+impl Schema {
+    /// Read `text`, a schema's JSON document, returning a description of what
+    /// is wrong with it if it isn't a schema.
+    #[cfg(feature = "json")]
+    pub fn from_json(text: &str) -> Result<Self, String> {
+        let members = match serde_json::from_str(text) {
+            Ok(Value::Object(members)) => members,
+            Ok(_) => return Err("expected an object".to_owned()),
+            Err(err) => return Err(err.to_string()),
+        };
+
+        let mut schema = Self::default();
+
+        for (key, value) in &members {
+            match key.as_str() {
+                "traits" => {
+                    let traits = value.as_object()
+                        .ok_or_else(|| expected(key, "an object"))?;
+
+                    let mut fields_by_trait = BTreeMap::new();
+                    for (trait_name, fields) in traits {
+                        let fields = names_of(fields)
+                            .ok_or_else(|| expected(&format!("traits.{}", trait_name), "an array of strings"))?;
+
+                        fields_by_trait.insert(trait_name.clone(), fields);
+                    }
+
+                    schema.traits = Some(fields_by_trait);
+                },
+                "projectiles" => {
+                    schema.projectiles = Some(names_of(value)
+                        .ok_or_else(|| expected(key, "an array of strings"))?);
+                },
+                "warheads" => {
+                    schema.warheads = Some(names_of(value)
+                        .ok_or_else(|| expected(key, "an array of strings"))?);
+                },
+                "references" => {
+                    let references = value.as_object()
+                        .ok_or_else(|| expected(key, "an object"))?;
+
+                    let mut fields = vec![];
+                    for (field_key, kind) in references {
+                        let kind = match kind {
+                            Value::Null => None,
+                            Value::String(kind) if kind == "actor" => Some(DefinitionKind::Actor),
+                            Value::String(kind) if kind == "weapon" => Some(DefinitionKind::Weapon),
+                            Value::String(kind) if kind == "image" => Some(DefinitionKind::Sequence),
+                            _ => return Err(expected(
+                                &format!("references.{}", field_key),
+                                "\"actor\", \"weapon\", \"image\", or null",
+                            )),
+                        };
+
+                        fields.push(ReferenceField { key: field_key.clone(), kind });
+                    }
+
+                    schema.references = Some(fields);
+                },
+                _ => return Err(format!("unknown key {:?}", key)),
+            }
+        }
+
+        Ok(schema)
+    }
+
+    /// The name of every trait, if traits are described.
+    pub fn trait_names(&self) -> Option<impl Iterator<Item = &str>> {
+        self.traits.as_ref()
+            .map(|traits| traits.keys().map(String::as_str))
+    }
+}
+
+// ----- private implementation details ----------------------------------------
+
+#[cfg(feature = "json")]
+fn expected(key: &str, expected: &str) -> String {
+    format!("value of {:?} must be {}", key, expected)
+}
+
+/// the strings of `value`, if it is an array of strings
+#[cfg(feature = "json")]
+fn names_of(value: &Value) -> Option<Vec<String>> {
+    value.as_array()?
+        .iter()
+        .map(|name| name.as_str().map(str::to_owned))
+        .collect()
+}
+
+// ----- tests -----------------------------------------------------------------
+
+#[cfg(all(test, feature = "json"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_every_kind() {
+        // arrange
+        let text = r#"{
+            "traits": { "Armament": ["Weapon", "LocalOffset"], "Health": ["HP"] },
+            "warheads": ["SpreadDamage"],
+            "references": { "Inherits": null, "Weapon": "weapon" }
+        }"#;
+
+        // act
+        let schema = Schema::from_json(text).unwrap();
+
+        // assert
+        assert_eq!(
+            Some(vec!["Armament", "Health"]),
+            schema.trait_names().map(|names| names.collect::<Vec<_>>()),
+        );
+        assert_eq!(Some(&vec!["HP".to_owned()]), schema.traits.as_ref().unwrap().get("Health"));
+        assert_eq!(None, schema.projectiles);
+        assert_eq!(Some(vec!["SpreadDamage".to_owned()]), schema.warheads);
+        assert_eq!(
+            Some(vec![
+                ReferenceField { key: "Inherits".to_owned(), kind: None },
+                ReferenceField { key: "Weapon".to_owned(), kind: Some(DefinitionKind::Weapon) },
+            ]),
+            schema.references,
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(Err("expected an object".to_owned()), Schema::from_json("[]"));
+        assert_eq!(Err("unknown key \"warhead\"".to_owned()), Schema::from_json(r#"{ "warhead": [] }"#));
+        assert_eq!(
+            Err("value of \"traits.Health\" must be an array of strings".to_owned()),
+            Schema::from_json(r#"{ "traits": { "Health": "HP" } }"#),
+        );
+        assert_eq!(
+            Err("value of \"references.Weapon\" must be \"actor\", \"weapon\", \"image\", or null".to_owned()),
+            Schema::from_json(r#"{ "references": { "Weapon": "weapons" } }"#),
+        );
+    }
+}
@@ -0,0 +1,299 @@
+// oraide - tools for OpenRA-based mod/game development
+// get the source code at https://github.com/Phrohdoh/oraide
+//
+// copyright (c)
+// - 2020 Taryn "Phrohdoh" Hill
+
+#![deny(missing_docs)]
+
+//! This [module] exposes items used to read the sprite sequences of an image
+//! (a top-level node of a sequences file), once its definitions have been
+//! merged and its `Inherits` resolved by [`Rules`].
+//!
+//! Each child of an image is a sequence, except `Defaults`, whose fields (and
+//! value) apply to every sequence which doesn't set them itself:
+//!
+//! ```text
+//! e1:
+//!     Defaults:
+//!         Facings: 8
+//!     stand:
+//!     run:
+//!         Start: 16
+//!         Length: 6
+//!     die1: e1death
+//!         Length: *
+//!         Facings: 1
+//! ```
+//!
+//! [module]: https://doc.rust-lang.org/book/ch07-02-defining-modules-to-control-scope-and-privacy.html
+//! [`Rules`]: struct.Rules.html
+
+use {
+    std::{
+        collections::BTreeMap,
+        convert::TryFrom,
+    },
+    crate::{
+        parse_ints,
+        Origin,
+        ResolveError,
+        RuleNode,
+        Rules,
+    },
+};
+
+// ----- public interface ------------------------------------------------------
+
+/// The sequences of an image.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Image {
+    /// the image's name (e.g. `e1`)
+    pub name: String,
+
+    /// its sequences, keyed by their names
+    pub sequences: BTreeMap<String, Sequence>,
+}
+
+/// How many frames, per facing, a sequence has.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum SequenceLength {
+    /// exactly this many
+    Frames(i64),
+
+    /// every frame from `Start` to the end of the file (written `*`)
+    All,
+}
+
+/// A sequence (i.e. an animation) of an image, with its `Defaults` applied.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Sequence {
+    /// the sequence's name (e.g. `stand`), or, for a part of a `Combine`, the
+    /// key of said part
+    pub name: String,
+
+    /// the file its frames are read from (without the extension which the
+    /// engine may add), which is the image's name unless given
+    pub filename: String,
+
+    /// index of its first frame in said file
+    pub start: i64,
+
+    /// how many frames it has per facing
+    pub length: SequenceLength,
+
+    /// the indices, in said file, of its frames, if given explicitly (rather
+    /// than being the consecutive frames from `start`)
+    pub frames: Option<Vec<i64>>,
+
+    /// how many directions it is drawn in (the engine accepts negative
+    /// counts, which reverse the order of facings, so this is the absolute
+    /// value)
+    pub facings: u64,
+
+    /// milliseconds between its frames
+    pub tick: i64,
+
+    /// `true` if the engine derives its file from the map's tileset (through
+    /// `UseTilesetExtension`, `UseTilesetCode`, or `TilesetFilenames`), so
+    /// `filename` isn't the name of a file
+    pub is_tileset_specific: bool,
+
+    /// the parts which, if given, the sequence's frames are combined from
+    /// (rather than read from a single file)
+    pub combine: Vec<Sequence>,
+
+    /// where the sequence was defined
+    pub origin: Origin,
+}
+
+impl Image {
+    /// Resolve the image `name` of `sequences` (the merged sequences files of
+    /// a mod).
+    pub fn resolve(sequences: &Rules, name: &str) -> Result<Self, ResolveError> {
+        let image = sequences.resolve(name)?;
+        let defaults = image.children.iter()
+            .find(|child| child.key == "Defaults");
+
+        let sequences = image.children.iter()
+            .filter(|child| child.key != "Defaults")
+            .map(|child| (child.key.clone(), sequence_of(child, defaults, name)))
+            .collect();
+
+        Ok(Self {
+            name: name.to_owned(),
+            sequences,
+        })
+    }
+
+    /// The sequence `name`, if the image defines it.
+    pub fn sequence(&self, name: &str) -> Option<&Sequence> {
+        self.sequences.get(name)
+    }
+}
+
+impl Sequence {
+    /// How many frames its file must have for every frame of the sequence to
+    /// exist, i.e. one more than the index of its last frame (`Start +
+    /// Length * Facings`, unless its `Frames` are explicit).
+    ///
+    /// A sequence whose `Length` is `*` needs only its first frame, and counts
+    /// too large to be represented are `i64::MAX`.
+    pub fn frame_count_needed(&self) -> i64 {
+        if let Some(frames) = &self.frames {
+            return frames.iter().max().map_or(0, |last_frame| last_frame.saturating_add(1));
+        }
+
+        match self.length {
+            SequenceLength::Frames(length) => {
+                let facings = i64::try_from(self.facings).unwrap_or(i64::MAX);
+                self.start.saturating_add(length.saturating_mul(facings))
+            },
+            SequenceLength::All => self.start.saturating_add(1),
+        }
+    }
+}
+
+// ----- private implementation details ----------------------------------------
+
+const DEFAULT_TICK: i64 = 40;
+
+/// the sequence defined by `node`, with the fields of `opt_defaults` it
+/// doesn't set itself, whose file is `default_filename` unless given
+fn sequence_of(node: &RuleNode, opt_defaults: Option<&RuleNode>, default_filename: &str) -> Sequence {
+    let field_of = |key: &str| -> Option<&str> {
+        let own_field = node.children.iter().find(|child| child.key == key);
+        let default_field = || opt_defaults
+            .and_then(|defaults| defaults.children.iter().find(|child| child.key == key));
+
+        own_field.or_else(default_field)
+            .and_then(|field| field.value.as_deref())
+    };
+
+    let int_of = |key: &str, default: i64| field_of(key)
+        .and_then(|txt| txt.parse().ok())
+        .unwrap_or(default);
+
+    let filename = field_of("Filename")
+        .or(node.value.as_deref())
+        .or_else(|| opt_defaults.and_then(|defaults| defaults.value.as_deref()))
+        .unwrap_or(default_filename)
+        .to_owned();
+
+    let length = match field_of("Length") {
+        Some("*") => SequenceLength::All,
+        Some(txt) => SequenceLength::Frames(txt.parse().unwrap_or(1)),
+        None => SequenceLength::Frames(1),
+    };
+
+    let is_tileset_specific = field_of("TilesetFilenames").is_some()
+        || ["UseTilesetExtension", "UseTilesetCode"].iter()
+            .any(|key| field_of(key).is_some_and(|txt| txt.eq_ignore_ascii_case("true")));
+
+    // the parts of a combined sequence don't inherit its defaults
+    let combine = node.children.iter()
+        .filter(|child| child.key == "Combine")
+        .flat_map(|combine| combine.children.iter())
+        .map(|part| sequence_of(part, None, &part.key))
+        .collect();
+
+    Sequence {
+        name: node.key.clone(),
+        filename,
+        start: int_of("Start", 0),
+        length,
+        frames: field_of("Frames").and_then(parse_ints),
+        facings: int_of("Facings", 1).unsigned_abs(),
+        tick: int_of("Tick", DEFAULT_TICK),
+        is_tileset_specific,
+        combine,
+        origin: node.origin.clone(),
+    }
+}
+
+// ----- tests -----------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn applies_defaults_and_inheritance() {
+        // arrange
+        let mut sequences = Rules::new();
+        sequences.add_file("sequences/infantry.yaml", &[
+            "^Infantry:\n",
+            "\tDefaults:\n",
+            "\t\tFacings: 8\n",
+            "\tstand:\n",
+            "\tdie1: deaths\n",
+            "\t\tLength: *\n",
+            "\t\tFacings: -1\n",
+            "e1:\n",
+            "\tInherits: ^Infantry\n",
+            "\trun:\n",
+            "\t\tStart: 16\n",
+            "\t\tLength: 6\n",
+            "\t\tTick: 80\n",
+            "\t-die1:\n",
+            "\ticon:\n",
+            "\t\tCombine:\n",
+            "\t\t\te1icon:\n",
+            "\t\t\t\tLength: 2\n",
+            "\t\t\t0:\n",
+            "\t\t\t\tFilename: chevron\n",
+        ].concat());
+
+        // act
+        let e1 = Image::resolve(&sequences, "e1").unwrap();
+        let infantry = Image::resolve(&sequences, "^Infantry").unwrap();
+
+        // assert
+        assert_eq!(vec!["icon", "run", "stand"], e1.sequences.keys().collect::<Vec<_>>());
+
+        let run = e1.sequence("run").unwrap();
+        assert_eq!(
+            ("e1", 16, SequenceLength::Frames(6), 8, 80),
+            (run.filename.as_str(), run.start, run.length, run.facings, run.tick),
+        );
+
+        let stand = e1.sequence("stand").unwrap();
+        assert_eq!((0, SequenceLength::Frames(1), 8), (stand.start, stand.length, stand.facings));
+
+        let combine = &e1.sequence("icon").unwrap().combine;
+        assert_eq!(
+            vec![("e1icon", SequenceLength::Frames(2), 1), ("chevron", SequenceLength::Frames(1), 1)],
+            combine.iter()
+                .map(|part| (part.filename.as_str(), part.length, part.facings))
+                .collect::<Vec<_>>(),
+        );
+
+        let die1 = infantry.sequence("die1").unwrap();
+        assert_eq!(("deaths", SequenceLength::All, 1), (die1.filename.as_str(), die1.length, die1.facings));
+
+        assert_eq!((64, 1, 8), (run.frame_count_needed(), die1.frame_count_needed(), stand.frame_count_needed()));
+    }
+
+    #[test]
+    fn overflowing_frame_counts() {
+        // arrange
+        let mut sequences = Rules::new();
+        sequences.add_file("sequences.yaml", &[
+            "e1:\n",
+            "\trun:\n",
+            "\t\tStart: 16\n",
+            "\t\tLength: 4611686018427387904\n",
+            "\t\tFacings: -9223372036854775808\n",
+            "\tdie1:\n",
+            "\t\tFrames: 9223372036854775807\n",
+        ].concat());
+
+        // act
+        let e1 = Image::resolve(&sequences, "e1").unwrap();
+
+        // assert
+        let run = e1.sequence("run").unwrap();
+        assert_eq!((1 << 63, i64::MAX), (run.facings, run.frame_count_needed()));
+        assert_eq!(i64::MAX, e1.sequence("die1").unwrap().frame_count_needed());
+    }
+}
@@ -11,9 +11,13 @@
 //! to supply their own implementation, and a convenience function
 //! (`span_lines_of`) which uses an implementor of said trait, [DefaultSpanner].
 //!
+//! [DefaultSpanner] can be configured, via [SpannerOptions], to match how
+//! different OpenRA releases interpret MiniYaml.
+//!
 //! [module]: https://doc.rust-lang.org/book/ch07-02-defining-modules-to-control-scope-and-privacy.html
 //! [Spanner]: trait.Spanner.html
 //! [DefaultSpanner]: struct.DefaultSpanner.html
+//! [SpannerOptions]: struct.SpannerOptions.html
 
 use {
     std::{
@@ -23,6 +27,7 @@ use {
     crate::{
         AbsByteIdx,
         AbsByteIdxSpan,
+        TextEdit,
     },
 };
 
@@ -42,6 +47,7 @@ pub trait Spanner {
 /// [`raw`]: struct.SpannedLine.html#structfield.raw
 #[derive(PartialEq)]
 #[cfg_attr(test, derive(Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct SpannedLine {
     /// absolutely-positioned span of the entire line
     /// (spans over all the other fields)
@@ -68,87 +74,290 @@ pub struct SpannedLine {
     pub term: Option<AbsByteIdxSpan>,
 }
 
-/// Derive spanned-lines from `doc` via [`DefaultSpanner`].
+impl SpannedLine {
+    /// `true` if the line's comment starts at its first non-whitespace
+    /// character, i.e., the line is nothing but (possibly indented) comment.
+    pub fn is_comment_only(&self) -> bool {
+        // without indentation, the first non-whitespace character starts the
+        // first component (which may follow a stripped byte-order-mark, so
+        // isn't necessarily at the start of `raw`)
+        let first_non_ws_abx = match self.indent {
+            Some(indent) => Some(indent.end),
+            None => [self.key, self.key_sep, self.value, self.comment].iter()
+                .flatten()
+                .map(|span| span.start.0)
+                .min()
+                .map(AbsByteIdx),
+        };
+
+        self.comment.is_some() && self.comment.map(|comment| comment.start) == first_non_ws_abx
+    }
+}
+
+/// How [`DefaultSpanner`] finds the separator between a line's key and value.
 ///
 /// [`DefaultSpanner`]: struct.DefaultSpanner.html
-pub fn span_lines_of(doc: &str) -> SpannedLines {
-    let mut spanner = DefaultSpanner::new(doc);
-    spanner.span_lines()
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum KeySepStyle {
+    /// the first `:` of the line (`Foo:Bar` is key `Foo`, value `Bar`)
+    FirstColon,
+
+    /// the first `:` followed by whitespace or the end of the line
+    /// (`Foo:Bar` is key `Foo:Bar` and has no value)
+    ColonSpace,
 }
 
-// ----- private implementation details ----------------------------------------
-
-type SpannedLines = Vec<SpannedLine>;
-
-/// Our implementation of MiniYaml spanning is a 2-stage process:
+/// Options which control how [`DefaultSpanner`] interprets a document.
 ///
-/// raw UTF-8 text -> 0+ `RawAndTerm`s -> 0+ `SpannedLine`
+/// The [`Default`] options are those of the current OpenRA release.
 ///
-/// This type, `RawAndTerm`, is a private, intermediate type used to make the
-/// implementation easier to comprehend than it would be with tuples everywhere.
-struct RawAndTerm {
-    raw: AbsByteIdxSpan,
-    term: Option<AbsByteIdxSpan>,
+/// [`DefaultSpanner`]: struct.DefaultSpanner.html
+/// [`Default`]: https://doc.rust-lang.org/std/default/trait.Default.html
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct SpannerOptions {
+    /// the character which starts a comment (default: `#`)
+    pub comment_char: char,
+
+    /// whether a [`comment_char`] immediately preceded by `\` is part of the
+    /// line's text rather than the start of a comment (default: `true`)
+    ///
+    /// [`comment_char`]: struct.SpannerOptions.html#structfield.comment_char
+    pub honor_escaped_comments: bool,
+
+    /// how the key-separator is found (default: [`KeySepStyle::FirstColon`])
+    ///
+    /// [`KeySepStyle::FirstColon`]: enum.KeySepStyle.html#variant.FirstColon
+    pub key_sep_style: KeySepStyle,
+
+    /// whether a leading UTF-8 byte-order-mark is excluded from the first
+    /// line's components (it is always included in the line's `raw` span)
+    /// (default: `true`)
+    pub strip_bom: bool,
 }
 
-impl RawAndTerm {
-    fn logical_line_end_abx(&self) -> AbsByteIdx {
-        self.term.map(|term| term.start)
-            .unwrap_or(self.raw.end)
+impl Default for SpannerOptions {
+    fn default() -> Self {
+        Self {
+            comment_char: '#',
+            honor_escaped_comments: true,
+            key_sep_style: KeySepStyle::FirstColon,
+            strip_bom: true,
+        }
     }
 }
 
-struct DefaultSpanner<'doc> {
+/// The [`Spanner`] used by [`span_lines_of`], configurable via
+/// [`SpannerOptions`].
+///
+/// [`Spanner`]: trait.Spanner.html
+/// [`span_lines_of`]: fn.span_lines_of.html
+/// [`SpannerOptions`]: struct.SpannerOptions.html
+pub struct DefaultSpanner<'doc> {
     _doc: &'doc str,
     _doc_len_bytes: usize,
+    options: SpannerOptions,
 }
 
 impl<'doc> DefaultSpanner<'doc> {
-    fn new(doc: &'doc str) -> Self {
+    /// Create a spanner of `doc` with the [`Default`] options.
+    ///
+    /// [`Default`]: struct.SpannerOptions.html#impl-Default
+    pub fn new(doc: &'doc str) -> Self {
+        Self::with_options(doc, SpannerOptions::default())
+    }
+
+    /// Create a spanner of `doc` with the given `options`.
+    pub fn with_options(doc: &'doc str, options: SpannerOptions) -> Self {
         Self {
             _doc_len_bytes: doc.len(),
             _doc: doc,
+            options,
         }
     }
 
-    #[inline(always)]
-    fn is_empty(&self) -> bool {
-        self._doc_len_bytes == 0
+    /// The options this spanner was created with.
+    pub fn options(&self) -> SpannerOptions {
+        self.options
     }
 
-    #[inline(always)]
-    fn iter(&self) -> Peekable<CharIndices> {
-        let ch_idx_iter = self._doc.char_indices().peekable();
-        ch_idx_iter
+    /// Lazily derive spanned-lines, see [`iter_spanned_lines_of`].
+    ///
+    /// [`iter_spanned_lines_of`]: fn.iter_spanned_lines_of.html
+    pub fn spanned_lines(&self) -> SpannedLineIter<'doc> {
+        SpannedLineIter {
+            spanner: DefaultSpanner::with_options(self._doc, self.options),
+            raw_and_terms: self.raw_and_terms(),
+        }
     }
+}
 
-    #[track_caller]
-    fn text_at(&self, abs_start_idx_li: usize, abs_end_idx_he: usize) -> &str {
-        assert!(
-            abs_end_idx_he <= self._doc_len_bytes,
-            "attempted to read past end of doc's contents: [{}..{})",
-            abs_start_idx_li,
-            abs_end_idx_he,
-        );
+/// Derive spanned-lines from `doc` via [`DefaultSpanner`].
+///
+/// [`DefaultSpanner`]: struct.DefaultSpanner.html
+pub fn span_lines_of(doc: &str) -> SpannedLines {
+    let mut spanner = DefaultSpanner::new(doc);
+    spanner.span_lines()
+}
 
-        &self._doc[abs_start_idx_li..abs_end_idx_he]
+/// Lazily derive spanned-lines from `doc` via [`DefaultSpanner`].
+///
+/// Unlike [`span_lines_of`], lines are spanned one at a time, as the iterator
+/// is advanced, in a single pass over `doc` and without allocating.
+///
+/// [`DefaultSpanner`]: struct.DefaultSpanner.html
+/// [`span_lines_of`]: fn.span_lines_of.html
+pub fn iter_spanned_lines_of(doc: &str) -> SpannedLineIter<'_> {
+    DefaultSpanner::new(doc).spanned_lines()
+}
+
+/// An iterator which spans the lines of a document as it is advanced,
+/// created by [`iter_spanned_lines_of`].
+///
+/// [`iter_spanned_lines_of`]: fn.iter_spanned_lines_of.html
+pub struct SpannedLineIter<'doc> {
+    spanner: DefaultSpanner<'doc>,
+    raw_and_terms: RawAndTerms<'doc>,
+}
+
+/// Update `lines`, which were spanned from the document before `edit` was
+/// applied, so that they are identical to `span_lines_of(doc)`, where `doc` is
+/// the document after `edit` was applied.
+///
+/// Only the lines touched by `edit` are re-spanned, the spans of every line
+/// after them are shifted by the change in length.
+pub fn respan_lines_of(doc: &str, lines: &mut SpannedLines, edit: &TextEdit) {
+    respan_lines_with_options(doc, lines, edit, SpannerOptions::default());
+}
+
+/// Like [`respan_lines_of`], for `lines` which were spanned by a
+/// [`DefaultSpanner`] created with `options`.
+///
+/// [`respan_lines_of`]: fn.respan_lines_of.html
+/// [`DefaultSpanner`]: struct.DefaultSpanner.html
+pub fn respan_lines_with_options(
+    doc: &str,
+    lines: &mut SpannedLines,
+    edit: &TextEdit,
+    options: SpannerOptions,
+) {
+    if lines.is_empty() {
+        *lines = DefaultSpanner::with_options(doc, options).span_lines();
+        return;
     }
 
-    fn lines(&self) -> Vec<RawAndTerm> {
-        if self.is_empty() {
-            return vec![];
+    let (edit_start_abs_idx, edit_end_abs_idx) = edit.replaced.into();
+
+    // a line-terminator can be merged with, or split from, the line on either
+    // side of an edit (e.g. inserting `\n` immediately after a `\r`), so the
+    // lines adjacent to those the edit touches are re-spanned as well
+    let first_line_idx = line_idx_containing(lines, edit_start_abs_idx)
+        .saturating_sub(1);
+    let last_line_idx = (line_idx_containing(lines, edit_end_abs_idx) + 1)
+        .min(lines.len() - 1);
+
+    let len_delta = edit.len_delta();
+    let region_start_abs_idx = lines[first_line_idx].raw.start.0;
+    let region_end_abs_idx = (lines[last_line_idx].raw.end.0 as isize + len_delta) as usize;
+
+    // the region is spanned as if it were a document of its own, but only the
+    // document's first line can start with a byte-order-mark
+    let region_options = SpannerOptions {
+        strip_bom: options.strip_bom && region_start_abs_idx == 0,
+        ..options
+    };
+
+    let region_txt = &doc[region_start_abs_idx..region_end_abs_idx];
+    let mut respanned_lines = DefaultSpanner::with_options(region_txt, region_options)
+        .span_lines();
+
+    for line in &mut respanned_lines {
+        line.shift_by(region_start_abs_idx as isize);
+    }
+
+    for line in &mut lines[last_line_idx + 1..] {
+        line.shift_by(len_delta);
+    }
+
+    lines.splice(first_line_idx..=last_line_idx, respanned_lines);
+}
+
+// ----- private implementation details ----------------------------------------
+
+type SpannedLines = Vec<SpannedLine>;
+
+/// the UTF-8 encoded byte-order-mark
+const BOM: char = '\u{FEFF}';
+
+impl SpannedLine {
+    /// move every span of this line by `delta` bytes
+    fn shift_by(&mut self, delta: isize) {
+        let shift_span = |span: &mut AbsByteIdxSpan| {
+            span.start = AbsByteIdx((span.start.0 as isize + delta) as usize);
+            span.end = AbsByteIdx((span.end.0 as isize + delta) as usize);
+        };
+
+        shift_span(&mut self.raw);
+
+        let mut opt_spans = [
+            &mut self.indent,
+            &mut self.key,
+            &mut self.key_sep,
+            &mut self.value,
+            &mut self.comment,
+            &mut self.term,
+        ];
+
+        for span in opt_spans.iter_mut().filter_map(|opt_span| opt_span.as_mut()) {
+            shift_span(span);
         }
+    }
+}
 
-        let mut ret = vec![];
+/// index of the line whose `raw` span contains `abs_idx` (or the last line, if
+/// `abs_idx` is the end of the document)
+fn line_idx_containing(lines: &[SpannedLine], abs_idx: usize) -> usize {
+    match lines.binary_search_by_key(&abs_idx, |line| line.raw.start.0) {
+        Ok(line_idx) => line_idx,
+        Err(next_line_idx) => next_line_idx - 1,
+    }
+}
 
-        let mut ch_idx_iter = self.iter();
-        let mut line_start_abx = 0.into(); // start at beginning of document
+/// Our implementation of MiniYaml spanning is a 2-stage process:
+///
+/// raw UTF-8 text -> 0+ `RawAndTerm`s -> 0+ `SpannedLine`
+///
+/// This type, `RawAndTerm`, is a private, intermediate type used to make the
+/// implementation easier to comprehend than it would be with tuples everywhere.
+struct RawAndTerm {
+    raw: AbsByteIdxSpan,
+    term: Option<AbsByteIdxSpan>,
+}
+
+impl RawAndTerm {
+    fn logical_line_end_abx(&self) -> AbsByteIdx {
+        self.term.map(|term| term.start)
+            .unwrap_or(self.raw.end)
+    }
+}
+
+/// The first stage of spanning (see [`RawAndTerm`]), performed lazily in a
+/// single pass over the document's `char`s.
+///
+/// [`RawAndTerm`]: struct.RawAndTerm.html
+struct RawAndTerms<'doc> {
+    ch_idx_iter: Peekable<CharIndices<'doc>>,
+    line_start_abx: AbsByteIdx,
+}
+
+impl Iterator for RawAndTerms<'_> {
+    type Item = RawAndTerm;
 
-        while let Some((ch_start_abs_idx, ch)) = ch_idx_iter.next() {
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((ch_start_abs_idx, ch)) = self.ch_idx_iter.next() {
             let ch_start_abx = AbsByteIdx(ch_start_abs_idx);
             let ch_end_abx = (ch_start_abs_idx + ch.len_utf8()).into();
 
-            let opt_next_tup2 = ch_idx_iter.peek()
+            let opt_next_tup2 = self.ch_idx_iter.peek()
                 .map(|tup2| tup2.to_owned());
 
             let opt_term_span = match ch {
@@ -156,7 +365,7 @@ impl<'doc> DefaultSpanner<'doc> {
                 '\r' => match opt_next_tup2 {
                     Some((lf_start_abs_idx, '\n')) => {
                         // advance over the `\n`
-                        ch_idx_iter.next();
+                        self.ch_idx_iter.next();
 
                         let lf_end_idx = lf_start_abs_idx + '\n'.len_utf8();
                         let end_abx = lf_end_idx.into();
@@ -172,29 +381,29 @@ impl<'doc> DefaultSpanner<'doc> {
                 // terminating the current line, with another line following
                 (Some(term_span), Some(_)) => {
                     let raw_span = AbsByteIdxSpan::from((
-                        line_start_abx,
+                        self.line_start_abx,
                         term_span.end,
                     ));
 
-                    line_start_abx = term_span.end;
+                    self.line_start_abx = term_span.end;
 
                     (raw_span, term_span.into())
                 },
                 // end-of-document with trailing line-terminator
                 (Some(term_span), None) => {
-                    let raw_span = (line_start_abx, term_span.end).into();
+                    let raw_span = (self.line_start_abx, term_span.end).into();
                     (raw_span, term_span.into())
                 },
                 // abrupt end-of-document (no trailing line-terminator)
                 (None, None) => {
-                    let raw_span = (line_start_abx, ch_end_abx).into();
+                    let raw_span = (self.line_start_abx, ch_end_abx).into();
                     (raw_span, None)
                 }
                 // not a line-terminator, followed by something
                 (None, Some(_)) => continue,
             };
 
-            ret.push(
+            return Some(
                 RawAndTerm {
                     raw: raw_span,
                     term: term_span,
@@ -202,7 +411,39 @@ impl<'doc> DefaultSpanner<'doc> {
             );
         }
 
-        ret
+        None
+    }
+}
+
+impl<'doc> DefaultSpanner<'doc> {
+    #[inline(always)]
+    fn iter(&self) -> Peekable<CharIndices<'doc>> {
+        let ch_idx_iter = self._doc.char_indices().peekable();
+        ch_idx_iter
+    }
+
+    #[track_caller]
+    fn text_at(&self, abs_start_idx_li: usize, abs_end_idx_he: usize) -> &'doc str {
+        assert!(
+            abs_end_idx_he <= self._doc_len_bytes,
+            "attempted to read past end of doc's contents: [{}..{})",
+            abs_start_idx_li,
+            abs_end_idx_he,
+        );
+
+        &self._doc[abs_start_idx_li..abs_end_idx_he]
+    }
+
+    #[cfg(test)]
+    fn lines(&self) -> Vec<RawAndTerm> {
+        self.raw_and_terms().collect()
+    }
+
+    fn raw_and_terms(&self) -> RawAndTerms<'doc> {
+        RawAndTerms {
+            ch_idx_iter: self.iter(),
+            line_start_abx: 0.into(), // start at beginning of document
+        }
     }
 
     /// if spanning `hello\n`, `line_txt` would be `hello`
@@ -212,6 +453,7 @@ impl<'doc> DefaultSpanner<'doc> {
         line_txt: &'doc str,
     ) -> SpannedLine {
         let RawAndTerm { raw, term } = raw_and_term;
+        let options = self.options;
 
         let mut ret = SpannedLine {
             raw,
@@ -223,12 +465,19 @@ impl<'doc> DefaultSpanner<'doc> {
             term,
         };
 
+        // a byte-order-mark can only be at the very start of the document
+        let (line_start_abx, line_txt) = match line_txt.strip_prefix(BOM) {
+            Some(txt_after_bom) if options.strip_bom && raw.start.0 == 0 => {
+                (AbsByteIdx(BOM.len_utf8()), txt_after_bom)
+            },
+            _ => (raw.start, line_txt),
+        };
+
         let is_line_empty = line_txt.is_empty();
         if is_line_empty {
             return ret;
         }
 
-        let line_start_abx = raw.start;
         let logical_line_end_abx = raw_and_term.logical_line_end_abx();
 
         let is_indent_only = line_txt.trim().is_empty();
@@ -288,7 +537,7 @@ impl<'doc> DefaultSpanner<'doc> {
 
         // convert a line-relative byte index into an absolutely-positioned byte index
         let rbx_to_abx = |rbx: RelByteIdx| -> AbsByteIdx {
-            raw.start + rbx
+            line_start_abx + rbx
         };
 
         let (first_non_ws_rbx, first_non_ws_abx) = {
@@ -309,7 +558,7 @@ impl<'doc> DefaultSpanner<'doc> {
         };
 
         // TODO: test escaped comment followed by actual comment
-        let opt_first_comment_start_bxs = line_txt.find('#')
+        let opt_first_comment_start_bxs = line_txt.find(options.comment_char)
             .map(|ridx| {
                 let rbx = ridx.into();
                 let abx = rbx_to_abx(rbx);
@@ -330,7 +579,7 @@ impl<'doc> DefaultSpanner<'doc> {
                     .map(|txt| txt.starts_with('\\'))
                     .unwrap_or(false);
 
-                if is_escaped_comment_start {
+                if is_escaped_comment_start && options.honor_escaped_comments {
                     return None;
                 }
 
@@ -352,7 +601,18 @@ impl<'doc> DefaultSpanner<'doc> {
 
             let find_end_ridx = find_end_abs_idx - line_start_abx.0;
             let line_txt_preceeding_comment = &line_txt[..find_end_ridx];
-            let opt_key_sep_start_rbx = line_txt_preceeding_comment.find(':')
+            #[allow(clippy::redundant_closure)]
+            let opt_key_sep_start_rbx = line_txt_preceeding_comment.match_indices(':')
+                .map(|(ridx, _)| ridx)
+                .find(|&ridx| match options.key_sep_style {
+                    KeySepStyle::FirstColon => true,
+                    KeySepStyle::ColonSpace => {
+                        let txt_after_colon = &line_txt_preceeding_comment[ridx + ':'.len_utf8()..];
+                        txt_after_colon.chars().next()
+                            .map(char::is_whitespace)
+                            .unwrap_or(true)
+                    },
+                })
                 .map(|ridx| RelByteIdx::from(ridx));
 
             if let Some(key_sep_start_rbx) = opt_key_sep_start_rbx {
@@ -414,7 +674,7 @@ impl<'doc> DefaultSpanner<'doc> {
             match (opt_value_start_ridx, opt_comment_span) {
                 (Some(value_start_ridx), Some(comment_span)) => {
                     let comment_start_abx = comment_span.start;
-                    let comment_start_ridx = comment_start_abx.0;
+                    let comment_start_ridx = comment_start_abx.0 - line_start_abx.0;
                     let value_start_abs_idx = line_start_abx.0 + value_start_ridx;
 
                     if value_start_ridx < comment_start_ridx {
@@ -442,30 +702,33 @@ impl<'doc> DefaultSpanner<'doc> {
 
 impl Spanner for DefaultSpanner<'_> {
     fn span_lines(&mut self) -> SpannedLines {
-        let lines = self.lines();
-        let spanned_lines = lines.into_iter()
-            .map(|raw_and_term| {
-                let line_txt = {
-                    let abs_start_idx = raw_and_term.raw.start.0;
+        self.spanned_lines().collect()
+    }
+}
 
-                    // end at whichever is first, line-term start or raw's end
-                    let abs_end_idx = raw_and_term.term.map(|term| term.start.0)
-                        .unwrap_or(raw_and_term.raw.end.0);
+impl<'doc> Iterator for SpannedLineIter<'doc> {
+    type Item = SpannedLine;
 
-                    self.text_at(abs_start_idx, abs_end_idx)
-                };
+    fn next(&mut self) -> Option<Self::Item> {
+        let raw_and_term = self.raw_and_terms.next()?;
 
-                self.componentize_line(raw_and_term, line_txt)
-            })
-            .collect::<Vec<_>>();
+        let line_txt = {
+            let abs_start_idx = raw_and_term.raw.start.0;
+
+            // end at whichever is first, line-term start or raw's end
+            let abs_end_idx = raw_and_term.logical_line_end_abx().0;
+
+            self.spanner.text_at(abs_start_idx, abs_end_idx)
+        };
 
-        spanned_lines
+        Some(self.spanner.componentize_line(raw_and_term, line_txt))
     }
 }
 
 // ----- tests -----------------------------------------------------------------
 
 #[cfg(test)]
+#[allow(clippy::useless_vec, clippy::useless_conversion)]
 mod tests {
     use super::*;
 
@@ -952,6 +1215,293 @@ mod tests {
         }
     }
 
+    #[test]
+    fn key_sep_followed_by_comment_has_no_value_on_any_line() {
+       // arrange
+       let doc = [
+           "a:#1\n",
+           "bb:#2\n",
+       ].join("");
+
+       // act
+       let actual_values = span_lines_of(&doc).into_iter()
+           .map(|line| line.value)
+           .collect::<Vec<_>>();
+
+       // assert
+       assert_eq!(
+           vec![None, None],
+           actual_values,
+       );
+    }
+
+    #[test]
+    fn iterator_is_lazy_and_matches_span_lines_of() {
+       // arrange
+       let doc = include_str!("../../../test-miniyaml-files/exploding-barrel.yaml");
+
+       // act
+       let mut iter = iter_spanned_lines_of(doc);
+       let first_line = iter.next();
+       let rest_lines = iter.collect::<Vec<_>>();
+
+       // assert
+       let expected_lines = span_lines_of(doc);
+       assert_eq!(expected_lines.first(), first_line.as_ref());
+       assert_eq!(&expected_lines[1..], &rest_lines[..]);
+    }
+
+    mod respan {
+        use {
+            super::*,
+            proptest::{
+                prelude::*,
+                sample::Index,
+            },
+        };
+
+        fn assert_respan_matches_full_span(doc_before: &str, edit: TextEdit) {
+            assert_respan_with_options_matches_full_span(doc_before, edit, SpannerOptions::default());
+        }
+
+        fn assert_respan_with_options_matches_full_span(
+            doc_before: &str,
+            edit: TextEdit,
+            options: SpannerOptions,
+        ) {
+            let doc_after = edit.apply_to(doc_before);
+            let mut actual_lines = DefaultSpanner::with_options(doc_before, options).span_lines();
+
+            respan_lines_with_options(&doc_after, &mut actual_lines, &edit, options);
+
+            assert_eq!(
+                DefaultSpanner::with_options(&doc_after, options).span_lines(),
+                actual_lines,
+                "doc before: {:?}, edit: {:?}, options: {:?}",
+                doc_before,
+                edit,
+                options,
+            );
+        }
+
+        fn options_strategy() -> impl Strategy<Value = SpannerOptions> {
+            let key_sep_styles = prop_oneof![
+                Just(KeySepStyle::FirstColon),
+                Just(KeySepStyle::ColonSpace),
+            ];
+
+            (prop_oneof![Just('#'), Just(';')], any::<bool>(), key_sep_styles, any::<bool>())
+                .prop_map(|(comment_char, honor_escaped_comments, key_sep_style, strip_bom)| SpannerOptions {
+                    comment_char,
+                    honor_escaped_comments,
+                    key_sep_style,
+                    strip_bom,
+                })
+        }
+
+        #[test]
+        fn inserting_lf_after_cr_merges_terminators() {
+            assert_respan_matches_full_span(
+                "a:\rb:\rc:\r",
+                TextEdit::new((3, 3), "\n"),
+            );
+        }
+
+        #[test]
+        fn deleting_lf_of_crlf_splits_terminators() {
+            assert_respan_matches_full_span(
+                "a:\r\nb: 1\r\nc:",
+                TextEdit::new((3, 4), ""),
+            );
+        }
+
+        #[test]
+        fn bom_after_the_first_line_is_not_stripped() {
+            assert_respan_matches_full_span(
+                "a:\nb:\n\u{FEFF}c: 1\nd:\n",
+                TextEdit::new((16, 16), "e"),
+            );
+        }
+
+        #[test]
+        fn replacing_entire_doc() {
+            assert_respan_matches_full_span(
+                "a:\n\tb: 1\n",
+                TextEdit::new((0, 9), ""),
+            );
+        }
+
+        proptest! {
+            #[test]
+            fn matches_full_span(
+                doc_before in "[a-c :#@^\\\\\t\r\n请-]{0,40}",
+                start_idx in any::<Index>(),
+                end_idx in any::<Index>(),
+                new_text in "[a :#\\\\\t\r\n请]{0,8}",
+            ) {
+                let char_boundaries = doc_before.char_indices()
+                    .map(|(abs_idx, _)| abs_idx)
+                    .chain(std::iter::once(doc_before.len()))
+                    .collect::<Vec<_>>();
+
+                let start = *start_idx.get(&char_boundaries);
+                let end = *end_idx.get(&char_boundaries);
+                let replaced = (start.min(end), start.max(end));
+
+                assert_respan_matches_full_span(
+                    &doc_before,
+                    TextEdit::new(replaced, new_text),
+                );
+            }
+
+            #[test]
+            fn matches_full_span_with_options(
+                doc_before in "[a-c :;#@^\\\\\t\r\n\u{FEFF}请-]{0,40}",
+                start_idx in any::<Index>(),
+                end_idx in any::<Index>(),
+                new_text in "[a :;#\\\\\t\r\n\u{FEFF}]{0,8}",
+                options in options_strategy(),
+            ) {
+                let char_boundaries = doc_before.char_indices()
+                    .map(|(abs_idx, _)| abs_idx)
+                    .chain(std::iter::once(doc_before.len()))
+                    .collect::<Vec<_>>();
+
+                let start = *start_idx.get(&char_boundaries);
+                let end = *end_idx.get(&char_boundaries);
+                let replaced = (start.min(end), start.max(end));
+
+                assert_respan_with_options_matches_full_span(
+                    &doc_before,
+                    TextEdit::new(replaced, new_text),
+                    options,
+                );
+            }
+        }
+    }
+
+    mod options {
+        use super::*;
+
+        fn component_texts_of(
+            doc: &str,
+            options: SpannerOptions,
+        ) -> Vec<(Option<&str>, Option<&str>, Option<&str>)> {
+            DefaultSpanner::with_options(doc, options).spanned_lines()
+                .map(|line| (
+                    line.key.map(|span| &doc[span]),
+                    line.value.map(|span| &doc[span]),
+                    line.comment.map(|span| &doc[span]),
+                ))
+                .collect()
+        }
+
+        #[test]
+        fn comment_char_and_escaping() {
+            // arrange
+            let doc = "a: \\; b\nc: d; e # f\n";
+            let options = SpannerOptions {
+                comment_char: ';',
+                honor_escaped_comments: false,
+                ..SpannerOptions::default()
+            };
+
+            // act
+            let actual = component_texts_of(doc, options);
+
+            // assert
+            assert_eq!(
+                vec![
+                    (Some("a"), Some("\\"), Some("; b")),
+                    (Some("c"), Some("d"), Some("; e # f")),
+                ],
+                actual,
+            );
+        }
+
+        #[test]
+        fn colon_space_key_sep() {
+            // arrange
+            let doc = "Foo:Bar: baz
+Qux:
+A:B
+";
+            let options = SpannerOptions {
+                key_sep_style: KeySepStyle::ColonSpace,
+                ..SpannerOptions::default()
+            };
+
+            // act
+            let actual = component_texts_of(doc, options);
+
+            // assert
+            assert_eq!(
+                vec![
+                    (Some("Foo:Bar"), Some("baz"), None),
+                    (Some("Qux"), None, None),
+                    (Some("A:B"), None, None),
+                ],
+                actual,
+            );
+        }
+
+        #[test]
+        fn strip_bom() {
+            // arrange
+            let doc = "\u{FEFF}# top\nFoo: bar\n";
+            let options = SpannerOptions {
+                strip_bom: false,
+                ..SpannerOptions::default()
+            };
+
+            // act
+            let stripped_lines = span_lines_of(doc);
+            let unstripped_lines = DefaultSpanner::with_options(doc, options).span_lines();
+
+            // assert
+            assert_eq!((0, 9), stripped_lines[0].raw.into());
+            assert_eq!("# top", &doc[stripped_lines[0].comment.unwrap()]);
+            assert!(stripped_lines[0].is_comment_only());
+            assert_eq!("\u{FEFF}", &doc[unstripped_lines[0].key.unwrap()]);
+        }
+
+        #[test]
+        fn bom_is_not_part_of_first_key() {
+            // arrange
+            let doc = "\u{FEFF}Foo: bar\n";
+
+            // act
+            let lines = span_lines_of(doc);
+
+            // assert
+            assert_eq!((3, 6), lines[0].key.unwrap().into());
+            assert!(lines[0].indent.is_none());
+        }
+    }
+
+    #[test]
+    fn is_comment_only() {
+       // arrange
+       let doc = [
+           "# top\n",
+           "\t  # indented\n",
+           "key: value # trailing\n",
+           "key: \\# escaped\n",
+           ": value # no key\n",
+       ].join("");
+
+       // act
+       let actual = span_lines_of(&doc).iter()
+           .map(SpannedLine::is_comment_only)
+           .collect::<Vec<_>>();
+
+       // assert
+       assert_eq!(
+           vec![true, true, false, false, false],
+           actual,
+       );
+    }
+
     #[test]
     #[allow(non_snake_case /* referencing types */)]
     fn AbsByteIdx_impls_Index_for_str_correctly() {
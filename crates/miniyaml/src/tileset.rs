@@ -0,0 +1,239 @@
+// oraide - tools for OpenRA-based mod/game development
+// get the source code at https://github.com/Phrohdoh/oraide
+//
+// copyright (c)
+// - 2020 Taryn "Phrohdoh" Hill
+
+#![deny(missing_docs)]
+
+//! This [module] exposes items used to read a tileset (e.g.
+//! `tilesets/temperat.yaml`), which defines the terrain types of a theater and
+//! the templates of tiles maps are painted with.
+//!
+//! [module]: https://doc.rust-lang.org/book/ch07-02-defining-modules-to-control-scope-and-privacy.html
+
+use {
+    std::collections::BTreeMap,
+    crate::{
+        parse_ints,
+        span_lines_of,
+        tree_of,
+        Node,
+        Tree,
+    },
+};
+
+// ----- public interface ------------------------------------------------------
+
+/// The terrain types and templates of a tileset.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct Tileset {
+    /// the `Id` of its `General` section, by which maps name the tileset
+    pub id: Option<String>,
+
+    /// the `Type` of each of its `TerrainType`s, in document order
+    pub terrain_types: Vec<String>,
+
+    /// its templates, keyed by their `Id`
+    pub templates: BTreeMap<u16, Template>,
+}
+
+/// A template (i.e. a group of tiles placed together) of a tileset.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct Template {
+    /// the `Id` of the template, by which `map.bin` files refer to it
+    pub id: u16,
+
+    /// width and height, in cells, of the template, if given (and neither is
+    /// negative)
+    pub size: Option<(i64, i64)>,
+
+    /// `true` if each of its tiles is a variant of a single cell, any of which
+    /// may be picked (rather than a part of a larger image)
+    pub is_pick_any: bool,
+
+    /// the terrain type of each of its tiles, keyed by the tile's index
+    pub tiles: BTreeMap<u8, String>,
+}
+
+impl Tileset {
+    /// Read the tileset `doc`.
+    pub fn new(doc: &str) -> Self {
+        let lines = span_lines_of(doc);
+        let tree = tree_of(doc, &lines);
+
+        Self::of_tree(doc, &tree)
+    }
+
+    /// Read the tileset `doc`, whose tree is `tree`.
+    pub fn of_tree(doc: &str, tree: &Tree) -> Self {
+        let sections_of = |key: &'static str| tree.nodes.iter()
+            .filter(move |node| doc[node.key].trim_end() == key)
+            .flat_map(|node| node.children.iter());
+
+        let id = sections_of("General")
+            .find(|child| doc[child.key].trim_end() == "Id")
+            .and_then(|child| child.value)
+            .map(|value_span| doc[value_span].trim_end().to_owned());
+
+        let terrain_types = sections_of("Terrain")
+            .filter_map(|terrain_type| value_txt_of(doc, terrain_type, "Type"))
+            .map(str::to_owned)
+            .collect();
+
+        let templates = sections_of("Templates")
+            .filter_map(|template| template_of(doc, template))
+            .map(|template| (template.id, template))
+            .collect();
+
+        Self {
+            id,
+            terrain_types,
+            templates,
+        }
+    }
+
+    /// `true` if any of its `TerrainType`s has the `Type` `name`.
+    pub fn is_terrain_type(&self, name: &str) -> bool {
+        self.terrain_types.iter().any(|terrain_type| terrain_type == name)
+    }
+}
+
+impl Template {
+    /// `true` if `index` is the index of one of its tiles, or is `255`, which
+    /// the engine replaces with a tile determined by the cell's position.
+    ///
+    /// The indices of templates without a `Size` (or one too large to count
+    /// the tiles of) or whose tiles may be picked from at random aren't
+    /// limited.
+    pub fn has_index(&self, index: u8) -> bool {
+        if index == u8::MAX || self.is_pick_any {
+            return true;
+        }
+
+        match self.size {
+            Some((width, height)) => width.checked_mul(height)
+                .is_none_or(|tile_count| i64::from(index) < tile_count),
+            None => true,
+        }
+    }
+}
+
+// ----- private implementation details ----------------------------------------
+
+/// the text, without trailing whitespace, of the value of `node`'s child `key`
+fn value_txt_of<'doc>(doc: &'doc str, node: &Node, key: &str) -> Option<&'doc str> {
+    node.child(doc, key)
+        .and_then(|child| child.value)
+        .map(|value_span| doc[value_span].trim_end())
+}
+
+/// the template defined by `node`, if it has a valid `Id`
+fn template_of(doc: &str, node: &Node) -> Option<Template> {
+    let id = value_txt_of(doc, node, "Id")?.parse().ok()?;
+
+    let size = value_txt_of(doc, node, "Size")
+        .and_then(parse_ints)
+        .and_then(|ints| match ints.as_slice() {
+            &[width, height] if width >= 0 && height >= 0 => Some((width, height)),
+            _ => None,
+        });
+
+    let is_pick_any = value_txt_of(doc, node, "PickAny")
+        .is_some_and(|txt| txt.eq_ignore_ascii_case("true"));
+
+    let tiles = node.child(doc, "Tiles")
+        .map(|tiles| tiles.children.iter()
+            .filter_map(|tile| {
+                let index = doc[tile.key].trim_end().parse().ok()?;
+                let terrain_type = doc[tile.value?].trim_end().to_owned();
+
+                Some((index, terrain_type))
+            })
+            .collect())
+        .unwrap_or_default();
+
+    Some(Template {
+        id,
+        size,
+        is_pick_any,
+        tiles,
+    })
+}
+
+// ----- tests -----------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_terrain_types_and_templates() {
+        // arrange
+        let doc = [
+            "General:\n",
+            "\tName: Temperate\n",
+            "\tId: TEMPERAT\n",
+            "Terrain:\n",
+            "\tTerrainType@Clear:\n",
+            "\t\tType: Clear\n",
+            "\tTerrainType@Water:\n",
+            "\t\tType: Water\n",
+            "Templates:\n",
+            "\tTemplate@255:\n",
+            "\t\tId: 255\n",
+            "\t\tSize: 1,1\n",
+            "\t\tPickAny: True\n",
+            "\t\tTiles:\n",
+            "\t\t\t0: Clear\n",
+            "\t\t\t1: Clear\n",
+            "\tTemplate@1:\n",
+            "\t\tId: 1\n",
+            "\t\tSize: 2,1\n",
+            "\t\tTiles:\n",
+            "\t\t\t0: Water\n",
+            "\t\t\t1: Clear\n",
+            "\tTemplate@Broken:\n",
+            "\t\tId: nope\n",
+        ].concat();
+
+        // act
+        let tileset = Tileset::new(&doc);
+
+        // assert
+        assert_eq!(Some("TEMPERAT"), tileset.id.as_deref());
+        assert!(tileset.is_terrain_type("Water"));
+        assert!(!tileset.is_terrain_type("Rough"));
+        assert_eq!(vec![&1, &255], tileset.templates.keys().collect::<Vec<_>>());
+
+        let template = &tileset.templates[&1];
+        assert_eq!(Some((2, 1)), template.size);
+        assert_eq!(Some("Water"), template.tiles.get(&0).map(String::as_str));
+        assert!(template.has_index(1));
+        assert!(!template.has_index(2));
+        assert!(template.has_index(255));
+        assert!(tileset.templates[&255].has_index(7));
+    }
+
+    #[test]
+    fn huge_and_negative_sizes() {
+        // arrange
+        let doc = [
+            "Templates:\n",
+            "\tTemplate@1:\n",
+            "\t\tId: 1\n",
+            "\t\tSize: 9999999999,9999999999\n",
+            "\tTemplate@2:\n",
+            "\t\tId: 2\n",
+            "\t\tSize: -1,-1\n",
+        ].concat();
+
+        // act
+        let tileset = Tileset::new(&doc);
+
+        // assert
+        assert!(tileset.templates[&1].has_index(254));
+        assert_eq!(None, tileset.templates[&2].size);
+        assert!(tileset.templates[&2].has_index(254));
+    }
+}
@@ -0,0 +1,255 @@
+// oraide - tools for OpenRA-based mod/game development
+// get the source code at https://github.com/Phrohdoh/oraide
+//
+// copyright (c)
+// - 2020 Taryn "Phrohdoh" Hill
+
+#![deny(missing_docs)]
+
+//! This [module] exposes items used to nest spanned-lines into a tree of
+//! [Node]s based on each line's indentation level.
+//!
+//! [module]: https://doc.rust-lang.org/book/ch07-02-defining-modules-to-control-scope-and-privacy.html
+//! [Node]: struct.Node.html
+
+use {
+    crate::{
+        AbsByteIdxSpan,
+        SpannedLine,
+    },
+};
+
+// ----- public interface ------------------------------------------------------
+
+/// The number of spaces which make up a single level of indentation
+/// (a tab is always a single level).
+pub const SPACES_PER_LEVEL: usize = 4;
+
+/// The top-level [`Node`]s of a MiniYaml document.
+///
+/// [`Node`]: struct.Node.html
+#[derive(Clone, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Tree {
+    /// nodes with no parent, in document order
+    pub nodes: Vec<Node>,
+}
+
+/// A keyed line of a MiniYaml document and the (more-indented) lines nested
+/// beneath it.
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Node {
+    /// index (into the spanned-lines this node was derived from) of the line
+    /// which defines this node
+    pub line_idx: usize,
+
+    /// index of the last line (inclusive) of this node's block, which is
+    /// `line_idx` if the node has no children
+    pub last_line_idx: usize,
+
+    /// indentation level of the defining line, see [`indent_level_of`]
+    ///
+    /// [`indent_level_of`]: fn.indent_level_of.html
+    pub level: usize,
+
+    /// span of the defining line's key
+    pub key: AbsByteIdxSpan,
+
+    /// span of the defining line's value, if it exists
+    pub value: Option<AbsByteIdxSpan>,
+
+    /// nodes nested beneath this one, in document order
+    pub children: Vec<Node>,
+}
+
+impl Tree {
+    /// Iterate over every node of the tree, parents before their children.
+    pub fn iter(&self) -> impl Iterator<Item = &Node> {
+        let mut stack = self.nodes.iter().rev().collect::<Vec<_>>();
+        std::iter::from_fn(move || {
+            let node = stack.pop()?;
+            stack.extend(node.children.iter().rev());
+            Some(node)
+        })
+    }
+}
+
+impl Node {
+    /// Find the first direct child whose key text (sans trailing whitespace)
+    /// is `key`.
+    pub fn child<'tree>(&'tree self, doc: &str, key: &str) -> Option<&'tree Node> {
+        self.children.iter()
+            .find(|child| doc[child.key].trim_end() == key)
+    }
+}
+
+/// Nest `lines`, which were spanned from `doc`, into a [`Tree`].
+///
+/// Lines without a key (empty, whitespace-only, and comment-only lines) do
+/// not produce nodes.
+///
+/// [`Tree`]: struct.Tree.html
+pub fn tree_of(doc: &str, lines: &[SpannedLine]) -> Tree {
+    // each entry is a node whose children are still being collected
+    let mut open_nodes: Vec<Node> = vec![];
+    let mut tree = Tree::default();
+
+    fn close(open_nodes: &mut Vec<Node>, tree: &mut Tree) {
+        let node = open_nodes.pop()
+            .expect("close called without an open node");
+
+        match open_nodes.last_mut() {
+            Some(parent) => {
+                parent.last_line_idx = node.last_line_idx;
+                parent.children.push(node);
+            },
+            None => tree.nodes.push(node),
+        }
+    }
+
+    for (line_idx, line) in lines.iter().enumerate() {
+        let key = match line.key {
+            Some(key) => key,
+            None => continue,
+        };
+
+        let level = indent_level_of(line.indent.map(|span| &doc[span]).unwrap_or(""));
+
+        // close every open node which is not a parent of this line
+        while let Some(open) = open_nodes.last() {
+            if open.level < level {
+                break;
+            }
+
+            close(&mut open_nodes, &mut tree);
+        }
+
+        open_nodes.push(Node {
+            line_idx,
+            last_line_idx: line_idx,
+            level,
+            key,
+            value: line.value,
+            children: vec![],
+        });
+    }
+
+    while !open_nodes.is_empty() {
+        close(&mut open_nodes, &mut tree);
+    }
+
+    tree
+}
+
+/// Compute the indentation level of `indent_txt`, in which
+/// each tab is a level, as is each run of [`SPACES_PER_LEVEL`] spaces.
+///
+/// [`SPACES_PER_LEVEL`]: constant.SPACES_PER_LEVEL.html
+pub fn indent_level_of(indent_txt: &str) -> usize {
+    let mut level = 0;
+    let mut spaces = 0;
+
+    for ch in indent_txt.chars() {
+        match ch {
+            '\t' => level += 1,
+            ' ' => {
+                spaces += 1;
+                if spaces == SPACES_PER_LEVEL {
+                    spaces = 0;
+                    level += 1;
+                }
+            },
+            _ => {},
+        }
+    }
+
+    level
+}
+
+// ----- tests -----------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::span_lines_of,
+    };
+
+    /// `(key text, line_idx, last_line_idx, children)`
+    #[derive(Debug, PartialEq)]
+    struct Shape<'doc>(&'doc str, usize, usize, Vec<Shape<'doc>>);
+
+    fn shape_of<'doc>(doc: &'doc str, nodes: &[Node]) -> Vec<Shape<'doc>> {
+        nodes.iter()
+            .map(|node| Shape(
+                doc[node.key].trim_end(),
+                node.line_idx,
+                node.last_line_idx,
+                shape_of(doc, &node.children),
+            ))
+            .collect()
+    }
+
+    #[test]
+    fn nests_by_indentation_level() {
+        // arrange
+        let doc = [
+            /* 0 */ "E2:\n",
+            /* 1 */ "    Inherits: ^Soldier\n",
+            /* 2 */ "\n",
+            /* 3 */ "\tValued:\n",
+            /* 4 */ "        # Cost: 300\n",
+            /* 5 */ "\t    Cost: 200\n",
+            /* 6 */ "barrel:\n",
+        ].join("");
+
+        let expected_shape = vec![
+            Shape("E2", 0, 5, vec![
+                Shape("Inherits", 1, 1, vec![]),
+                Shape("Valued", 3, 5, vec![
+                    Shape("Cost", 5, 5, vec![]),
+                ]),
+            ]),
+            Shape("barrel", 6, 6, vec![]),
+        ];
+
+        // act
+        let lines = span_lines_of(&doc);
+        let tree = tree_of(&doc, &lines);
+
+        // assert
+        assert_eq!(
+            expected_shape,
+            shape_of(&doc, &tree.nodes),
+        );
+    }
+
+    #[test]
+    fn iter_visits_parents_before_children() {
+        // arrange
+        let doc = "a:\n\tb:\n\t\tc:\n\td:\ne:\n";
+
+        // act
+        let lines = span_lines_of(doc);
+        let tree = tree_of(doc, &lines);
+        let actual_keys = tree.iter()
+            .map(|node| &doc[node.key])
+            .collect::<Vec<_>>();
+
+        // assert
+        assert_eq!(
+            vec!["a", "b", "c", "d", "e"],
+            actual_keys,
+        );
+    }
+
+    #[test]
+    fn indent_levels() {
+        assert_eq!(0, indent_level_of(""));
+        assert_eq!(0, indent_level_of("   "));
+        assert_eq!(1, indent_level_of("    "));
+        assert_eq!(2, indent_level_of("\t\t"));
+        assert_eq!(2, indent_level_of("  \t    "));
+    }
+}
@@ -0,0 +1,213 @@
+// oraide - tools for OpenRA-based mod/game development
+// get the source code at https://github.com/Phrohdoh/oraide
+//
+// copyright (c)
+// - 2020 Taryn "Phrohdoh" Hill
+
+#![deny(missing_docs)]
+
+//! This [module] exposes items used to determine what type of data a MiniYaml
+//! value holds.
+//!
+//! MiniYaml itself is untyped (every value is text), so this is a best guess
+//! based on the value's shape, the same way a human reading the file would.
+//!
+//! [module]: https://doc.rust-lang.org/book/ch07-02-defining-modules-to-control-scope-and-privacy.html
+
+use {
+    crate::{
+        AbsByteIdx,
+        AbsByteIdxSpan,
+    },
+};
+
+// ----- public interface ------------------------------------------------------
+
+/// The type of data a (single, non-list) value appears to hold.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ValueKind {
+    /// `true` or `false` (in any case)
+    Boolean,
+
+    /// e.g. `200` or `-5`
+    Integer,
+
+    /// e.g. `0.5` or `-1.25`
+    Decimal,
+
+    /// a world distance in cells and 1024ths of a cell, e.g. `5c512`
+    WDist,
+
+    /// anything else, e.g. `^Soldier` or `large-barrel-explode`
+    Text,
+}
+
+/// Determine the [`ValueKind`] of `value_txt` (surrounding whitespace is
+/// ignored).
+///
+/// [`ValueKind`]: enum.ValueKind.html
+pub fn kind_of_value(value_txt: &str) -> ValueKind {
+    let txt = value_txt.trim();
+
+    if txt.eq_ignore_ascii_case("true") || txt.eq_ignore_ascii_case("false") {
+        ValueKind::Boolean
+    } else if is_integer(txt) {
+        ValueKind::Integer
+    } else if is_decimal(txt) {
+        ValueKind::Decimal
+    } else if parse_wdist(txt).is_some() {
+        ValueKind::WDist
+    } else {
+        ValueKind::Text
+    }
+}
+
+/// Parse a world distance, either in the `<cells>c<subcells>` form or as a
+/// plain integer (of 1024ths of a cell), into 1024ths of a cell.
+///
+/// Distances which don't fit in an `i64` are `None`.
+pub fn parse_wdist(value_txt: &str) -> Option<i64> {
+    let txt = value_txt.trim();
+
+    if is_integer(txt) {
+        return txt.parse().ok();
+    }
+
+    let (is_negative, unsigned_txt) = match txt.strip_prefix('-') {
+        Some(unsigned_txt) => (true, unsigned_txt),
+        None => (false, txt),
+    };
+
+    let c_idx = unsigned_txt.find('c')?;
+    let (cells_txt, subcells_txt) = (&unsigned_txt[..c_idx], &unsigned_txt[c_idx + 1..]);
+
+    if !is_digits(cells_txt) || !is_digits(subcells_txt) {
+        return None;
+    }
+
+    let cells = cells_txt.parse::<i64>().ok()?;
+    let subcells = subcells_txt.parse::<i64>().ok()?;
+    let units = cells.checked_mul(1024)?.checked_add(subcells)?;
+
+    Some(if is_negative { -units } else { units })
+}
+
+/// Parse a comma-separated list of integers (e.g. a `Location` of `12,34`).
+pub fn parse_ints(value_txt: &str) -> Option<Vec<i64>> {
+    value_txt.split(',')
+        .map(|item_txt| {
+            let item_txt = item_txt.trim();
+            if is_integer(item_txt) { item_txt.parse().ok() } else { None }
+        })
+        .collect()
+}
+
+/// Split the value spanned by `value_span` in `doc` into the spans of its
+/// comma-separated items, without surrounding whitespace (a value without a
+/// comma is a single item).
+///
+/// Empty items (as in `a,,b`) are skipped.
+pub fn list_items_of(doc: &str, value_span: AbsByteIdxSpan) -> Vec<AbsByteIdxSpan> {
+    let value_start_abs_idx = value_span.start.0;
+    let mut items = vec![];
+    let mut item_start_rel_idx = 0;
+
+    let value_txt = &doc[value_span];
+    let item_ends = value_txt.match_indices(',')
+        .map(|(rel_idx, _)| rel_idx)
+        .chain(std::iter::once(value_txt.len()));
+
+    for item_end_rel_idx in item_ends {
+        let item_txt = &value_txt[item_start_rel_idx..item_end_rel_idx];
+        let trimmed_start_txt = item_txt.trim_start();
+        let trimmed_txt = trimmed_start_txt.trim_end();
+
+        if !trimmed_txt.is_empty() {
+            let start_rel_idx = item_start_rel_idx + (item_txt.len() - trimmed_start_txt.len());
+            let end_rel_idx = start_rel_idx + trimmed_txt.len();
+
+            items.push(AbsByteIdxSpan::from((
+                AbsByteIdx(value_start_abs_idx + start_rel_idx),
+                AbsByteIdx(value_start_abs_idx + end_rel_idx),
+            )));
+        }
+
+        item_start_rel_idx = item_end_rel_idx + ','.len_utf8();
+    }
+
+    items
+}
+
+// ----- private implementation details ----------------------------------------
+
+fn is_digits(txt: &str) -> bool {
+    !txt.is_empty() && txt.bytes().all(|b| b.is_ascii_digit())
+}
+
+fn is_integer(txt: &str) -> bool {
+    is_digits(txt.strip_prefix('-').unwrap_or(txt))
+}
+
+fn is_decimal(txt: &str) -> bool {
+    let unsigned_txt = txt.strip_prefix('-').unwrap_or(txt);
+    match unsigned_txt.find('.') {
+        Some(dot_idx) => is_digits(&unsigned_txt[..dot_idx])
+            && is_digits(&unsigned_txt[dot_idx + 1..]),
+        None => false,
+    }
+}
+
+// ----- tests -----------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn value_kinds() {
+        assert_eq!(ValueKind::Boolean, kind_of_value(" True "));
+        assert_eq!(ValueKind::Integer, kind_of_value("-200"));
+        assert_eq!(ValueKind::Decimal, kind_of_value("0.75"));
+        assert_eq!(ValueKind::WDist, kind_of_value("5c512"));
+        assert_eq!(ValueKind::Text, kind_of_value("^Soldier"));
+        assert_eq!(ValueKind::Text, kind_of_value("c4"));
+        assert_eq!(ValueKind::Text, kind_of_value("1.-5"));
+    }
+
+    #[test]
+    fn wdists() {
+        assert_eq!(Some(5 * 1024 + 512), parse_wdist("5c512"));
+        assert_eq!(Some(-1024), parse_wdist("-1c0"));
+        assert_eq!(Some(768), parse_wdist("768"));
+        assert_eq!(None, parse_wdist("5c"));
+        assert_eq!(None, parse_wdist("c5"));
+        assert_eq!(None, parse_wdist("99999999999999999c0"));
+        assert_eq!(None, parse_wdist("9007199254740991c1024"));
+    }
+
+    #[test]
+    fn ints() {
+        assert_eq!(Some(vec![12, -3]), parse_ints("12, -3"));
+        assert_eq!(Some(vec![7]), parse_ints("7"));
+        assert_eq!(None, parse_ints("1,,2"));
+        assert_eq!(None, parse_ints("1,2c0"));
+    }
+
+    #[test]
+    fn list_items_are_trimmed() {
+        // arrange
+        let doc = "Categories: props,  dangerous-props ,,x ";
+        let value_span = (12, doc.len()).into();
+
+        // act
+        let actual_items = list_items_of(doc, value_span).into_iter()
+            .map(|span| &doc[span])
+            .collect::<Vec<_>>();
+
+        // assert
+        assert_eq!(
+            vec!["props", "dangerous-props", "x"],
+            actual_items,
+        );
+    }
+}
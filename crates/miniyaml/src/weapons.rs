@@ -0,0 +1,190 @@
+// oraide - tools for OpenRA-based mod/game development
+// get the source code at https://github.com/Phrohdoh/oraide
+//
+// copyright (c)
+// - 2020 Taryn "Phrohdoh" Hill
+
+#![deny(missing_docs)]
+
+//! This [module] exposes items used to read a weapon (a top-level node of a
+//! weapons file), once its definitions have been merged and its `Inherits`
+//! resolved by [`Rules`]:
+//!
+//! ```text
+//! 25mm:
+//!     ReloadDelay: 21
+//!     Range: 4c0
+//!     Projectile: Bullet
+//!         Speed: 1c682
+//!     Warhead@1Dam: SpreadDamage
+//!         Damage: 2500
+//! ```
+//!
+//! [module]: https://doc.rust-lang.org/book/ch07-02-defining-modules-to-control-scope-and-privacy.html
+//! [`Rules`]: struct.Rules.html
+
+use {
+    crate::{
+        parse_wdist,
+        Origin,
+        ResolveError,
+        RuleNode,
+        Rules,
+    },
+};
+
+// ----- public interface ------------------------------------------------------
+
+/// A weapon, with its `Inherits` resolved.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Weapon {
+    /// the weapon's name (e.g. `25mm`)
+    pub name: String,
+
+    /// its fields other than its projectile and warheads (e.g. `Range`)
+    pub fields: Vec<WeaponField>,
+
+    /// its `Projectile`, if it has one
+    pub projectile: Option<WeaponPart>,
+
+    /// its `Warhead`s, in order
+    pub warheads: Vec<WeaponPart>,
+}
+
+/// A field of a weapon, projectile, or warhead.
+#[derive(Clone, PartialEq, Debug)]
+pub struct WeaponField {
+    /// the field's key
+    pub key: String,
+
+    /// the field's value, or an empty string if it has none
+    pub value: String,
+
+    /// where the field's value was defined
+    pub origin: Origin,
+}
+
+/// The projectile or a warhead of a weapon, whose value is its type.
+#[derive(Clone, PartialEq, Debug)]
+pub struct WeaponPart {
+    /// the part's key (e.g. `Warhead@1Dam`)
+    pub key: String,
+
+    /// the part's type (e.g. `SpreadDamage`), if given
+    pub type_name: Option<String>,
+
+    /// where the part's type was defined
+    pub origin: Origin,
+
+    /// the part's fields
+    pub fields: Vec<WeaponField>,
+}
+
+impl Weapon {
+    /// Resolve the weapon `name` of `weapons` (the merged weapons files of a
+    /// mod).
+    pub fn resolve(weapons: &Rules, name: &str) -> Result<Self, ResolveError> {
+        let weapon = weapons.resolve(name)?;
+
+        let projectile = weapon.children.iter()
+            .find(|child| child.key == "Projectile")
+            .map(part_of);
+
+        let warheads = weapon.children.iter()
+            .filter(|child| child.key == "Warhead" || child.key.starts_with("Warhead@"))
+            .map(part_of)
+            .collect();
+
+        let fields = weapon.children.iter()
+            .filter(|child| child.key != "Projectile" && child.key != "Warhead" && !child.key.starts_with("Warhead@"))
+            .map(field_of)
+            .collect();
+
+        Ok(Self {
+            name: name.to_owned(),
+            fields,
+            projectile,
+            warheads,
+        })
+    }
+
+    /// The field `key`, if the weapon sets it.
+    pub fn field(&self, key: &str) -> Option<&WeaponField> {
+        self.fields.iter().find(|field| field.key == key)
+    }
+
+    /// The weapon's `Range`, in 1024ths of a cell, if it sets a valid one.
+    pub fn range(&self) -> Option<i64> {
+        self.field("Range").and_then(|field| parse_wdist(&field.value))
+    }
+
+    /// The weapon's `ReloadDelay`, in ticks, if it sets a valid one.
+    pub fn reload_delay(&self) -> Option<i64> {
+        self.field("ReloadDelay").and_then(|field| field.value.parse().ok())
+    }
+}
+
+// ----- private implementation details ----------------------------------------
+
+fn field_of(node: &RuleNode) -> WeaponField {
+    WeaponField {
+        key: node.key.clone(),
+        value: node.value.clone().unwrap_or_default(),
+        origin: node.origin.clone(),
+    }
+}
+
+fn part_of(node: &RuleNode) -> WeaponPart {
+    WeaponPart {
+        key: node.key.clone(),
+        type_name: node.value.clone(),
+        origin: node.origin.clone(),
+        fields: node.children.iter().map(field_of).collect(),
+    }
+}
+
+// ----- tests -----------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_parts_and_inheritance() {
+        // arrange
+        let mut weapons = Rules::new();
+        weapons.add_file("weapons.yaml", &[
+            "^Cannon:\n",
+            "\tReloadDelay: 50\n",
+            "\tRange: 4c768\n",
+            "\tProjectile: Bullet\n",
+            "\t\tSpeed: 682\n",
+            "\tWarhead@1Dam: SpreadDamage\n",
+            "\t\tDamage: 4000\n",
+            "\tWarhead@2Eff: CreateEffect\n",
+            "25mm:\n",
+            "\tInherits: ^Cannon\n",
+            "\tReloadDelay: 21\n",
+            "\t-Warhead@2Eff:\n",
+        ].concat());
+
+        // act
+        let weapon = Weapon::resolve(&weapons, "25mm").unwrap();
+
+        // assert
+        assert_eq!((Some(4 * 1024 + 768), Some(21)), (weapon.range(), weapon.reload_delay()));
+
+        let projectile = weapon.projectile.as_ref().unwrap();
+        assert_eq!(Some("Bullet"), projectile.type_name.as_deref());
+        assert_eq!(vec!["Speed"], projectile.fields.iter().map(|field| field.key.as_str()).collect::<Vec<_>>());
+
+        assert_eq!(
+            vec![("Warhead@1Dam", Some("SpreadDamage"))],
+            weapon.warheads.iter()
+                .map(|warhead| (warhead.key.as_str(), warhead.type_name.as_deref()))
+                .collect::<Vec<_>>(),
+        );
+
+        assert_eq!(11, weapon.field("ReloadDelay").unwrap().origin.line_idx + 1);
+    }
+}